@@ -1,10 +1,14 @@
+mod background_tasks_panel;
+
 use auto_update::{AutoUpdateStatus, AutoUpdater, DismissMessage, VersionCheckType};
+use db::{kvp::KEY_VALUE_STORE, write_and_log};
 use editor::Editor;
 use extension_host::{ExtensionOperation, ExtensionStore};
 use futures::StreamExt;
 use gpui::{
-    App, Context, CursorStyle, Entity, EventEmitter, InteractiveElement as _, ParentElement as _,
-    Render, SharedString, StatefulInteractiveElement, Styled, Window, actions,
+    Action, App, ClipboardItem, Context, CursorStyle, DismissEvent, Entity, EventEmitter,
+    Focusable, InteractiveElement as _, ParentElement as _, Render, SharedString,
+    StatefulInteractiveElement, Styled, Task, WeakEntity, Window, actions,
 };
 use language::{
     BinaryStatus, LanguageRegistry, LanguageServerId, LanguageServerName,
@@ -15,10 +19,12 @@ use project::{
     ProjectEnvironmentEvent,
     git_store::{GitStoreEvent, Repository},
 };
+use schemars::JsonSchema;
+use serde::Deserialize;
 use smallvec::SmallVec;
 use std::{
     cmp::Reverse,
-    collections::HashSet,
+    collections::{BTreeMap, HashSet},
     fmt::Write,
     path::Path,
     sync::Arc,
@@ -28,19 +34,35 @@ use ui::{
     ButtonLike, CommonAnimationExt, ContextMenu, PopoverMenu, PopoverMenuHandle, Tooltip,
     prelude::*,
 };
-use util::truncate_and_trailoff;
+use util::{ResultExt as _, truncate_and_trailoff};
 use workspace::{StatusItemView, Workspace, item::ItemHandle};
 
+pub use background_tasks_panel::BackgroundTasksPanel;
+
 const GIT_OPERATION_DELAY: Duration = Duration::from_millis(0);
+const NOTIFY_THROTTLE_INTERVAL: Duration = Duration::from_millis(16);
+const MAX_PENDING_WORK_IN_MENU: usize = 10;
+const FAILED_SERVERS_KEY: &str = "activity_indicator_failed_servers";
 
 actions!(
     activity_indicator,
     [
         /// Displays error messages from language servers in the status bar.
-        ShowErrorMessage
+        ShowErrorMessage,
+        /// Copies the error messages of failed language servers to the clipboard.
+        CopyErrorMessage,
+        /// Opens a panel listing all pending background tasks.
+        OpenBackgroundTasks,
+        /// Toggles focus on the background tasks panel.
+        ToggleBackgroundTasksFocus
     ]
 );
 
+/// Hides progress in the status bar while paused. Failures are still shown.
+#[derive(Clone, PartialEq, Debug, Deserialize, JsonSchema, Action)]
+#[action(namespace = activity_indicator)]
+pub struct SetActivityIndicatorPaused(pub bool);
+
 pub enum Event {
     ShowStatus {
         server_name: LanguageServerName,
@@ -50,9 +72,16 @@ pub enum Event {
 
 pub struct ActivityIndicator {
     statuses: Vec<ServerStatus>,
+    /// Failures persisted by an earlier session that haven't been dismissed yet.
+    previous_session_failures: BTreeMap<LanguageServerName, String>,
+    persisted_failures: BTreeMap<LanguageServerName, String>,
+    /// The key-value store key under which this workspace's failures are persisted.
+    failures_key: Option<String>,
     project: Entity<Project>,
     auto_updater: Option<Entity<AutoUpdater>>,
     context_menu_handle: PopoverMenuHandle<ContextMenu>,
+    pending_notify: Option<Task<()>>,
+    paused: bool,
 }
 
 #[derive(Debug)]
@@ -84,6 +113,7 @@ impl ActivityIndicator {
     ) -> Entity<ActivityIndicator> {
         let project = workspace.project().clone();
         let auto_updater = AutoUpdater::get(cx);
+        let failures_key = Self::failures_key(workspace);
         let this = cx.new(|cx| {
             let mut status_events = languages.language_server_binary_statuses();
             cx.spawn(async move |this, cx| {
@@ -94,7 +124,8 @@ impl ActivityIndicator {
                             name,
                             status: LanguageServerStatusUpdate::Binary(binary_status),
                         });
-                        cx.notify();
+                        this.persist_failures(cx);
+                        this.notify_throttled(cx);
                     })?;
                 }
                 anyhow::Ok(())
@@ -170,8 +201,9 @@ impl ActivityIndicator {
                             activity_indicator
                                 .statuses
                                 .push(ServerStatus { name, status });
+                            activity_indicator.persist_failures(cx);
                         }
-                        cx.notify()
+                        activity_indicator.notify_throttled(cx)
                     }
                 },
             )
@@ -179,17 +211,19 @@ impl ActivityIndicator {
 
             cx.subscribe(
                 &project.read(cx).environment().clone(),
-                |_, _, event, cx| match event {
-                    ProjectEnvironmentEvent::ErrorsUpdated => cx.notify(),
+                |activity_indicator, _, event, cx| match event {
+                    ProjectEnvironmentEvent::ErrorsUpdated => {
+                        activity_indicator.notify_throttled(cx)
+                    }
                 },
             )
             .detach();
 
             cx.subscribe(
                 &project.read(cx).git_store().clone(),
-                |_, _, event: &GitStoreEvent, cx| {
+                |activity_indicator, _, event: &GitStoreEvent, cx| {
                     if let project::git_store::GitStoreEvent::JobsUpdated = event {
-                        cx.notify()
+                        activity_indicator.notify_throttled(cx)
                     }
                 },
             )
@@ -199,14 +233,36 @@ impl ActivityIndicator {
                 cx.observe(auto_updater, |_, _, cx| cx.notify()).detach();
             }
 
+            let previous_session_failures = failures_key
+                .as_deref()
+                .map(read_persisted_failures)
+                .unwrap_or_default();
             Self {
                 statuses: Vec::new(),
+                persisted_failures: previous_session_failures.clone(),
+                previous_session_failures,
+                failures_key,
                 project: project.clone(),
                 auto_updater,
                 context_menu_handle: Default::default(),
+                pending_notify: None,
+                paused: false,
             }
         });
 
+        let activity_indicator = this.downgrade();
+        workspace.register_action(move |workspace, _: &OpenBackgroundTasks, window, cx| {
+            Self::add_background_tasks_panel(workspace, &activity_indicator, window, cx);
+            workspace.focus_panel::<BackgroundTasksPanel>(window, cx);
+        });
+        let activity_indicator = this.downgrade();
+        workspace.register_action(
+            move |workspace, _: &ToggleBackgroundTasksFocus, window, cx| {
+                Self::add_background_tasks_panel(workspace, &activity_indicator, window, cx);
+                workspace.toggle_panel_focus::<BackgroundTasksPanel>(window, cx);
+            },
+        );
+
         cx.subscribe_in(&this, window, move |_, _, event, window, cx| match event {
             Event::ShowStatus {
                 server_name,
@@ -249,6 +305,36 @@ impl ActivityIndicator {
         this
     }
 
+    /// Coalesces the notifications of language servers, environments and git jobs, which can
+    /// report progress many times per frame, into at most one per frame.
+    fn notify_throttled(&mut self, cx: &mut Context<Self>) {
+        if self.pending_notify.is_some() {
+            return;
+        }
+        self.pending_notify = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor()
+                .timer(NOTIFY_THROTTLE_INTERVAL)
+                .await;
+            this.update(cx, |this, cx| {
+                this.pending_notify = None;
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    fn add_background_tasks_panel(
+        workspace: &mut Workspace,
+        activity_indicator: &WeakEntity<ActivityIndicator>,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        if workspace.panel::<BackgroundTasksPanel>(cx).is_none() {
+            let panel = BackgroundTasksPanel::new(workspace, activity_indicator.clone(), cx);
+            workspace.add_panel(panel, window, cx);
+        }
+    }
+
     fn show_error_message(&mut self, _: &ShowErrorMessage, _: &mut Window, cx: &mut Context<Self>) {
         let mut status_message_shown = false;
         self.statuses.retain(|status| match &status.status {
@@ -278,6 +364,98 @@ impl ActivityIndicator {
             },
             _ => true,
         });
+        if !status_message_shown
+            && let Some((server_name, error)) = self.previous_session_failures.pop_first()
+        {
+            cx.emit(Event::ShowStatus {
+                server_name,
+                status: SharedString::from(error),
+            });
+        }
+        self.persist_failures(cx);
+    }
+
+    fn copy_error_message(&mut self, _: &CopyErrorMessage, _: &mut Window, cx: &mut Context<Self>) {
+        let error_message = self
+            .undismissed_failures()
+            .into_iter()
+            .map(|(name, error)| format!("Language server {name}:\n\n{error}"))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        if error_message.is_empty() {
+            return;
+        }
+        cx.write_to_clipboard(ClipboardItem::new_string(error_message));
+        cx.notify();
+    }
+
+    fn show_error_message_for_server(
+        &mut self,
+        server_name: &LanguageServerName,
+        cx: &mut Context<Self>,
+    ) {
+        let error = failed_servers(&self.statuses)
+            .find(|(name, _)| *name == server_name)
+            .map(|(_, error)| error)
+            .or_else(|| {
+                self.previous_session_failures
+                    .get(server_name)
+                    .map(String::as_str)
+            });
+        if let Some(error) = error {
+            cx.emit(Event::ShowStatus {
+                server_name: server_name.clone(),
+                status: SharedString::from(error.to_string()),
+            });
+        }
+        self.dismiss_failed_server(server_name, cx);
+    }
+
+    fn dismiss_failed_server(&mut self, server_name: &LanguageServerName, cx: &mut Context<Self>) {
+        clear_server_failure(&mut self.statuses, server_name);
+        self.previous_session_failures.remove(server_name);
+        self.persist_failures(cx);
+        cx.notify();
+    }
+
+    fn failures_key(workspace: &Workspace) -> Option<String> {
+        workspace
+            .database_id()
+            .map(|id| i64::from(id).to_string())
+            .or(workspace.session_id())
+            .map(|id| format!("{}-{:?}", FAILED_SERVERS_KEY, id))
+    }
+
+    fn undismissed_failures(&self) -> BTreeMap<LanguageServerName, String> {
+        let mut failures = self.previous_session_failures.clone();
+        failures.extend(
+            failed_servers(&self.statuses).map(|(name, error)| (name.clone(), error.to_string())),
+        );
+        failures
+    }
+
+    /// Keeps the on-disk failures in sync with the undismissed ones, so that they can be shown
+    /// again after a restart.
+    fn persist_failures(&mut self, cx: &mut Context<Self>) {
+        let Some(failures_key) = self.failures_key.clone() else {
+            return;
+        };
+        let failures = self.undismissed_failures();
+        if failures == self.persisted_failures {
+            return;
+        }
+        self.persisted_failures = failures.clone();
+        write_and_log(cx, move || write_persisted_failures(failures_key, failures));
+    }
+
+    fn set_paused(
+        &mut self,
+        action: &SetActivityIndicatorPaused,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.paused = action.0;
+        cx.notify();
     }
 
     fn dismiss_message(&mut self, _: &DismissMessage, _: &mut Window, cx: &mut Context<Self>) {
@@ -290,6 +468,12 @@ impl ActivityIndicator {
             return;
         }
 
+        if !self.previous_session_failures.is_empty() {
+            self.previous_session_failures.clear();
+            self.persist_failures(cx);
+            cx.notify();
+        }
+
         self.project.update(cx, |project, cx| {
             if project.last_formatting_failure(cx).is_some() {
                 project.reset_last_formatting_failure(cx);
@@ -304,28 +488,15 @@ impl ActivityIndicator {
         &self,
         cx: &'a App,
     ) -> impl Iterator<Item = PendingWork<'a>> {
-        self.project
-            .read(cx)
-            .language_server_statuses(cx)
-            .rev()
-            .filter_map(|(server_id, status)| {
-                if status.pending_work.is_empty() {
-                    None
-                } else {
-                    let mut pending_work = status
-                        .pending_work
-                        .iter()
-                        .map(|(token, progress)| PendingWork {
-                            language_server_id: server_id,
-                            progress_token: token.as_str(),
-                            progress,
-                        })
-                        .collect::<SmallVec<[_; 4]>>();
-                    pending_work.sort_by_key(|work| Reverse(work.progress.last_update_at));
-                    Some(pending_work)
-                }
-            })
-            .flatten()
+        pending_language_server_work(self.project.read(cx), cx)
+    }
+
+    fn pending_language_server_work_top<'a>(
+        &self,
+        count: usize,
+        cx: &'a App,
+    ) -> Vec<PendingWork<'a>> {
+        most_recent_pending_work(self.pending_language_server_work(cx), count)
     }
 
     fn pending_environment_errors<'a>(
@@ -335,7 +506,11 @@ impl ActivityIndicator {
         self.project.read(cx).shell_environment_errors(cx)
     }
 
-    fn content_to_render(&mut self, cx: &mut Context<Self>) -> Option<Content> {
+    fn content_to_render(
+        &mut self,
+        max_message_len: usize,
+        cx: &mut Context<Self>,
+    ) -> Option<Content> {
         // Show if any direnv calls failed
         if let Some((abs_path, error)) = self.pending_environment_errors(cx).next() {
             let abs_path = abs_path.clone();
@@ -356,34 +531,11 @@ impl ActivityIndicator {
             });
         }
         // Show any language server has pending activity.
-        {
-            let mut pending_work = self.pending_language_server_work(cx);
-            if let Some(PendingWork {
-                progress_token,
-                progress,
-                ..
-            }) = pending_work.next()
-            {
-                let mut message = progress
-                    .title
-                    .as_deref()
-                    .unwrap_or(progress_token)
-                    .to_string();
-
-                if let Some(percentage) = progress.percentage {
-                    write!(&mut message, " ({}%)", percentage).unwrap();
-                }
-
-                if let Some(progress_message) = progress.message.as_ref() {
-                    message.push_str(": ");
-                    message.push_str(progress_message);
-                }
-
-                let additional_work_count = pending_work.count();
-                if additional_work_count > 0 {
-                    write!(&mut message, " + {} more", additional_work_count).unwrap();
-                }
-
+        if !self.paused {
+            let pending_work = self
+                .pending_language_server_work(cx)
+                .collect::<SmallVec<[_; 4]>>();
+            if let Some(message) = pending_work_message(&pending_work) {
                 return Some(Content {
                     icon: Some(
                         Icon::new(IconName::ArrowCircle)
@@ -392,19 +544,21 @@ impl ActivityIndicator {
                             .into_any_element(),
                     ),
                     message,
-                    on_click: Some(Arc::new(Self::toggle_language_server_work_context_menu)),
+                    // The popover's trigger opens the menu listing the pending work.
+                    on_click: None,
                     tooltip_message: None,
                 });
             }
         }
 
-        if let Some(session) = self
-            .project
-            .read(cx)
-            .dap_store()
-            .read(cx)
-            .sessions()
-            .find(|s| !s.read(cx).is_started())
+        if !self.paused
+            && let Some(session) = self
+                .project
+                .read(cx)
+                .dap_store()
+                .read(cx)
+                .sessions()
+                .find(|s| !s.read(cx).is_started())
         {
             return Some(Content {
                 icon: Some(
@@ -426,7 +580,8 @@ impl ActivityIndicator {
             .map(|r| r.read(cx))
             .and_then(Repository::current_job);
         // Show any long-running git command
-        if let Some(job_info) = current_job
+        if !self.paused
+            && let Some(job_info) = current_job
             && Instant::now() - job_info.start >= GIT_OPERATION_DELAY
         {
             return Some(Content {
@@ -437,7 +592,9 @@ impl ActivityIndicator {
                         .into_any_element(),
                 ),
                 message: job_info.message.into(),
-                on_click: None,
+                on_click: Some(Arc::new(|_, window, cx| {
+                    window.dispatch_action(Box::new(zed_actions::git_panel::ToggleFocus), cx);
+                })),
                 tooltip_message: None,
             });
         }
@@ -485,7 +642,7 @@ impl ActivityIndicator {
             ServerHealth::Ok => 0,
         });
 
-        if !downloading.is_empty() {
+        if !self.paused && !downloading.is_empty() {
             return Some(Content {
                 icon: Some(
                     Icon::new(IconName::Download)
@@ -514,7 +671,7 @@ impl ActivityIndicator {
             });
         }
 
-        if !checking_for_update.is_empty() {
+        if !self.paused && !checking_for_update.is_empty() {
             return Some(Content {
                 icon: Some(
                     Icon::new(IconName::Download)
@@ -563,8 +720,31 @@ impl ActivityIndicator {
                             acc
                         }),
                 ),
-                on_click: Some(Arc::new(|this, window, cx| {
-                    this.show_error_message(&ShowErrorMessage, window, cx)
+                // Several failures are listed by the menu the popover's trigger opens.
+                on_click: if failed.len() == 1 {
+                    Some(Arc::new(|this, window, cx| {
+                        this.show_error_message(&ShowErrorMessage, window, cx)
+                    }))
+                } else {
+                    None
+                },
+                tooltip_message: None,
+            });
+        }
+
+        if let Some(server_name) = self.previous_session_failures.keys().next() {
+            let server_name = server_name.clone();
+            return Some(Content {
+                icon: Some(
+                    Icon::new(IconName::Warning)
+                        .size(IconSize::Small)
+                        .into_any_element(),
+                ),
+                message: format!(
+                    "Failed to run {server_name} in the previous session. Click to show error."
+                ),
+                on_click: Some(Arc::new(move |this, _, cx| {
+                    this.show_error_message_for_server(&server_name, cx)
                 })),
                 tooltip_message: None,
             });
@@ -590,7 +770,9 @@ impl ActivityIndicator {
         }
 
         // Show any health messages for the language servers
-        if let Some((server_name, health, message)) = health_messages.pop() {
+        if let Some((server_name, health, message)) = health_messages.pop()
+            && (!self.paused || health != ServerHealth::Ok)
+        {
             let health_str = match health {
                 ServerHealth::Ok => format!("({server_name}) "),
                 ServerHealth::Warning => format!("({server_name}) Warning: "),
@@ -607,7 +789,7 @@ impl ActivityIndicator {
             let mut altered_message = single_line_message != message;
             let truncated_message = truncate_and_trailoff(
                 &single_line_message,
-                MAX_MESSAGE_LEN.saturating_sub(health_str.len()),
+                max_message_len.saturating_sub(health_str.chars().count()),
             );
             altered_message |= truncated_message != single_line_message;
             let final_message = format!("{health_str}{truncated_message}");
@@ -640,8 +822,12 @@ impl ActivityIndicator {
         }
 
         // Show any application auto-update info.
+        let paused = self.paused;
         self.auto_updater
             .as_ref()
+            .filter(|updater| {
+                !paused || matches!(updater.read(cx).status(), AutoUpdateStatus::Errored { .. })
+            })
             .and_then(|updater| match &updater.read(cx).status() {
                 AutoUpdateStatus::Checking => Some(Content {
                     icon: Some(
@@ -656,13 +842,24 @@ impl ActivityIndicator {
                     })),
                     tooltip_message: None,
                 }),
-                AutoUpdateStatus::Downloading { version } => Some(Content {
+                AutoUpdateStatus::Downloading {
+                    version,
+                    downloaded_bytes,
+                    total_bytes,
+                } => Some(Content {
                     icon: Some(
                         Icon::new(IconName::Download)
                             .size(IconSize::Small)
                             .into_any_element(),
                     ),
-                    message: "Downloading Zed update…".to_string(),
+                    message: if *downloaded_bytes > 0 {
+                        format!(
+                            "Downloading Zed update… {}",
+                            download_progress_message(*downloaded_bytes, *total_bytes)
+                        )
+                    } else {
+                        "Downloading Zed update…".to_string()
+                    },
                     on_click: Some(Arc::new(|this, window, cx| {
                         this.dismiss_message(&DismissMessage, window, cx)
                     })),
@@ -703,8 +900,9 @@ impl ActivityIndicator {
                 AutoUpdateStatus::Idle => None,
             })
             .or_else(|| {
-                if let Some(extension_store) =
-                    ExtensionStore::try_global(cx).map(|extension_store| extension_store.read(cx))
+                if !paused
+                    && let Some(extension_store) = ExtensionStore::try_global(cx)
+                        .map(|extension_store| extension_store.read(cx))
                     && let Some((extension_id, operation)) =
                         extension_store.outstanding_operations().iter().next()
                 {
@@ -757,30 +955,364 @@ impl ActivityIndicator {
         })
     }
 
-    fn toggle_language_server_work_context_menu(
-        &mut self,
+    fn cancel_all_language_server_work(&mut self, cx: &mut Context<Self>) {
+        let cancellable_work = self
+            .pending_language_server_work(cx)
+            .filter(|work| work.progress.is_cancellable)
+            .map(|work| (work.language_server_id, work.progress_token.to_string()))
+            .collect::<Vec<_>>();
+        self.project.update(cx, |project, cx| {
+            for (language_server_id, token) in cancellable_work {
+                project.cancel_language_server_work(language_server_id, Some(token), cx);
+            }
+        });
+        self.context_menu_handle.hide(cx);
+        cx.notify();
+    }
+
+    fn build_context_menu(
+        this: WeakEntity<Self>,
         window: &mut Window,
-        cx: &mut Context<Self>,
-    ) {
-        self.context_menu_handle.toggle(window, cx);
+        cx: &mut App,
+    ) -> Option<Entity<ContextMenu>> {
+        let strong_this = this.upgrade()?;
+        let previously_focused = window.focused(cx);
+        let mut has_work = false;
+        let mut has_failures = false;
+        let menu = ContextMenu::build(window, cx, |mut menu, _, cx| {
+            let cancellable_work_count = strong_this
+                .read(cx)
+                .pending_language_server_work(cx)
+                .filter(|work| work.progress.is_cancellable)
+                .count();
+            if cancellable_work_count > 1 {
+                let this = this.clone();
+                menu = menu
+                    .entry("Cancel All", None, move |_, cx| {
+                        this.update(cx, |this, cx| {
+                            this.cancel_all_language_server_work(cx);
+                        })
+                        .ok();
+                    })
+                    .separator();
+            }
+            for work in strong_this
+                .read(cx)
+                .pending_language_server_work_top(MAX_PENDING_WORK_IN_MENU, cx)
+            {
+                has_work = true;
+                let this = this.clone();
+                let mut title = work
+                    .progress
+                    .title
+                    .as_deref()
+                    .unwrap_or(work.progress_token)
+                    .to_owned();
+
+                if work.progress.is_cancellable {
+                    let language_server_id = work.language_server_id;
+                    let token = work.progress_token.to_string();
+                    let title = SharedString::from(title);
+                    menu = menu.custom_entry(
+                        move |_, _| {
+                            h_flex()
+                                .w_full()
+                                .justify_between()
+                                .child(Label::new(title.clone()))
+                                .child(Icon::new(IconName::XCircle))
+                                .into_any_element()
+                        },
+                        move |_, cx| {
+                            this.update(cx, |this, cx| {
+                                this.project.update(cx, |project, cx| {
+                                    project.cancel_language_server_work(
+                                        language_server_id,
+                                        Some(token.clone()),
+                                        cx,
+                                    );
+                                });
+                                this.context_menu_handle.hide(cx);
+                                cx.notify();
+                            })
+                            .ok();
+                        },
+                    );
+                } else {
+                    if let Some(progress_message) = work.progress.message.as_ref() {
+                        title.push_str(": ");
+                        title.push_str(progress_message);
+                    }
+
+                    menu = menu.label(title);
+                }
+            }
+            if has_work {
+                menu = menu
+                    .separator()
+                    .action("View All Background Tasks", Box::new(OpenBackgroundTasks));
+            }
+            // Failures are listed even while work is pending, as the indicator keeps showing
+            // them when paused, and this menu is the only way to pick between several.
+            let failed_server_names = strong_this.read(cx).undismissed_failures().into_keys();
+            for server_name in failed_server_names {
+                if !has_failures && has_work {
+                    menu = menu.separator();
+                }
+                has_failures = true;
+                menu = menu.entry(format!("Show {server_name} Error"), None, {
+                    let this = this.clone();
+                    let server_name = server_name.clone();
+                    move |_, cx| {
+                        this.update(cx, |this, cx| {
+                            this.show_error_message_for_server(&server_name, cx);
+                            this.context_menu_handle.hide(cx);
+                        })
+                        .ok();
+                    }
+                });
+                let this = this.clone();
+                menu = menu.entry(
+                    format!("Dismiss {server_name} Error"),
+                    None,
+                    move |_, cx| {
+                        this.update(cx, |this, cx| {
+                            this.dismiss_failed_server(&server_name, cx);
+                        })
+                        .ok();
+                    },
+                );
+            }
+            if has_failures {
+                let this = this.clone();
+                menu = menu
+                    .separator()
+                    .entry("Copy Error Messages", None, move |window, cx| {
+                        this.update(cx, |this, cx| {
+                            this.copy_error_message(&CopyErrorMessage, window, cx);
+                            this.context_menu_handle.hide(cx);
+                        })
+                        .ok();
+                    });
+            }
+            menu
+        });
+        if !has_work && !has_failures {
+            return None;
+        }
+        // The popover only returns focus when it's still inside the menu, so also restore it
+        // when the menu closed because focus was lost, to keep keystrokes going to the editor.
+        window
+            .subscribe(&menu, cx, move |menu, _: &DismissEvent, window, cx| {
+                if let Some(previously_focused) = previously_focused.as_ref()
+                    && (menu.focus_handle(cx).contains_focused(window, cx)
+                        || window.focused(cx).is_none())
+                {
+                    window.focus(previously_focused);
+                }
+            })
+            .detach();
+        Some(menu)
     }
 }
 
 impl EventEmitter<Event> for ActivityIndicator {}
 
+fn failed_servers(statuses: &[ServerStatus]) -> impl Iterator<Item = (&LanguageServerName, &str)> {
+    statuses.iter().filter_map(|status| match &status.status {
+        LanguageServerStatusUpdate::Binary(BinaryStatus::Failed { error }) => {
+            Some((&status.name, error.as_str()))
+        }
+        _ => None,
+    })
+}
+
+fn clear_server_failure(statuses: &mut Vec<ServerStatus>, server_name: &LanguageServerName) {
+    statuses.retain(|status| {
+        &status.name != server_name
+            || !matches!(
+                status.status,
+                LanguageServerStatusUpdate::Binary(BinaryStatus::Failed { .. })
+            )
+    });
+}
+
+fn read_persisted_failures(failures_key: &str) -> BTreeMap<LanguageServerName, String> {
+    KEY_VALUE_STORE
+        .read_kvp(failures_key)
+        .log_err()
+        .flatten()
+        .and_then(|failures| serde_json::from_str(&failures).log_err())
+        .unwrap_or_default()
+}
+
+async fn write_persisted_failures(
+    failures_key: String,
+    failures: BTreeMap<LanguageServerName, String>,
+) -> anyhow::Result<()> {
+    if failures.is_empty() {
+        KEY_VALUE_STORE.delete_kvp(failures_key).await
+    } else {
+        KEY_VALUE_STORE
+            .write_kvp(failures_key, serde_json::to_string(&failures)?)
+            .await
+    }
+}
+
+fn pending_language_server_work<'a>(
+    project: &'a Project,
+    cx: &'a App,
+) -> impl Iterator<Item = PendingWork<'a>> {
+    project
+        .language_server_statuses(cx)
+        .rev()
+        .filter_map(|(server_id, status)| {
+            if status.pending_work.is_empty() {
+                None
+            } else {
+                let mut pending_work = status
+                    .pending_work
+                    .iter()
+                    .map(|(token, progress)| PendingWork {
+                        language_server_id: server_id,
+                        progress_token: token.as_str(),
+                        progress,
+                    })
+                    .collect::<SmallVec<[_; 4]>>();
+                pending_work.sort_by_key(|work| Reverse(work.progress.last_update_at));
+                Some(pending_work)
+            }
+        })
+        .flatten()
+}
+
+/// Returns the `count` most recently updated items of pending work across all language servers,
+/// most recent first.
+fn most_recent_pending_work<'a>(
+    pending_work: impl IntoIterator<Item = PendingWork<'a>>,
+    count: usize,
+) -> Vec<PendingWork<'a>> {
+    let mut pending_work = pending_work.into_iter().collect::<Vec<_>>();
+    pending_work.sort_by_key(|work| Reverse(work.progress.last_update_at));
+    pending_work.truncate(count);
+    pending_work
+}
+
+/// Summarizes pending language server work, averaging the progress of all
+/// tokens that report a percentage when there is more than one of them.
+fn pending_work_message(pending_work: &[PendingWork]) -> Option<String> {
+    let (first, rest) = pending_work.split_first()?;
+    let title = first
+        .progress
+        .title
+        .as_deref()
+        .unwrap_or(first.progress_token);
+
+    let percentages = pending_work
+        .iter()
+        .filter_map(|work| work.progress.percentage)
+        .collect::<SmallVec<[_; 4]>>();
+    if percentages.len() > 1 {
+        let average = percentages.iter().sum::<usize>() / percentages.len();
+        return Some(format!(
+            "{title}: {} tasks ({average}%)",
+            pending_work.len()
+        ));
+    }
+
+    let mut message = title.to_string();
+    if let Some(percentage) = first.progress.percentage {
+        write!(&mut message, " ({}%)", percentage).unwrap();
+    }
+    if let Some(progress_message) = first.progress.message.as_ref() {
+        message.push_str(": ");
+        message.push_str(progress_message);
+    }
+    if !rest.is_empty() {
+        write!(&mut message, " + {} more", rest.len()).unwrap();
+    }
+    Some(message)
+}
+
+const BYTE_UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+
+/// Formats download progress as "12.3/48.1 MB", scaling both amounts to the unit of the total.
+fn download_progress_message(downloaded_bytes: u64, total_bytes: Option<u64>) -> String {
+    let unit = byte_unit(total_bytes.unwrap_or(downloaded_bytes));
+    let format_amount = |bytes: u64| {
+        if unit == 0 {
+            bytes.to_string()
+        } else {
+            format!("{:.1}", bytes as f64 / 1024f64.powi(unit as i32))
+        }
+    };
+    match total_bytes {
+        Some(total_bytes) => format!(
+            "{}/{} {}",
+            format_amount(downloaded_bytes),
+            format_amount(total_bytes),
+            BYTE_UNITS[unit]
+        ),
+        None => format!("{} {}", format_amount(downloaded_bytes), BYTE_UNITS[unit]),
+    }
+}
+
+fn byte_unit(bytes: u64) -> usize {
+    let mut unit = 0;
+    let mut value = bytes;
+    while value >= 1024 && unit < BYTE_UNITS.len() - 1 {
+        value /= 1024;
+        unit += 1;
+    }
+    unit
+}
+
 const MAX_MESSAGE_LEN: usize = 50;
+const MIN_MESSAGE_LEN: usize = 10;
+/// The share of the window width that the status message may occupy.
+const MESSAGE_WIDTH_RATIO: f32 = 0.3;
+
+fn max_message_len(window: &Window, cx: &App) -> usize {
+    let font_id = window
+        .text_system()
+        .resolve_font(&window.text_style().font());
+    let font_size = TextSize::Small.rems(cx).to_pixels(window.rem_size());
+    let char_width = window.text_system().em_advance(font_id, font_size).ok();
+    message_len_for_width(
+        window.viewport_size().width * MESSAGE_WIDTH_RATIO,
+        char_width,
+    )
+}
+
+/// Whether `message` has more than `max_chars` characters, i.e. whether
+/// [`truncate_and_trailoff`] would shorten it.
+fn exceeds_message_len(message: &str, max_chars: usize) -> bool {
+    // The char count never exceeds the byte length, so short messages need no walk.
+    message.len() > max_chars && message.chars().nth(max_chars).is_some()
+}
+
+fn message_len_for_width(available_width: Pixels, char_width: Option<Pixels>) -> usize {
+    match char_width {
+        Some(char_width) if char_width > Pixels::ZERO => {
+            ((available_width / char_width) as usize).max(MIN_MESSAGE_LEN)
+        }
+        _ => MAX_MESSAGE_LEN,
+    }
+}
 
 impl Render for ActivityIndicator {
-    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let result = h_flex()
             .id("activity-indicator")
             .on_action(cx.listener(Self::show_error_message))
+            .on_action(cx.listener(Self::copy_error_message))
+            .on_action(cx.listener(Self::set_paused))
             .on_action(cx.listener(Self::dismiss_message));
-        let Some(content) = self.content_to_render(cx) else {
+        let max_message_len = max_message_len(window, cx);
+        let Some(content) = self.content_to_render(max_message_len, cx) else {
             return result;
         };
         let this = cx.entity().downgrade();
-        let truncate_content = content.message.len() > MAX_MESSAGE_LEN;
+        let truncate_content = exceeds_message_len(&content.message, max_message_len);
         result.gap_2().child(
             PopoverMenu::new("activity-indicator-popover")
                 .trigger(
@@ -795,7 +1327,7 @@ impl Render for ActivityIndicator {
                                         .child(
                                             Label::new(truncate_and_trailoff(
                                                 &content.message,
-                                                MAX_MESSAGE_LEN,
+                                                max_message_len,
                                             ))
                                             .size(LabelSize::Small),
                                         )
@@ -820,61 +1352,8 @@ impl Render for ActivityIndicator {
                     ),
                 )
                 .anchor(gpui::Corner::BottomLeft)
-                .menu(move |window, cx| {
-                    let strong_this = this.upgrade()?;
-                    let mut has_work = false;
-                    let menu = ContextMenu::build(window, cx, |mut menu, _, cx| {
-                        for work in strong_this.read(cx).pending_language_server_work(cx) {
-                            has_work = true;
-                            let this = this.clone();
-                            let mut title = work
-                                .progress
-                                .title
-                                .as_deref()
-                                .unwrap_or(work.progress_token)
-                                .to_owned();
-
-                            if work.progress.is_cancellable {
-                                let language_server_id = work.language_server_id;
-                                let token = work.progress_token.to_string();
-                                let title = SharedString::from(title);
-                                menu = menu.custom_entry(
-                                    move |_, _| {
-                                        h_flex()
-                                            .w_full()
-                                            .justify_between()
-                                            .child(Label::new(title.clone()))
-                                            .child(Icon::new(IconName::XCircle))
-                                            .into_any_element()
-                                    },
-                                    move |_, cx| {
-                                        this.update(cx, |this, cx| {
-                                            this.project.update(cx, |project, cx| {
-                                                project.cancel_language_server_work(
-                                                    language_server_id,
-                                                    Some(token.clone()),
-                                                    cx,
-                                                );
-                                            });
-                                            this.context_menu_handle.hide(cx);
-                                            cx.notify();
-                                        })
-                                        .ok();
-                                    },
-                                );
-                            } else {
-                                if let Some(progress_message) = work.progress.message.as_ref() {
-                                    title.push_str(": ");
-                                    title.push_str(progress_message);
-                                }
-
-                                menu = menu.label(title);
-                            }
-                        }
-                        menu
-                    });
-                    has_work.then_some(menu)
-                }),
+                .with_handle(self.context_menu_handle.clone())
+                .menu(move |window, cx| Self::build_context_menu(this.clone(), window, cx)),
         )
     }
 }
@@ -891,8 +1370,13 @@ impl StatusItemView for ActivityIndicator {
 
 #[cfg(test)]
 mod tests {
-    use gpui::SemanticVersion;
+    use gpui::{SemanticVersion, TestAppContext, VisualTestContext};
     use release_channel::AppCommitSha;
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+    };
+    use workspace::AppState;
 
     use super::*;
 
@@ -910,4 +1394,593 @@ mod tests {
 
         assert_eq!(message, "Version: 14d9a41…");
     }
+
+    #[test]
+    fn test_pending_work_message() {
+        fn progress(title: &str, percentage: Option<usize>) -> LanguageServerProgress {
+            LanguageServerProgress {
+                is_disk_based_diagnostics_progress: false,
+                is_cancellable: false,
+                title: Some(title.to_string()),
+                message: None,
+                percentage,
+                last_update_at: Instant::now(),
+            }
+        }
+        fn work(id: usize, progress: &LanguageServerProgress) -> PendingWork<'_> {
+            PendingWork {
+                language_server_id: LanguageServerId(id),
+                progress_token: "token",
+                progress,
+            }
+        }
+
+        let indexing = progress("Indexing", Some(20));
+        let checking = progress("Checking", Some(50));
+        let loading = progress("Loading", Some(71));
+        let fetching = progress("Fetching", None);
+
+        assert_eq!(pending_work_message(&[]), None);
+        assert_eq!(
+            pending_work_message(&[work(0, &indexing)]).as_deref(),
+            Some("Indexing (20%)")
+        );
+        assert_eq!(
+            pending_work_message(&[work(0, &indexing), work(1, &fetching)]).as_deref(),
+            Some("Indexing (20%) + 1 more")
+        );
+        assert_eq!(
+            pending_work_message(&[
+                work(0, &indexing),
+                work(1, &checking),
+                work(2, &loading),
+                work(2, &fetching),
+            ])
+            .as_deref(),
+            Some("Indexing: 4 tasks (47%)")
+        );
+    }
+
+    #[test]
+    fn test_most_recent_pending_work() {
+        let start = Instant::now();
+        let progress = |title: &str, seconds_since_start: u64| LanguageServerProgress {
+            is_disk_based_diagnostics_progress: false,
+            is_cancellable: false,
+            title: Some(title.to_string()),
+            message: None,
+            percentage: None,
+            last_update_at: start + Duration::from_secs(seconds_since_start),
+        };
+        let indexing = progress("Indexing", 0);
+        let checking = progress("Checking", 25);
+        let loading = progress("Loading", 10);
+        let fetching = progress("Fetching", 20);
+        let pending_work = [
+            (0, &indexing),
+            (0, &loading),
+            (1, &fetching),
+            (2, &checking),
+        ]
+        .map(|(id, progress)| PendingWork {
+            language_server_id: LanguageServerId(id),
+            progress_token: "token",
+            progress,
+        });
+
+        assert_eq!(
+            most_recent_pending_work(pending_work, 2)
+                .iter()
+                .map(|work| (work.language_server_id, work.progress.title.as_deref()))
+                .collect::<Vec<_>>(),
+            [
+                (LanguageServerId(2), Some("Checking")),
+                (LanguageServerId(1), Some("Fetching")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failed_servers() {
+        let statuses = [
+            ServerStatus {
+                name: LanguageServerName::new_static("rust-analyzer"),
+                status: LanguageServerStatusUpdate::Binary(BinaryStatus::Failed {
+                    error: "binary not found".to_string(),
+                }),
+            },
+            ServerStatus {
+                name: LanguageServerName::new_static("pyright"),
+                status: LanguageServerStatusUpdate::Binary(BinaryStatus::Downloading),
+            },
+            ServerStatus {
+                name: LanguageServerName::new_static("gopls"),
+                status: LanguageServerStatusUpdate::Binary(BinaryStatus::Failed {
+                    error: "exited with status 1".to_string(),
+                }),
+            },
+        ];
+
+        assert_eq!(
+            failed_servers(&statuses)
+                .map(|(name, error)| (name.clone(), error))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    LanguageServerName::new_static("rust-analyzer"),
+                    "binary not found"
+                ),
+                (
+                    LanguageServerName::new_static("gopls"),
+                    "exited with status 1"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_language_server_binary_message() {
+        let statuses = [
+            ServerStatus {
+                name: LanguageServerName::new_static("pyright"),
+                status: LanguageServerStatusUpdate::Binary(BinaryStatus::Downloading),
+            },
+            ServerStatus {
+                name: LanguageServerName::new_static("gopls"),
+                status: LanguageServerStatusUpdate::Binary(BinaryStatus::CheckingForUpdate),
+            },
+            ServerStatus {
+                name: LanguageServerName::new_static("rust-analyzer"),
+                status: LanguageServerStatusUpdate::Binary(BinaryStatus::Starting),
+            },
+        ];
+
+        assert_eq!(
+            statuses
+                .iter()
+                .filter_map(background_tasks_panel::language_server_binary_message)
+                .collect::<Vec<_>>(),
+            ["Downloading pyright…", "Checking for updates to gopls…"]
+        );
+    }
+
+    #[test]
+    fn test_message_len_for_width() {
+        assert_eq!(message_len_for_width(px(600.), None), MAX_MESSAGE_LEN);
+        assert_eq!(
+            message_len_for_width(px(600.), Some(px(0.))),
+            MAX_MESSAGE_LEN
+        );
+        assert_eq!(message_len_for_width(px(600.), Some(px(10.))), 60);
+        assert_eq!(
+            message_len_for_width(px(50.), Some(px(10.))),
+            MIN_MESSAGE_LEN
+        );
+
+        let message = "Indexing workspace symbols for the current project";
+        assert_eq!(
+            truncate_and_trailoff(message, message_len_for_width(px(200.), Some(px(10.)))),
+            "Indexing workspace s…"
+        );
+        assert_eq!(
+            truncate_and_trailoff(message, message_len_for_width(px(1000.), Some(px(10.)))),
+            message
+        );
+    }
+
+    #[test]
+    fn test_truncate_multibyte_message() {
+        let message = format!("{}ü{}", "a".repeat(MAX_MESSAGE_LEN - 1), "é".repeat(5));
+        assert!(exceeds_message_len(&message, MAX_MESSAGE_LEN));
+        let truncated = truncate_and_trailoff(&message, MAX_MESSAGE_LEN);
+        assert_eq!(truncated, format!("{}ü…", "a".repeat(MAX_MESSAGE_LEN - 1)));
+        assert!(!truncated.contains(char::REPLACEMENT_CHARACTER));
+
+        let message = format!("{}ü", "é".repeat(MAX_MESSAGE_LEN - 1));
+        assert!(message.len() > MAX_MESSAGE_LEN);
+        assert!(
+            !exceeds_message_len(&message, MAX_MESSAGE_LEN),
+            "messages are measured in chars, not bytes"
+        );
+        assert_eq!(truncate_and_trailoff(&message, MAX_MESSAGE_LEN), message);
+    }
+
+    #[test]
+    fn test_download_progress_message() {
+        assert_eq!(download_progress_message(512, None), "512 B");
+        assert_eq!(download_progress_message(1023, None), "1023 B");
+        assert_eq!(download_progress_message(1024, None), "1.0 KB");
+        assert_eq!(download_progress_message(1536, None), "1.5 KB");
+        assert_eq!(download_progress_message(12_900_000, None), "12.3 MB");
+        assert_eq!(
+            download_progress_message(5 * 1024 * 1024 * 1024, None),
+            "5.0 GB"
+        );
+        assert_eq!(
+            download_progress_message(4096 * 1024 * 1024 * 1024, None),
+            "4096.0 GB"
+        );
+
+        assert_eq!(download_progress_message(0, Some(800)), "0/800 B");
+        assert_eq!(
+            download_progress_message(12_900_000, Some(50_436_505)),
+            "12.3/48.1 MB"
+        );
+        assert_eq!(download_progress_message(300, Some(2048)), "0.3/2.0 KB");
+    }
+
+    #[test]
+    fn test_clear_server_failure() {
+        let mut statuses = vec![
+            ServerStatus {
+                name: LanguageServerName::new_static("rust-analyzer"),
+                status: LanguageServerStatusUpdate::Binary(BinaryStatus::Failed {
+                    error: "binary not found".to_string(),
+                }),
+            },
+            ServerStatus {
+                name: LanguageServerName::new_static("gopls"),
+                status: LanguageServerStatusUpdate::Binary(BinaryStatus::Failed {
+                    error: "exited with status 1".to_string(),
+                }),
+            },
+        ];
+
+        clear_server_failure(
+            &mut statuses,
+            &LanguageServerName::new_static("rust-analyzer"),
+        );
+        assert_eq!(
+            failed_servers(&statuses)
+                .map(|(name, error)| (name.clone(), error))
+                .collect::<Vec<_>>(),
+            [(
+                LanguageServerName::new_static("gopls"),
+                "exited with status 1"
+            )]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_persisted_failures_round_trip() {
+        let failures = BTreeMap::from_iter([(
+            LanguageServerName::new_static("rust-analyzer"),
+            "binary not found".to_string(),
+        )]);
+        write_persisted_failures("failures-a".to_string(), failures.clone())
+            .await
+            .unwrap();
+        assert_eq!(read_persisted_failures("failures-a"), failures);
+        assert!(
+            read_persisted_failures("failures-b").is_empty(),
+            "failures are persisted per workspace"
+        );
+
+        write_persisted_failures("failures-a".to_string(), BTreeMap::default())
+            .await
+            .unwrap();
+        assert!(read_persisted_failures("failures-a").is_empty());
+    }
+
+    /// Opens a workspace on a fake `/dir` containing `tree` and adds an activity indicator
+    /// to it, after persisting `previous_session_failures` for that workspace.
+    async fn init_test(
+        cx: &mut TestAppContext,
+        tree: serde_json::Value,
+        previous_session_failures: BTreeMap<LanguageServerName, String>,
+    ) -> (
+        Entity<Workspace>,
+        Entity<ActivityIndicator>,
+        Arc<LanguageRegistry>,
+        &mut VisualTestContext,
+    ) {
+        let app_state = cx.update(|cx| {
+            let app_state = AppState::test(cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            app_state
+        });
+        app_state.fs.as_fake().insert_tree("/dir", tree).await;
+        let project = Project::test(app_state.fs.clone(), ["/dir".as_ref()], cx).await;
+        let languages = project.read_with(cx, |project, _| project.languages().clone());
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project, window, cx));
+        if !previous_session_failures.is_empty() {
+            let failures_key = workspace
+                .read_with(cx, |workspace, _| {
+                    ActivityIndicator::failures_key(workspace)
+                })
+                .unwrap();
+            write_persisted_failures(failures_key, previous_session_failures)
+                .await
+                .unwrap();
+        }
+        let activity_indicator = workspace.update_in(cx, |workspace, window, cx| {
+            ActivityIndicator::new(workspace, languages.clone(), window, cx)
+        });
+        cx.run_until_parked();
+        (workspace, activity_indicator, languages, cx)
+    }
+
+    fn rendered_message(
+        activity_indicator: &Entity<ActivityIndicator>,
+        cx: &mut VisualTestContext,
+    ) -> Option<String> {
+        activity_indicator.update(cx, |activity_indicator, cx| {
+            activity_indicator
+                .content_to_render(MAX_MESSAGE_LEN, cx)
+                .map(|content| content.message)
+        })
+    }
+
+    #[gpui::test]
+    async fn test_copy_error_message(cx: &mut TestAppContext) {
+        let (_, activity_indicator, languages, cx) =
+            init_test(cx, serde_json::json!({}), BTreeMap::default()).await;
+
+        languages.update_lsp_binary_status(
+            LanguageServerName::new_static("rust-analyzer"),
+            BinaryStatus::Failed {
+                error: "binary not found".to_string(),
+            },
+        );
+        cx.run_until_parked();
+
+        activity_indicator.update_in(cx, |activity_indicator, window, cx| {
+            activity_indicator.copy_error_message(&CopyErrorMessage, window, cx)
+        });
+        assert_eq!(
+            cx.read_from_clipboard().and_then(|item| item.text()),
+            Some("Language server rust-analyzer:\n\nbinary not found".to_string())
+        );
+    }
+
+    #[gpui::test]
+    async fn test_dismiss_clears_previous_session_failures(cx: &mut TestAppContext) {
+        let (_, activity_indicator, _, cx) = init_test(
+            cx,
+            serde_json::json!({}),
+            BTreeMap::from_iter([(
+                LanguageServerName::new_static("rust-analyzer"),
+                "binary not found".to_string(),
+            )]),
+        )
+        .await;
+        assert_eq!(
+            rendered_message(&activity_indicator, cx),
+            Some(
+                "Failed to run rust-analyzer in the previous session. Click to show error."
+                    .to_string()
+            )
+        );
+
+        activity_indicator.update_in(cx, |activity_indicator, window, cx| {
+            activity_indicator.dismiss_message(&DismissMessage, window, cx)
+        });
+        assert_eq!(rendered_message(&activity_indicator, cx), None);
+    }
+
+    #[gpui::test]
+    async fn test_paused_indicator_still_shows_failures(cx: &mut TestAppContext) {
+        let (_, activity_indicator, languages, cx) =
+            init_test(cx, serde_json::json!({}), BTreeMap::default()).await;
+        let set_paused = |paused: bool, cx: &mut VisualTestContext| {
+            activity_indicator.update_in(cx, |activity_indicator, window, cx| {
+                activity_indicator.set_paused(&SetActivityIndicatorPaused(paused), window, cx)
+            });
+        };
+        let is_visible = |cx: &mut VisualTestContext| {
+            activity_indicator.update(cx, |activity_indicator, cx| {
+                activity_indicator
+                    .content_to_render(MAX_MESSAGE_LEN, cx)
+                    .is_some()
+            })
+        };
+
+        languages.update_lsp_binary_status(
+            LanguageServerName::new_static("rust-analyzer"),
+            BinaryStatus::Downloading,
+        );
+        cx.run_until_parked();
+        assert!(is_visible(cx));
+
+        set_paused(true, cx);
+        assert!(!is_visible(cx), "progress is hidden while paused");
+
+        languages.update_lsp_binary_status(
+            LanguageServerName::new_static("gopls"),
+            BinaryStatus::Failed {
+                error: "binary not found".to_string(),
+            },
+        );
+        cx.run_until_parked();
+        assert_eq!(
+            rendered_message(&activity_indicator, cx),
+            Some("Failed to run gopls. Click to show error.".to_string()),
+            "failures are shown while paused"
+        );
+
+        languages
+            .update_lsp_binary_status(LanguageServerName::new_static("gopls"), BinaryStatus::None);
+        cx.run_until_parked();
+        assert!(!is_visible(cx));
+
+        set_paused(false, cx);
+        assert!(is_visible(cx));
+    }
+
+    #[gpui::test]
+    async fn test_context_menu_restores_focus(cx: &mut TestAppContext) {
+        let (workspace, activity_indicator, languages, cx) =
+            init_test(cx, serde_json::json!({}), BTreeMap::default()).await;
+        for server_name in ["rust-analyzer", "gopls"] {
+            languages.update_lsp_binary_status(
+                LanguageServerName::new_static(server_name),
+                BinaryStatus::Failed {
+                    error: "binary not found".to_string(),
+                },
+            );
+        }
+        cx.run_until_parked();
+
+        let pane_focus_handle =
+            workspace.update(cx, |workspace, cx| workspace.active_pane().focus_handle(cx));
+        let open_menu = |cx: &mut VisualTestContext| {
+            cx.update(|window, cx| {
+                window.focus(&pane_focus_handle);
+                let menu = ActivityIndicator::build_context_menu(
+                    activity_indicator.downgrade(),
+                    window,
+                    cx,
+                )
+                .expect("failed servers should be listed");
+                window.focus(&menu.focus_handle(cx));
+                menu
+            })
+        };
+        let is_pane_focused = |cx: &mut VisualTestContext| {
+            cx.update(|window, _| pane_focus_handle.is_focused(window))
+        };
+
+        let menu = open_menu(cx);
+        assert!(!is_pane_focused(cx));
+        menu.update(cx, |_, cx| cx.emit(DismissEvent));
+        cx.run_until_parked();
+        assert!(is_pane_focused(cx), "closing the menu returns focus");
+
+        let menu = open_menu(cx);
+        cx.update(|window, _| window.blur());
+        menu.update(cx, |_, cx| cx.emit(DismissEvent));
+        cx.run_until_parked();
+        assert!(
+            is_pane_focused(cx),
+            "closing the menu after focus was lost returns focus"
+        );
+
+        let menu = open_menu(cx);
+        let other_focus_handle = cx.update(|window, cx| {
+            let other_focus_handle = cx.focus_handle();
+            window.focus(&other_focus_handle);
+            other_focus_handle
+        });
+        menu.update(cx, |_, cx| cx.emit(DismissEvent));
+        cx.run_until_parked();
+        assert!(
+            cx.update(|window, _| other_focus_handle.is_focused(window)),
+            "focus moved elsewhere is left alone"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_context_menu_lists_previous_session_failures(cx: &mut TestAppContext) {
+        let (_, activity_indicator, languages, cx) = init_test(
+            cx,
+            serde_json::json!({}),
+            BTreeMap::from_iter([(
+                LanguageServerName::new_static("gopls"),
+                "exited with status 1".to_string(),
+            )]),
+        )
+        .await;
+        languages.update_lsp_binary_status(
+            LanguageServerName::new_static("rust-analyzer"),
+            BinaryStatus::Failed {
+                error: "binary not found".to_string(),
+            },
+        );
+        cx.run_until_parked();
+
+        let shown_servers = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = cx.update(|_, cx| {
+            let shown_servers = shown_servers.clone();
+            cx.subscribe(&activity_indicator, move |_, event, _| {
+                let Event::ShowStatus { server_name, .. } = event;
+                shown_servers.borrow_mut().push(server_name.clone());
+            })
+        });
+        let menu = cx.update(|window, cx| {
+            ActivityIndicator::build_context_menu(activity_indicator.downgrade(), window, cx)
+                .expect("failed servers should be listed")
+        });
+        menu.update_in(cx, |menu, window, cx| {
+            menu.select_first(&menu::SelectFirst, window, cx);
+            menu.confirm(&menu::Confirm, window, cx);
+        });
+        assert_eq!(
+            shown_servers.borrow().as_slice(),
+            [LanguageServerName::new_static("gopls")],
+            "failures from the previous session are listed"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_notifications_are_throttled(cx: &mut TestAppContext) {
+        let (_, activity_indicator, languages, cx) =
+            init_test(cx, serde_json::json!({}), BTreeMap::default()).await;
+        let notify_count = Rc::new(Cell::new(0));
+        let _subscription = cx.update(|_, cx| {
+            let notify_count = notify_count.clone();
+            cx.observe(&activity_indicator, move |_, _| {
+                notify_count.set(notify_count.get() + 1)
+            })
+        });
+
+        for _ in 0..50 {
+            languages.update_lsp_binary_status(
+                LanguageServerName::new_static("rust-analyzer"),
+                BinaryStatus::Downloading,
+            );
+        }
+        languages.update_lsp_binary_status(
+            LanguageServerName::new_static("rust-analyzer"),
+            BinaryStatus::Starting,
+        );
+        cx.run_until_parked();
+        assert_eq!(notify_count.get(), 0);
+
+        cx.executor().advance_clock(NOTIFY_THROTTLE_INTERVAL);
+        cx.run_until_parked();
+        assert_eq!(notify_count.get(), 1);
+        activity_indicator.read_with(cx, |activity_indicator, _| {
+            assert!(matches!(
+                activity_indicator.statuses.as_slice(),
+                [ServerStatus {
+                    status: LanguageServerStatusUpdate::Binary(BinaryStatus::Starting),
+                    ..
+                }]
+            ));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_git_job_is_shown(cx: &mut TestAppContext) {
+        let (workspace, activity_indicator, _, cx) = init_test(
+            cx,
+            serde_json::json!({ ".git": {}, "a.txt": "" }),
+            BTreeMap::default(),
+        )
+        .await;
+
+        let repository = workspace
+            .read_with(cx, |workspace, cx| {
+                workspace.project().read(cx).active_repository(cx)
+            })
+            .unwrap();
+        let (job_finished_tx, job_finished_rx) = futures::channel::oneshot::channel::<()>();
+        let job = repository.update(cx, |repository, _| {
+            repository.send_job(Some("Fetching origin…".into()), move |_, _| async move {
+                job_finished_rx.await.ok();
+            })
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            rendered_message(&activity_indicator, cx),
+            Some("Fetching origin…".to_string())
+        );
+
+        job_finished_tx.send(()).ok();
+        job.await.unwrap();
+        cx.run_until_parked();
+        assert_eq!(rendered_message(&activity_indicator, cx), None);
+    }
 }
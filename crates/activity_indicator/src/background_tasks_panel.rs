@@ -0,0 +1,329 @@
+use auto_update::{AutoUpdateStatus, AutoUpdater};
+use extension_host::{ExtensionOperation, ExtensionStore};
+use gpui::{Entity, EventEmitter, FocusHandle, Focusable, WeakEntity};
+use language::{BinaryStatus, LanguageServerStatusUpdate};
+use project::Project;
+use ui::{IconButton, Tooltip, prelude::*};
+use workspace::{
+    Workspace,
+    dock::{DockPosition, Panel, PanelEvent},
+};
+
+use crate::{
+    ActivityIndicator, PendingWork, ServerStatus, ToggleBackgroundTasksFocus,
+    download_progress_message, pending_language_server_work,
+};
+
+/// Lists every piece of background activity the activity indicator knows about,
+/// for sessions where too much is going on to fit in the status bar popover.
+pub struct BackgroundTasksPanel {
+    project: Entity<Project>,
+    activity_indicator: WeakEntity<ActivityIndicator>,
+    auto_updater: Option<Entity<AutoUpdater>>,
+    focus_handle: FocusHandle,
+    position: DockPosition,
+    size: Option<Pixels>,
+    _subscriptions: Vec<gpui::Subscription>,
+}
+
+impl BackgroundTasksPanel {
+    pub fn new(
+        workspace: &Workspace,
+        activity_indicator: WeakEntity<ActivityIndicator>,
+        cx: &mut Context<Workspace>,
+    ) -> Entity<Self> {
+        let project = workspace.project().clone();
+        let auto_updater = AutoUpdater::get(cx);
+        cx.new(|cx| {
+            let mut subscriptions =
+                vec![cx.subscribe(&project.read(cx).lsp_store(), |_, _, _, cx| cx.notify())];
+            // The activity indicator collects the language server binary statuses.
+            if let Some(activity_indicator) = activity_indicator.upgrade() {
+                subscriptions.push(cx.observe(&activity_indicator, |_, _, cx| cx.notify()));
+            }
+            if let Some(auto_updater) = auto_updater.as_ref() {
+                subscriptions.push(cx.observe(auto_updater, |_, _, cx| cx.notify()));
+            }
+            if let Some(extension_store) = ExtensionStore::try_global(cx) {
+                subscriptions.push(cx.observe(&extension_store, |_, _, cx| cx.notify()));
+            }
+
+            Self {
+                project,
+                activity_indicator,
+                auto_updater,
+                focus_handle: cx.focus_handle(),
+                position: DockPosition::Bottom,
+                size: None,
+                _subscriptions: subscriptions,
+            }
+        })
+    }
+
+    fn render_language_server_work(&self, cx: &mut Context<Self>) -> Vec<AnyElement> {
+        let project = self.project.read(cx);
+        pending_language_server_work(project, cx)
+            .enumerate()
+            .map(
+                |(
+                    ix,
+                    PendingWork {
+                        language_server_id,
+                        progress_token,
+                        progress,
+                    },
+                )| {
+                    let server_name = project
+                        .language_server_statuses(cx)
+                        .find(|(id, _)| *id == language_server_id)
+                        .map(|(_, status)| status.name.clone());
+                    let mut title = progress
+                        .title
+                        .as_deref()
+                        .unwrap_or(progress_token)
+                        .to_string();
+                    if let Some(message) = progress.message.as_ref() {
+                        title.push_str(": ");
+                        title.push_str(message);
+                    }
+
+                    h_flex()
+                        .w_full()
+                        .gap_2()
+                        .justify_between()
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .child(
+                                    Icon::new(IconName::ArrowCircle)
+                                        .size(IconSize::Small)
+                                        .color(Color::Muted),
+                                )
+                                .when_some(server_name, |this, name| {
+                                    this.child(
+                                        Label::new(name.0.clone())
+                                            .size(LabelSize::Small)
+                                            .color(Color::Muted),
+                                    )
+                                })
+                                .child(Label::new(title).size(LabelSize::Small)),
+                        )
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .when_some(progress.percentage, |this, percentage| {
+                                    this.child(
+                                        Label::new(format!("{percentage}%"))
+                                            .size(LabelSize::Small)
+                                            .color(Color::Muted),
+                                    )
+                                })
+                                .when(progress.is_cancellable, |this| {
+                                    let token = progress_token.to_string();
+                                    this.child(
+                                        IconButton::new(("cancel-work", ix), IconName::XCircle)
+                                            .icon_size(IconSize::Small)
+                                            .tooltip(Tooltip::text("Cancel"))
+                                            .on_click(cx.listener(move |this, _, _, cx| {
+                                                this.project.update(cx, |project, cx| {
+                                                    project.cancel_language_server_work(
+                                                        language_server_id,
+                                                        Some(token.clone()),
+                                                        cx,
+                                                    );
+                                                });
+                                                cx.notify();
+                                            })),
+                                    )
+                                }),
+                        )
+                        .into_any_element()
+                },
+            )
+            .collect()
+    }
+
+    fn render_language_server_binaries(&self, cx: &App) -> Vec<AnyElement> {
+        let Some(activity_indicator) = self.activity_indicator.upgrade() else {
+            return Vec::new();
+        };
+        activity_indicator
+            .read(cx)
+            .statuses
+            .iter()
+            .filter_map(language_server_binary_message)
+            .map(|message| {
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Icon::new(IconName::Download)
+                            .size(IconSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(Label::new(message).size(LabelSize::Small))
+                    .into_any_element()
+            })
+            .collect()
+    }
+
+    fn render_extension_operations(&self, cx: &App) -> Vec<AnyElement> {
+        let Some(extension_store) = ExtensionStore::try_global(cx) else {
+            return Vec::new();
+        };
+        extension_store
+            .read(cx)
+            .outstanding_operations()
+            .iter()
+            .map(|(extension_id, operation)| {
+                let message = match operation {
+                    ExtensionOperation::Install => format!("Installing {extension_id} extension…"),
+                    ExtensionOperation::Upgrade => format!("Updating {extension_id} extension…"),
+                    ExtensionOperation::Remove => format!("Removing {extension_id} extension…"),
+                };
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Icon::new(IconName::Download)
+                            .size(IconSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(Label::new(message).size(LabelSize::Small))
+                    .into_any_element()
+            })
+            .collect()
+    }
+
+    fn render_auto_update(&self, cx: &App) -> Option<AnyElement> {
+        let message = match self.auto_updater.as_ref()?.read(cx).status() {
+            AutoUpdateStatus::Checking => "Checking for Zed updates…".to_string(),
+            AutoUpdateStatus::Downloading {
+                downloaded_bytes: 0,
+                ..
+            } => "Downloading Zed update…".to_string(),
+            AutoUpdateStatus::Downloading {
+                downloaded_bytes,
+                total_bytes,
+                ..
+            } => format!(
+                "Downloading Zed update… {}",
+                download_progress_message(*downloaded_bytes, *total_bytes)
+            ),
+            AutoUpdateStatus::Installing { .. } => "Installing Zed update…".to_string(),
+            AutoUpdateStatus::Idle
+            | AutoUpdateStatus::Updated { .. }
+            | AutoUpdateStatus::Errored { .. } => return None,
+        };
+        Some(
+            h_flex()
+                .gap_2()
+                .child(
+                    Icon::new(IconName::Download)
+                        .size(IconSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(Label::new(message).size(LabelSize::Small))
+                .into_any_element(),
+        )
+    }
+}
+
+/// Describes the download or update check a language server's binary is going through.
+pub(crate) fn language_server_binary_message(status: &ServerStatus) -> Option<String> {
+    match &status.status {
+        LanguageServerStatusUpdate::Binary(BinaryStatus::Downloading) => {
+            Some(format!("Downloading {}…", status.name.as_ref()))
+        }
+        LanguageServerStatusUpdate::Binary(BinaryStatus::CheckingForUpdate) => {
+            Some(format!("Checking for updates to {}…", status.name.as_ref()))
+        }
+        LanguageServerStatusUpdate::Binary(
+            BinaryStatus::None
+            | BinaryStatus::Starting
+            | BinaryStatus::Stopping
+            | BinaryStatus::Stopped
+            | BinaryStatus::Failed { .. },
+        )
+        | LanguageServerStatusUpdate::Health(..) => None,
+    }
+}
+
+impl Render for BackgroundTasksPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let mut tasks = self.render_language_server_work(cx);
+        tasks.extend(self.render_language_server_binaries(cx));
+        tasks.extend(self.render_extension_operations(cx));
+        tasks.extend(self.render_auto_update(cx));
+
+        v_flex()
+            .id("background-tasks-panel")
+            .key_context("BackgroundTasksPanel")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .p_2()
+            .gap_1()
+            .overflow_y_scroll()
+            .map(|this| {
+                if tasks.is_empty() {
+                    this.child(
+                        Label::new("No background tasks")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                } else {
+                    this.children(tasks)
+                }
+            })
+    }
+}
+
+impl Focusable for BackgroundTasksPanel {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for BackgroundTasksPanel {}
+
+impl Panel for BackgroundTasksPanel {
+    fn persistent_name() -> &'static str {
+        "BackgroundTasksPanel"
+    }
+
+    fn position(&self, _: &Window, _: &App) -> DockPosition {
+        self.position
+    }
+
+    fn position_is_valid(&self, _: DockPosition) -> bool {
+        true
+    }
+
+    fn set_position(&mut self, position: DockPosition, _: &mut Window, cx: &mut Context<Self>) {
+        self.position = position;
+        cx.notify();
+    }
+
+    fn size(&self, _: &Window, _: &App) -> Pixels {
+        self.size.unwrap_or(px(240.))
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, _: &mut Window, cx: &mut Context<Self>) {
+        self.size = size;
+        cx.notify();
+    }
+
+    fn icon(&self, _: &Window, _: &App) -> Option<IconName> {
+        None
+    }
+
+    fn icon_tooltip(&self, _: &Window, _: &App) -> Option<&'static str> {
+        Some("Background Tasks")
+    }
+
+    fn toggle_action(&self) -> Box<dyn gpui::Action> {
+        Box::new(ToggleBackgroundTasksFocus)
+    }
+
+    fn activation_priority(&self) -> u32 {
+        10
+    }
+}
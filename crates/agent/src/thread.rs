@@ -19,7 +19,10 @@ use chrono::{DateTime, Utc};
 use client::{ModelRequestUsage, RequestUsage};
 use cloud_llm_client::{CompletionIntent, CompletionRequestStatus, Plan, UsageLimit};
 use collections::HashMap;
-use futures::{FutureExt, StreamExt as _, future::Shared};
+use futures::{
+    FutureExt, StreamExt as _,
+    future::{BoxFuture, Shared},
+};
 use git::repository::DiffType;
 use gpui::{
     AnyWindowHandle, App, AppContext, AsyncApp, Context, Entity, EventEmitter, SharedString, Task,
@@ -55,6 +58,9 @@ use uuid::Uuid;
 
 const MAX_RETRY_ATTEMPTS: u8 = 4;
 const BASE_RETRY_DELAY: Duration = Duration::from_secs(5);
+/// How long to wait for further messages before recounting a thread's tokens, as
+/// some providers count them with a network request.
+const TOKEN_COUNT_DEBOUNCE: Duration = Duration::from_millis(500);
 
 #[derive(Debug, Clone)]
 enum RetryStrategy {
@@ -312,7 +318,8 @@ pub struct TotalTokenUsage {
 }
 
 impl TotalTokenUsage {
-    pub fn ratio(&self) -> TokenUsageRatio {
+    /// The fraction of the model's maximum at which the thread warns about its token usage.
+    pub fn warning_threshold() -> f32 {
         #[cfg(debug_assertions)]
         let warning_threshold: f32 = std::env::var("ZED_THREAD_WARNING_THRESHOLD")
             .unwrap_or("0.8".to_string())
@@ -320,6 +327,11 @@ impl TotalTokenUsage {
             .unwrap();
         #[cfg(not(debug_assertions))]
         let warning_threshold: f32 = 0.8;
+        warning_threshold
+    }
+
+    pub fn ratio(&self) -> TokenUsageRatio {
+        let warning_threshold = Self::warning_threshold();
 
         // When the maximum is unknown because there is no selected model,
         // avoid showing the token limit warning.
@@ -385,6 +397,8 @@ pub struct Thread {
     request_token_usage: Vec<TokenUsage>,
     cumulative_token_usage: TokenUsage,
     exceeded_window_error: Option<ExceededWindowError>,
+    counted_tokens: Option<u64>,
+    pending_token_count: Task<Option<()>>,
     tool_use_limit_reached: bool,
     retry_state: Option<RetryState>,
     message_feedback: HashMap<MessageId, ThreadFeedback>,
@@ -484,6 +498,8 @@ impl Thread {
             request_token_usage: Vec::new(),
             cumulative_token_usage: TokenUsage::default(),
             exceeded_window_error: None,
+            counted_tokens: None,
+            pending_token_count: Task::ready(None),
             tool_use_limit_reached: false,
             retry_state: None,
             message_feedback: HashMap::default(),
@@ -608,6 +624,8 @@ impl Thread {
             request_token_usage: serialized.request_token_usage,
             cumulative_token_usage: serialized.cumulative_token_usage,
             exceeded_window_error: None,
+            counted_tokens: None,
+            pending_token_count: Task::ready(None),
             tool_use_limit_reached: serialized.tool_use_limit_reached,
             message_feedback: HashMap::default(),
             last_error_context: None,
@@ -1028,6 +1046,16 @@ impl Thread {
             });
         }
 
+        self.pending_token_count = cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(TOKEN_COUNT_DEBOUNCE).await;
+            this.update(cx, |this, cx| {
+                this.refresh_token_count(TotalTokenUsage::warning_threshold(), cx)
+            })
+            .log_err()?
+            .await
+            .log_err()
+        });
+
         message_id
     }
 
@@ -3121,6 +3149,57 @@ impl Thread {
         Some(TotalTokenUsage { total, max })
     }
 
+    /// Counts the tokens the thread's next completion request would occupy using the
+    /// model's tokenizer.
+    ///
+    /// Unlike [`Self::total_token_usage`], this doesn't depend on the usage reported
+    /// by the last completion, so it also covers messages that haven't been sent yet.
+    pub fn count_tokens(
+        &self,
+        model: &Arc<dyn LanguageModel>,
+        cx: &mut Context<Self>,
+    ) -> BoxFuture<'static, Result<u64>> {
+        let request = self.to_completion_request(model.clone(), CompletionIntent::UserPrompt, cx);
+        model.count_tokens(request, cx)
+    }
+
+    /// The token count computed by the last call to [`Self::refresh_token_count`].
+    pub fn counted_tokens(&self) -> Option<u64> {
+        self.counted_tokens
+    }
+
+    /// Recounts the thread's tokens with the configured model, emitting
+    /// [`ThreadEvent::TokenCountWarning`] when the count crosses `warning_fraction`
+    /// of the model's maximum.
+    pub fn refresh_token_count(
+        &mut self,
+        warning_fraction: f32,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let Some(model) = self
+            .configured_model
+            .as_ref()
+            .map(|model| model.model.clone())
+        else {
+            return Task::ready(Ok(()));
+        };
+        let max = model.max_token_count_for_mode(self.completion_mode().into());
+        let count = self.count_tokens(&model, cx);
+        cx.spawn(async move |this, cx| {
+            let total = count.await?;
+            this.update(cx, |this, cx| {
+                let exceeds_fraction =
+                    |tokens: u64| max > 0 && tokens as f32 / max as f32 >= warning_fraction;
+                let was_exceeding = this.counted_tokens.is_some_and(exceeds_fraction);
+                this.counted_tokens = Some(total);
+                if exceeds_fraction(total) && !was_exceeding {
+                    cx.emit(ThreadEvent::TokenCountWarning { total, max });
+                }
+                cx.notify();
+            })
+        })
+    }
+
     fn token_usage_at_last_message(&self) -> Option<TokenUsage> {
         self.request_token_usage
             .get(self.messages.len().saturating_sub(1))
@@ -3236,6 +3315,10 @@ pub enum ThreadEvent {
     CancelEditing,
     CompletionCanceled,
     ProfileChanged,
+    TokenCountWarning {
+        total: u64,
+        max: u64,
+    },
 }
 
 impl EventEmitter<ThreadEvent> for Thread {}
@@ -3274,6 +3357,8 @@ mod tests {
     use prompt_store::PromptBuilder;
     use serde_json::json;
     use settings::{LanguageModelParameters, Settings, SettingsStore};
+    use std::cell::RefCell;
+    use std::rc::Rc;
     use std::sync::Arc;
     use std::time::Duration;
     use theme::ThemeSettings;
@@ -4044,6 +4129,186 @@ fn main() {{
         });
     }
 
+    struct WordCountingModel {
+        inner: Arc<FakeLanguageModel>,
+        max_token_count: u64,
+    }
+
+    impl LanguageModel for WordCountingModel {
+        fn id(&self) -> LanguageModelId {
+            self.inner.id()
+        }
+
+        fn name(&self) -> LanguageModelName {
+            self.inner.name()
+        }
+
+        fn provider_id(&self) -> LanguageModelProviderId {
+            self.inner.provider_id()
+        }
+
+        fn provider_name(&self) -> LanguageModelProviderName {
+            self.inner.provider_name()
+        }
+
+        fn supports_tools(&self) -> bool {
+            self.inner.supports_tools()
+        }
+
+        fn supports_tool_choice(&self, choice: LanguageModelToolChoice) -> bool {
+            self.inner.supports_tool_choice(choice)
+        }
+
+        fn supports_images(&self) -> bool {
+            self.inner.supports_images()
+        }
+
+        fn telemetry_id(&self) -> String {
+            self.inner.telemetry_id()
+        }
+
+        fn max_token_count(&self) -> u64 {
+            self.max_token_count
+        }
+
+        fn count_tokens(
+            &self,
+            request: LanguageModelRequest,
+            _cx: &App,
+        ) -> BoxFuture<'static, Result<u64>> {
+            let count = request
+                .messages
+                .iter()
+                .map(|message| message.string_contents().split_whitespace().count() as u64)
+                .sum();
+            futures::future::ready(Ok(count)).boxed()
+        }
+
+        fn stream_completion(
+            &self,
+            request: LanguageModelRequest,
+            cx: &AsyncApp,
+        ) -> BoxFuture<
+            'static,
+            Result<
+                BoxStream<
+                    'static,
+                    Result<LanguageModelCompletionEvent, LanguageModelCompletionError>,
+                >,
+                LanguageModelCompletionError,
+            >,
+        > {
+            self.inner.stream_completion(request, cx)
+        }
+
+        fn as_fake(&self) -> &FakeLanguageModel {
+            &self.inner
+        }
+    }
+
+    #[gpui::test]
+    async fn test_count_tokens(cx: &mut TestAppContext) {
+        let fs = init_test_settings(cx);
+
+        let project = create_test_project(&fs, cx, json!({})).await;
+        let (_, _, thread, _, _) = setup_test_environment(cx, project.clone()).await;
+
+        // The system prompt counts towards the thread's tokens.
+        let system_prompt_tokens = thread
+            .update(cx, |thread, cx| {
+                let model: Arc<dyn LanguageModel> = Arc::new(WordCountingModel {
+                    inner: Arc::new(FakeLanguageModel::default()),
+                    max_token_count: 0,
+                });
+                thread.count_tokens(&model, cx)
+            })
+            .await
+            .unwrap();
+        assert!(system_prompt_tokens > 0);
+
+        // Leaves the automatic recounts, which warn at 80%, below their threshold.
+        let max_token_count = 2 * (system_prompt_tokens + 10);
+        let model: Arc<dyn LanguageModel> = Arc::new(WordCountingModel {
+            inner: Arc::new(FakeLanguageModel::default()),
+            max_token_count,
+        });
+        let warnings = Rc::new(RefCell::new(Vec::new()));
+        cx.update(|cx| {
+            let warnings = warnings.clone();
+            cx.subscribe(&thread, move |_, event, _| {
+                if let ThreadEvent::TokenCountWarning { total, max } = event {
+                    warnings.borrow_mut().push((*total, *max));
+                }
+            })
+            .detach();
+        });
+        thread.update(cx, |thread, cx| {
+            thread.set_configured_model(
+                Some(ConfiguredModel {
+                    provider: Arc::new(FakeLanguageModelProvider::default()),
+                    model: model.clone(),
+                }),
+                cx,
+            );
+            thread.insert_user_message(
+                "What is the capital of France?",
+                ContextLoadResult::default(),
+                None,
+                vec![],
+                cx,
+            );
+        });
+
+        // Messages are recounted once no further message arrives for a while.
+        cx.run_until_parked();
+        assert_eq!(
+            thread.read_with(cx, |thread, _| thread.counted_tokens()),
+            None
+        );
+        cx.executor().advance_clock(TOKEN_COUNT_DEBOUNCE);
+        cx.run_until_parked();
+        assert_eq!(
+            thread.read_with(cx, |thread, _| thread.counted_tokens()),
+            Some(system_prompt_tokens + 6)
+        );
+        assert!(warnings.borrow().is_empty());
+
+        thread.update(cx, |thread, cx| {
+            thread.insert_assistant_message(vec![MessageSegment::Text("It is Paris.".into())], cx);
+        });
+        let count = thread
+            .update(cx, |thread, cx| thread.count_tokens(&model, cx))
+            .await
+            .unwrap();
+        assert_eq!(count, system_prompt_tokens + 9);
+
+        let warning_fraction = (system_prompt_tokens + 8) as f32 / max_token_count as f32;
+        thread
+            .update(cx, |thread, cx| {
+                thread.refresh_token_count(warning_fraction, cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            *warnings.borrow(),
+            vec![(system_prompt_tokens + 9, max_token_count)]
+        );
+
+        thread.update(cx, |thread, cx| {
+            thread.insert_user_message("Thanks!", ContextLoadResult::default(), None, vec![], cx);
+        });
+        cx.executor().advance_clock(TOKEN_COUNT_DEBOUNCE);
+        cx.run_until_parked();
+        assert_eq!(
+            thread.read_with(cx, |thread, _| thread.counted_tokens()),
+            Some(system_prompt_tokens + 10)
+        );
+        assert_eq!(
+            *warnings.borrow(),
+            vec![(system_prompt_tokens + 9, max_token_count)]
+        );
+    }
+
     // Helper to create a model that returns errors
     enum TestError {
         Overloaded,
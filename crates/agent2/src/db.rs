@@ -3,7 +3,7 @@ use acp_thread::UserMessageId;
 use agent::{thread::DetailedSummaryState, thread_store};
 use agent_client_protocol as acp;
 use agent_settings::{AgentProfileId, CompletionMode};
-use anyhow::{Result, anyhow};
+use anyhow::{Context as _, Result, anyhow};
 use chrono::{DateTime, Utc};
 use collections::{HashMap, IndexMap};
 use futures::{FutureExt, future::Shared};
@@ -16,8 +16,9 @@ use sqlez::{
     connection::Connection,
     statement::Statement,
 };
-use std::sync::Arc;
+use std::{ops::Range, sync::Arc};
 use ui::{App, SharedString};
+use util::ResultExt as _;
 use zed_env_vars::ZED_STATELESS;
 
 pub type DbMessage = crate::Message;
@@ -32,6 +33,14 @@ pub struct DbThreadMetadata {
     pub updated_at: DateTime<Utc>,
 }
 
+/// A thread whose messages contain a search query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DbThreadMatch {
+    pub id: acp::SessionId,
+    /// The text surrounding the first match in the thread's messages.
+    pub snippet: SharedString,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DbThread {
     pub title: SharedString,
@@ -67,6 +76,37 @@ impl DbThread {
         }
     }
 
+    /// Returns the text around the first message text containing `query`, ignoring case.
+    fn search_snippet(&self, query: &str) -> Option<String> {
+        self.messages.iter().find_map(|message| {
+            let texts = match message {
+                DbMessage::User(message) => message
+                    .content
+                    .iter()
+                    .filter_map(|content| match content {
+                        UserMessageContent::Text(text) => Some(text.as_str()),
+                        UserMessageContent::Mention { .. } | UserMessageContent::Image(_) => None,
+                    })
+                    .collect::<Vec<_>>(),
+                DbMessage::Agent(message) => message
+                    .content
+                    .iter()
+                    .filter_map(|content| match content {
+                        AgentMessageContent::Text(text)
+                        | AgentMessageContent::Thinking { text, .. } => Some(text.as_str()),
+                        AgentMessageContent::RedactedThinking(_)
+                        | AgentMessageContent::ToolUse(_) => None,
+                    })
+                    .collect(),
+                DbMessage::Resume => Vec::new(),
+            };
+            texts.into_iter().find_map(|text| {
+                let range = find_ignoring_case(text, query)?;
+                Some(snippet_around(text, range))
+            })
+        })
+    }
+
     fn upgrade_from_agent_1(thread: agent::SerializedThread) -> Result<Self> {
         let mut messages = Vec::new();
         let mut request_token_usage = HashMap::default();
@@ -376,21 +416,81 @@ impl ThreadsDatabase {
 
             let rows = select(id.0)?;
             if let Some((data_type, data)) = rows.into_iter().next() {
-                let json_data = match data_type {
-                    DataType::Zstd => {
-                        let decompressed = zstd::decode_all(&data[..])?;
-                        String::from_utf8(decompressed)?
-                    }
-                    DataType::Json => String::from_utf8(data)?,
-                };
-                let thread = DbThread::from_json(json_data.as_bytes())?;
-                Ok(Some(thread))
+                Ok(Some(Self::deserialize_thread(data_type, data)?))
             } else {
                 Ok(None)
             }
         })
     }
 
+    /// Finds threads whose messages contain `query`, ignoring case, most recently
+    /// updated first, stopping once `limit` have been found.
+    ///
+    /// Threads are loaded one at a time, so that saving isn't blocked for the
+    /// whole search, and threads that fail to load are skipped.
+    pub fn search_threads(&self, query: String, limit: usize) -> Task<Result<Vec<DbThreadMatch>>> {
+        let connection = self.connection.clone();
+
+        self.executor.spawn(async move {
+            if query.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let ids = {
+                let connection = connection.lock();
+                let mut select = connection.select_bound::<(), Arc<str>>(indoc! {"
+                    SELECT id FROM threads ORDER BY updated_at DESC
+                "})?;
+                select(())?
+            };
+
+            let mut matches = Vec::new();
+            for id in ids {
+                if matches.len() >= limit {
+                    break;
+                }
+
+                let row = {
+                    let connection = connection.lock();
+                    let mut select =
+                        connection.select_bound::<Arc<str>, (DataType, Vec<u8>)>(indoc! {"
+                            SELECT data_type, data FROM threads WHERE id = ? LIMIT 1
+                        "})?;
+                    select(id.clone())?.into_iter().next()
+                };
+                // The thread was deleted since the search started.
+                let Some((data_type, data)) = row else {
+                    continue;
+                };
+                let Some(thread) = Self::deserialize_thread(data_type, data)
+                    .with_context(|| format!("loading thread {id} for search"))
+                    .log_err()
+                else {
+                    continue;
+                };
+                if let Some(snippet) = thread.search_snippet(&query) {
+                    matches.push(DbThreadMatch {
+                        id: acp::SessionId(id),
+                        snippet: snippet.into(),
+                    });
+                }
+            }
+
+            Ok(matches)
+        })
+    }
+
+    fn deserialize_thread(data_type: DataType, data: Vec<u8>) -> Result<DbThread> {
+        let json_data = match data_type {
+            DataType::Zstd => {
+                let decompressed = zstd::decode_all(&data[..])?;
+                String::from_utf8(decompressed)?
+            }
+            DataType::Json => String::from_utf8(data)?,
+        };
+        DbThread::from_json(json_data.as_bytes())
+    }
+
     pub fn save_thread(&self, id: acp::SessionId, thread: DbThread) -> Task<Result<()>> {
         let connection = self.connection.clone();
 
@@ -415,6 +515,56 @@ impl ThreadsDatabase {
     }
 }
 
+const THREAD_SEARCH_SNIPPET_CONTEXT: usize = 40;
+
+fn find_ignoring_case(text: &str, query: &str) -> Option<Range<usize>> {
+    let query = query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .collect::<Vec<_>>();
+    if query.is_empty() {
+        return None;
+    }
+
+    text.char_indices().find_map(|(start, _)| {
+        let mut remaining = query.as_slice();
+        for (offset, character) in text[start..].char_indices() {
+            for lowercase in character.to_lowercase() {
+                match remaining.split_first() {
+                    Some((expected, rest)) if *expected == lowercase => remaining = rest,
+                    _ => return None,
+                }
+            }
+            if remaining.is_empty() {
+                return Some(start..start + offset + character.len_utf8());
+            }
+        }
+        None
+    })
+}
+
+fn snippet_around(text: &str, range: Range<usize>) -> String {
+    let start = text[..range.start]
+        .char_indices()
+        .rev()
+        .nth(THREAD_SEARCH_SNIPPET_CONTEXT - 1)
+        .map_or(0, |(ix, _)| ix);
+    let end = text[range.end..]
+        .char_indices()
+        .nth(THREAD_SEARCH_SNIPPET_CONTEXT)
+        .map_or(text.len(), |(ix, _)| range.end + ix);
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    snippet.push_str(&text[start..end].replace('\n', " "));
+    if end < text.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -494,4 +644,103 @@ mod tests {
             "## Assistant\n\nHow're you doing?\n"
         );
     }
+
+    #[gpui::test]
+    async fn test_search_threads(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        init_test(fs, cx);
+        let db = cx.update(ThreadsDatabase::connect).await.unwrap();
+
+        let thread = |title: &str, user_text: &str, agent_text: &str, minutes_ago: i64| DbThread {
+            title: title.to_string().into(),
+            messages: vec![
+                DbMessage::User(UserMessage {
+                    id: UserMessageId::new(),
+                    content: vec![UserMessageContent::Text(user_text.into())],
+                }),
+                DbMessage::Agent(AgentMessage {
+                    content: vec![AgentMessageContent::Text(agent_text.into())],
+                    ..Default::default()
+                }),
+            ],
+            updated_at: Utc::now() - chrono::Duration::minutes(minutes_ago),
+            detailed_summary: None,
+            initial_project_snapshot: None,
+            cumulative_token_usage: Default::default(),
+            request_token_usage: Default::default(),
+            model: None,
+            completion_mode: None,
+            profile: None,
+        };
+        db.save_thread(
+            acp::SessionId("pasta".into()),
+            thread("Pasta", "How do I make carbonara?", "Use guanciale.", 2),
+        )
+        .await
+        .unwrap();
+        db.save_thread(
+            acp::SessionId("rice".into()),
+            thread("Rice", "What about risotto?", "Stir in the GUANCIALE.", 1),
+        )
+        .await
+        .unwrap();
+
+        // A row that can't be decoded is skipped rather than failing the search.
+        {
+            let connection = db.connection.lock();
+            let mut insert = connection
+                .exec_bound::<(Arc<str>, String, String, DataType, Vec<u8>)>(indoc! {"
+                    INSERT INTO threads (id, summary, updated_at, data_type, data) VALUES (?, ?, ?, ?, ?)
+                "})
+                .unwrap();
+            insert((
+                "corrupt".into(),
+                "Corrupt".into(),
+                Utc::now().to_rfc3339(),
+                DataType::Json,
+                b"not json".to_vec(),
+            ))
+            .unwrap();
+        }
+
+        let ids = |matches: Vec<DbThreadMatch>| {
+            matches
+                .into_iter()
+                .map(|thread_match| thread_match.id.0.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(db.search_threads("Guanciale".into(), 10).await.unwrap()),
+            ["rice", "pasta"]
+        );
+        assert_eq!(
+            ids(db.search_threads("CARBONARA".into(), 10).await.unwrap()),
+            ["pasta"]
+        );
+        assert_eq!(
+            ids(db.search_threads("guanciale".into(), 1).await.unwrap()),
+            ["rice"]
+        );
+        assert!(db.search_threads("".into(), 10).await.unwrap().is_empty());
+
+        let matches = db.search_threads("carbonara".into(), 10).await.unwrap();
+        assert_eq!(matches[0].snippet, "How do I make carbonara?");
+    }
+
+    #[test]
+    fn test_snippet_around() {
+        let text = format!("{}needle{}", "x".repeat(50), "y".repeat(50));
+        let range = find_ignoring_case(&text, "NEEDLE").unwrap();
+        assert_eq!(range, 50..56);
+        assert_eq!(
+            snippet_around(&text, range),
+            format!("…{}needle{}…", "x".repeat(40), "y".repeat(40))
+        );
+
+        let text = "Straße\nund Weg";
+        let range = find_ignoring_case(text, "STRASSE").map(|range| &text[range]);
+        assert_eq!(range, None);
+        let range = find_ignoring_case(text, "STRAßE\nUND").unwrap();
+        assert_eq!(snippet_around(text, range), "Straße und Weg");
+    }
 }
@@ -1,4 +1,4 @@
-use crate::{DbThreadMetadata, ThreadsDatabase};
+use crate::{DbThreadMatch, DbThreadMetadata, ThreadsDatabase};
 use acp_thread::MentionUri;
 use agent_client_protocol as acp;
 use anyhow::{Context as _, Result, anyhow};
@@ -16,6 +16,7 @@ use util::ResultExt as _;
 const MAX_RECENTLY_OPENED_ENTRIES: usize = 6;
 const RECENTLY_OPENED_THREADS_KEY: &str = "recent-agent-threads";
 const SAVE_RECENTLY_OPENED_ENTRIES_DEBOUNCE: Duration = Duration::from_millis(50);
+const MAX_THREAD_SEARCH_RESULTS: usize = 50;
 
 const DEFAULT_TITLE: &SharedString = &SharedString::new_static("New Thread");
 
@@ -140,6 +141,22 @@ impl HistoryStore {
         })
     }
 
+    /// Finds threads whose messages contain `query`, ignoring case, most recently
+    /// updated first.
+    pub fn search_thread_contents(
+        &self,
+        query: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<DbThreadMatch>>> {
+        let database_future = ThreadsDatabase::connect(cx);
+        cx.background_spawn(async move {
+            let database = database_future.await.map_err(|err| anyhow!(err))?;
+            database
+                .search_threads(query, MAX_THREAD_SEARCH_RESULTS)
+                .await
+        })
+    }
+
     pub fn delete_text_thread(
         &mut self,
         path: Arc<Path>,
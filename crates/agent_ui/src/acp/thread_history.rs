@@ -1,6 +1,6 @@
 use crate::acp::AcpThreadView;
 use crate::{AgentPanel, RemoveSelectedThread};
-use agent2::{HistoryEntry, HistoryStore};
+use agent2::{DbThreadMatch, HistoryEntry, HistoryStore};
 use chrono::{Datelike as _, Local, NaiveDate, TimeDelta};
 use editor::{Editor, EditorEvent};
 use fuzzy::StringMatchCandidate;
@@ -15,6 +15,7 @@ use ui::{
     HighlightedLabel, IconButtonShape, ListItem, ListItemSpacing, Tooltip, WithScrollbar,
     prelude::*,
 };
+use util::ResultExt as _;
 
 pub struct AcpThreadHistory {
     pub(crate) history_store: Entity<HistoryStore>,
@@ -41,6 +42,7 @@ enum ListItemType {
     SearchResult {
         entry: HistoryEntry,
         positions: Vec<usize>,
+        snippet: Option<SharedString>,
     },
 }
 
@@ -112,10 +114,17 @@ impl AcpThreadHistory {
         let entries = self
             .history_store
             .update(cx, |store, _| store.entries().collect());
-        let new_list_items = if self.search_query.is_empty() {
-            self.add_list_separators(entries, cx)
+        let (new_list_items, content_search) = if self.search_query.is_empty() {
+            (self.add_list_separators(entries, cx), None)
         } else {
-            self.filter_search_results(entries, cx)
+            let query = self.search_query.to_string();
+            let content_search = self
+                .history_store
+                .update(cx, |store, cx| store.search_thread_contents(query, cx));
+            (
+                self.filter_search_results(entries, cx),
+                Some(content_search),
+            )
         };
         let selected_history_entry = if preserve_selected_item {
             self.selected_history_entry().cloned()
@@ -145,6 +154,16 @@ impl AcpThreadHistory {
                 cx.notify();
             })
             .ok();
+
+            // Title matches are shown without waiting for the slower search of thread contents.
+            let Some(content_search) = content_search else {
+                return;
+            };
+            let content_matches = content_search.await.log_err().unwrap_or_default();
+            this.update(cx, |this, cx| {
+                this.add_content_search_results(content_matches, cx)
+            })
+            .ok();
         });
     }
 
@@ -209,12 +228,57 @@ impl AcpThreadHistory {
                     .map(|search_match| ListItemType::SearchResult {
                         entry: entries[search_match.candidate_id].clone(),
                         positions: search_match.positions,
+                        snippet: None,
                     })
                     .collect()
             }
         })
     }
 
+    /// Adds a snippet to title matches that also match on their contents, and lists
+    /// threads that only match on their contents after them.
+    fn add_content_search_results(
+        &mut self,
+        content_matches: Vec<DbThreadMatch>,
+        cx: &mut Context<Self>,
+    ) {
+        if content_matches.is_empty() {
+            return;
+        }
+
+        for content_match in content_matches {
+            let title_match = self.visible_items.iter_mut().find_map(|item| match item {
+                ListItemType::SearchResult {
+                    entry: HistoryEntry::AcpThread(thread),
+                    snippet,
+                    ..
+                } if thread.id == content_match.id => Some(snippet),
+                _ => None,
+            });
+            if let Some(snippet) = title_match {
+                *snippet = Some(content_match.snippet);
+                continue;
+            }
+
+            let Some(thread) = self
+                .history_store
+                .read(cx)
+                .thread_from_session_id(&content_match.id)
+                .cloned()
+            else {
+                continue;
+            };
+            self.visible_items.push(ListItemType::SearchResult {
+                entry: HistoryEntry::AcpThread(thread),
+                positions: Vec::new(),
+                snippet: Some(content_match.snippet),
+            });
+        }
+
+        self.set_selected_index(self.selected_index, Bias::Right, cx);
+        cx.notify();
+    }
+
     fn search_produced_no_matches(&self) -> bool {
         self.visible_items.is_empty() && !self.search_query.is_empty()
     }
@@ -352,13 +416,18 @@ impl AcpThreadHistory {
     fn render_list_item(&self, item: &ListItemType, ix: usize, cx: &Context<Self>) -> AnyElement {
         match item {
             ListItemType::Entry { entry, format } => self
-                .render_history_entry(entry, *format, ix, Vec::default(), cx)
+                .render_history_entry(entry, *format, ix, Vec::default(), None, cx)
                 .into_any(),
-            ListItemType::SearchResult { entry, positions } => self.render_history_entry(
+            ListItemType::SearchResult {
+                entry,
+                positions,
+                snippet,
+            } => self.render_history_entry(
                 entry,
                 EntryTimeFormat::DateAndTime,
                 ix,
                 positions.clone(),
+                snippet.clone(),
                 cx,
             ),
             ListItemType::BucketSeparator(bucket) => div()
@@ -380,6 +449,7 @@ impl AcpThreadHistory {
         format: EntryTimeFormat,
         ix: usize,
         highlight_positions: Vec<usize>,
+        snippet: Option<SharedString>,
         cx: &Context<Self>,
     ) -> AnyElement {
         let selected = ix == self.selected_index;
@@ -396,20 +466,32 @@ impl AcpThreadHistory {
                     .toggle_state(selected)
                     .spacing(ListItemSpacing::Sparse)
                     .start_slot(
-                        h_flex()
+                        v_flex()
                             .w_full()
-                            .gap_2()
-                            .justify_between()
                             .child(
-                                HighlightedLabel::new(entry.title(), highlight_positions)
-                                    .size(LabelSize::Small)
-                                    .truncate(),
+                                h_flex()
+                                    .w_full()
+                                    .gap_2()
+                                    .justify_between()
+                                    .child(
+                                        HighlightedLabel::new(entry.title(), highlight_positions)
+                                            .size(LabelSize::Small)
+                                            .truncate(),
+                                    )
+                                    .child(
+                                        Label::new(thread_timestamp)
+                                            .color(Color::Muted)
+                                            .size(LabelSize::XSmall),
+                                    ),
                             )
-                            .child(
-                                Label::new(thread_timestamp)
-                                    .color(Color::Muted)
-                                    .size(LabelSize::XSmall),
-                            ),
+                            .when_some(snippet, |this, snippet| {
+                                this.child(
+                                    Label::new(snippet)
+                                        .color(Color::Muted)
+                                        .size(LabelSize::XSmall)
+                                        .truncate(),
+                                )
+                            }),
                     )
                     .on_hover(cx.listener(move |this, is_hovered, _window, cx| {
                         if *is_hovered {
@@ -268,18 +268,11 @@ pub fn init(
     }
     assistant_slash_command::init(cx);
     agent::init(fs.clone(), cx);
-    agent_panel::init(cx);
+    init_panel_and_inline_assistants(fs.clone(), prompt_builder, client.telemetry().clone(), cx);
     context_server_configuration::init(language_registry.clone(), fs.clone(), cx);
     TextThreadEditor::init(cx);
 
     register_slash_commands(cx);
-    inline_assistant::init(
-        fs.clone(),
-        prompt_builder.clone(),
-        client.telemetry().clone(),
-        cx,
-    );
-    terminal_inline_assistant::init(fs.clone(), prompt_builder, client.telemetry().clone(), cx);
     cx.observe_new(move |workspace, window, cx| {
         ConfigureContextServerModal::register(workspace, language_registry.clone(), window, cx)
     })
@@ -297,6 +290,32 @@ pub fn init(
     .detach();
 }
 
+/// Registers the agent panel's actions and the inline assistants.
+fn init_panel_and_inline_assistants(
+    fs: Arc<dyn Fs>,
+    prompt_builder: Arc<PromptBuilder>,
+    telemetry: Arc<client::telemetry::Telemetry>,
+    cx: &mut App,
+) {
+    agent_panel::init(cx);
+    inline_assistant::init(fs.clone(), prompt_builder.clone(), telemetry.clone(), cx);
+    terminal_inline_assistant::init(fs, prompt_builder, telemetry, cx);
+}
+
+/// Registers the agent actions and inline assistants without the command palette
+/// filtering and language model setup done by [`init`], so tests can drive inline
+/// assist directly.
+#[cfg(any(test, feature = "test-support"))]
+pub fn init_inline_assist_for_test(
+    fs: Arc<dyn Fs>,
+    prompt_builder: Arc<PromptBuilder>,
+    telemetry: Arc<client::telemetry::Telemetry>,
+    cx: &mut App,
+) {
+    AgentSettings::register(cx);
+    init_panel_and_inline_assistants(fs, prompt_builder, telemetry, cx);
+}
+
 fn update_command_palette_filter(cx: &mut App) {
     let disable_ai = DisableAiSettings::get_global(cx).disable_ai;
     CommandPaletteFilter::update_global(cx, |filter, _| {
@@ -433,3 +452,66 @@ fn register_slash_commands(cx: &mut App) {
     })
     .detach();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clock::FakeSystemClock;
+    use editor::EditorSettings;
+    use fs::FakeFs;
+    use gpui::{SemanticVersion, TestAppContext};
+    use http_client::FakeHttpClient;
+    use project::Project;
+    use std::{cell::Cell, rc::Rc};
+    use theme::ThemeSettings;
+    use util::path;
+    use workspace::Workspace;
+
+    #[gpui::test]
+    async fn test_init_inline_assist_for_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            client::init_settings(cx);
+            Project::init_settings(cx);
+            workspace::init_settings(cx);
+            ThemeSettings::register(cx);
+            release_channel::init(SemanticVersion::default(), cx);
+            EditorSettings::register(cx);
+            prompt_store::init(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/dir"), serde_json::json!({})).await;
+        cx.update(|cx| {
+            let client = Client::new(
+                Arc::new(FakeSystemClock::new()),
+                FakeHttpClient::with_404_response(),
+                cx,
+            );
+            let prompt_builder = Arc::new(PromptBuilder::new(None).unwrap());
+            init_inline_assist_for_test(fs.clone(), prompt_builder, client.telemetry().clone(), cx);
+            assert!(cx.has_global::<InlineAssistant>());
+            assert!(cx.has_global::<terminal_inline_assistant::TerminalInlineAssistant>());
+        });
+
+        let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+        let (_workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project, window, cx));
+        cx.run_until_parked();
+        cx.update(|window, cx| {
+            assert!(window.is_action_available(&NewThread::default(), cx));
+        });
+
+        // The workspace's handler consumes the action, so it never reaches the app.
+        let unhandled_count = Rc::new(Cell::new(0));
+        cx.update(|_, cx| {
+            let unhandled_count = unhandled_count.clone();
+            cx.on_action(move |_: &NewThread, _| unhandled_count.set(unhandled_count.get() + 1));
+        });
+        cx.dispatch_action(NewThread::default());
+        cx.run_until_parked();
+        assert_eq!(unhandled_count.get(), 0);
+    }
+}
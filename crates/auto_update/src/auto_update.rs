@@ -11,7 +11,10 @@ use paths::remote_servers_dir;
 use release_channel::{AppCommitSha, ReleaseChannel};
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsStore};
-use smol::{fs, io::AsyncReadExt};
+use smol::{
+    fs,
+    io::{AsyncReadExt, AsyncWriteExt},
+};
 use smol::{fs::File, process::Command};
 use std::mem;
 use std::{
@@ -22,12 +25,13 @@ use std::{
     ffi::OsString,
     path::{Path, PathBuf},
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 use workspace::Workspace;
 
 const SHOULD_SHOW_UPDATE_NOTIFICATION_KEY: &str = "auto-updater-should-show-updated-notification";
 const POLL_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const DOWNLOAD_PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
 
 actions!(
     auto_update,
@@ -60,10 +64,20 @@ pub enum VersionCheckType {
 pub enum AutoUpdateStatus {
     Idle,
     Checking,
-    Downloading { version: VersionCheckType },
-    Installing { version: VersionCheckType },
-    Updated { version: VersionCheckType },
-    Errored { error: Arc<anyhow::Error> },
+    Downloading {
+        version: VersionCheckType,
+        downloaded_bytes: u64,
+        total_bytes: Option<u64>,
+    },
+    Installing {
+        version: VersionCheckType,
+    },
+    Updated {
+        version: VersionCheckType,
+    },
+    Errored {
+        error: Arc<anyhow::Error>,
+    },
 }
 
 impl AutoUpdateStatus {
@@ -571,13 +585,15 @@ impl AutoUpdater {
         this.update(&mut cx, |this, cx| {
             this.status = AutoUpdateStatus::Downloading {
                 version: newer_version.clone(),
+                downloaded_bytes: 0,
+                total_bytes: None,
             };
             cx.notify();
         })?;
 
         let installer_dir = InstallerDir::new().await?;
         let target_path = Self::target_path(&installer_dir).await?;
-        download_release(&target_path, fetched_release_data, client, &cx).await?;
+        download_release(&this, &target_path, fetched_release_data, client, &mut cx).await?;
 
         this.update(&mut cx, |this, cx| {
             this.status = AutoUpdateStatus::Installing {
@@ -768,10 +784,11 @@ fn build_remote_server_update_request_body(cx: &AsyncApp) -> Result<UpdateReques
 }
 
 async fn download_release(
+    this: &Entity<AutoUpdater>,
     target_path: &Path,
     release: JsonRelease,
     client: Arc<HttpClientWithUrl>,
-    cx: &AsyncApp,
+    cx: &mut AsyncApp,
 ) -> Result<()> {
     let mut target_file = File::create(&target_path).await?;
 
@@ -800,7 +817,41 @@ async fn download_release(
     })?);
 
     let mut response = client.get(&release.url, request_body, true).await?;
-    smol::io::copy(response.body_mut(), &mut target_file).await?;
+    let total_bytes = response
+        .headers()
+        .get(http_client::http::header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok()?.parse::<u64>().ok());
+
+    let body = response.body_mut();
+    let mut buffer = vec![0; 64 * 1024];
+    let mut downloaded_bytes = 0;
+    let mut last_reported_at = Instant::now();
+    loop {
+        let bytes_read = body.read(&mut buffer).await?;
+        if bytes_read == 0 {
+            break;
+        }
+        target_file.write_all(&buffer[..bytes_read]).await?;
+        downloaded_bytes += bytes_read as u64;
+
+        // Reporting every chunk would re-render the status bar thousands of times per update.
+        if last_reported_at.elapsed() >= DOWNLOAD_PROGRESS_INTERVAL {
+            last_reported_at = Instant::now();
+            this.update(cx, |this, cx| {
+                if let AutoUpdateStatus::Downloading {
+                    downloaded_bytes: status_downloaded_bytes,
+                    total_bytes: status_total_bytes,
+                    ..
+                } = &mut this.status
+                {
+                    *status_downloaded_bytes = downloaded_bytes;
+                    *status_total_bytes = total_bytes;
+                    cx.notify();
+                }
+            })?;
+        }
+    }
+    target_file.flush().await?;
     log::info!("downloaded update. path:{:?}", target_path);
 
     Ok(())
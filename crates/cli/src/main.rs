@@ -127,7 +127,12 @@ fn parse_path_with_position(argument_str: &str) -> anyhow::Result<String> {
     let canonicalized = match Path::new(argument_str).canonicalize() {
         Ok(existing_path) => PathWithPosition::from_path(existing_path),
         Err(_) => {
-            let path = PathWithPosition::parse_str(argument_str);
+            let mut path = PathWithPosition::parse_str(argument_str);
+            // A name like `notes@2024` is only an offset into `notes` when that file exists,
+            // otherwise it's the name of a new file.
+            if path.offset.is_some() && !path.path.is_file() {
+                path = PathWithPosition::from_path(PathBuf::from(argument_str));
+            }
             let curdir = env::current_dir().context("retrieving current directory")?;
             path.map_path(|path| match fs::canonicalize(&path) {
                 Ok(path) => Ok(path),
@@ -23,6 +23,12 @@ pub trait DapLocator: Send + Sync {
         adapter: &DebugAdapterName,
     ) -> Option<DebugScenario>;
 
+    /// Determines whether this locator is able to produce a debug target for the given build
+    /// without running it, so that callers can fail early when running it would be pointless.
+    fn can_locate(&self, _build_config: &SpawnInTerminal) -> bool {
+        true
+    }
+
     async fn run(&self, build_config: SpawnInTerminal) -> Result<DebugRequest>;
 }
 
@@ -83,6 +89,23 @@ impl DapRegistry {
         self.0.read().locators.clone()
     }
 
+    /// Looks up a locator by name, listing the registered ones if it isn't found.
+    pub fn locator(&self, name: &str) -> Result<Arc<dyn DapLocator>> {
+        let locators = &self.0.read().locators;
+        if let Some(locator) = locators.get(name) {
+            return Ok(locator.clone());
+        }
+        let mut available = locators
+            .keys()
+            .map(|name| name.as_ref())
+            .collect::<Vec<_>>();
+        available.sort_unstable();
+        anyhow::bail!(
+            "Couldn't find locator `{name}`. Available locators: {}",
+            available.join(", ")
+        )
+    }
+
     pub fn adapter(&self, name: &str) -> Option<Arc<dyn DebugAdapter>> {
         self.0.read().adapters.get(name).cloned()
     }
@@ -91,3 +114,46 @@ impl DapRegistry {
         self.0.read().adapters.keys().cloned().collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeLocator(&'static str);
+
+    #[async_trait]
+    impl DapLocator for FakeLocator {
+        fn name(&self) -> SharedString {
+            SharedString::new_static(self.0)
+        }
+
+        async fn create_scenario(
+            &self,
+            _build_config: &TaskTemplate,
+            _resolved_label: &str,
+            _adapter: &DebugAdapterName,
+        ) -> Option<DebugScenario> {
+            None
+        }
+
+        async fn run(&self, _build_config: SpawnInTerminal) -> Result<DebugRequest> {
+            anyhow::bail!("not supported")
+        }
+    }
+
+    #[test]
+    fn test_unknown_locator_lists_available_locators() {
+        let registry = DapRegistry::default();
+        registry.add_locator(Arc::new(FakeLocator("node-locator")));
+        registry.add_locator(Arc::new(FakeLocator("cargo-locator")));
+
+        assert_eq!(
+            registry.locator("node-locator").unwrap().name(),
+            "node-locator"
+        );
+        assert_eq!(
+            registry.locator("cargo").err().unwrap().to_string(),
+            "Couldn't find locator `cargo`. Available locators: cargo-locator, node-locator"
+        );
+    }
+}
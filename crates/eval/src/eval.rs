@@ -16,7 +16,7 @@ use clap::Parser;
 use client::{Client, ProxySettings, UserStore};
 use collections::{HashMap, HashSet};
 use extension::ExtensionHostProxy;
-use futures::future;
+use futures::future::{self, Either};
 use gpui::http_client::read_proxy_from_env;
 use gpui::{App, AppContext, Application, AsyncApp, Entity, UpdateGlobal};
 use gpui_tokio::Tokio;
@@ -32,10 +32,13 @@ use settings::{Settings, SettingsStore};
 use std::cell::RefCell;
 use std::collections::VecDeque;
 use std::env;
+use std::fmt;
 use std::path::{Path, PathBuf};
+use std::pin::pin;
 use std::rc::Rc;
 use std::str::FromStr;
 use std::sync::{Arc, LazyLock};
+use std::time::Duration;
 use util::ResultExt as _;
 
 static CARGO_MANIFEST_DIR: LazyLock<PathBuf> =
@@ -61,6 +64,12 @@ struct Args {
     /// Maximum number of examples to run concurrently.
     #[arg(long, default_value = "4")]
     concurrency: usize,
+    /// How many seconds an example may run before it's reported as timed out.
+    #[arg(long, default_value = "600")]
+    timeout_secs: u64,
+    /// Worktrees of commits that haven't been evaluated in this many days are deleted.
+    #[arg(long, default_value = "7")]
+    worktree_max_age_days: u64,
 }
 
 fn main() {
@@ -100,6 +109,11 @@ fn main() {
     let zed_commit_sha = commit_sha_for_path(&root_dir);
     let zed_branch_name = git_branch_for_path(&root_dir);
     let args = Args::parse();
+    instance::remove_stale_worktrees(
+        &worktrees_dir,
+        Duration::from_secs(args.worktree_max_age_days * 24 * 60 * 60),
+    )
+    .log_err();
     let languages: HashSet<String> = args.languages.into_iter().collect();
 
     let http_client = Arc::new(ReqwestClient::new());
@@ -126,8 +140,9 @@ fn main() {
 
         let mut cumulative_tool_metrics = ToolMetrics::default();
 
-        let agent_model = load_model(&args.model, cx).unwrap();
-        let judge_model = load_model(&args.judge_model, cx).unwrap();
+        let Some((agent_model, judge_model)) = load_models(&args, cx) else {
+            return;
+        };
 
         LanguageModelRegistry::global(cx).update(cx, |registry, cx| {
             registry.set_default_model(Some(agent_model.clone()), cx);
@@ -253,6 +268,8 @@ fn main() {
                             actual_origin == repo_url,
                             "remote origin {actual_origin} does not match expected origin {repo_url}"
                         );
+                        // Forget worktrees deleted by `remove_stale_worktrees`.
+                        run_git(&repo_path, &["worktree", "prune"]).await?;
                     }
                 }
             }
@@ -263,6 +280,7 @@ fn main() {
                 example_instance.fetch().await?;
             }
 
+            let timeout = Duration::from_secs(args.timeout_secs);
             let examples = Rc::new(RefCell::new(VecDeque::from(examples)));
             let results_by_example_name = Rc::new(RefCell::new(HashMap::default()));
 
@@ -281,10 +299,18 @@ fn main() {
                             break;
                         };
                         let result = async {
-                            example.setup().await?;
-                            let run_output = cx
-                                .update(|cx| example.run(model.clone(), app_state.clone(), cx))?
-                                .await?;
+                            let run_output = run_with_timeout(
+                                async {
+                                    example.setup().await?;
+                                    cx.update(|cx| {
+                                        example.run(model.clone(), app_state.clone(), cx)
+                                    })?
+                                    .await
+                                },
+                                cx.background_executor().timer(timeout),
+                                timeout,
+                            )
+                            .await?;
                             let judge_output = judge_example(
                                 example.clone(),
                                 judge_model.clone(),
@@ -323,6 +349,31 @@ fn main() {
     });
 }
 
+/// Reported in place of an example's result when it runs for longer than `--timeout-secs`.
+#[derive(Debug)]
+struct ExampleTimedOut(Duration);
+
+impl fmt::Display for ExampleTimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "⏱ Timed out after {}s", self.0.as_secs())
+    }
+}
+
+impl std::error::Error for ExampleTimedOut {}
+
+/// Runs an example until it completes or `timer` fires, so that a hung model call
+/// doesn't stall the whole run.
+async fn run_with_timeout<T>(
+    example: impl Future<Output = anyhow::Result<T>>,
+    timer: impl Future<Output = ()>,
+    timeout: Duration,
+) -> anyhow::Result<T> {
+    match future::select(pin!(example), pin!(timer)).await {
+        Either::Left((result, _)) => result,
+        Either::Right(((), _)) => Err(ExampleTimedOut(timeout).into()),
+    }
+}
+
 /// Subset of `workspace::AppState` needed by `HeadlessAssistant`, with additional fields.
 pub struct AgentAppState {
     pub languages: Arc<LanguageRegistry>,
@@ -469,6 +520,21 @@ pub fn find_model(
         })
 }
 
+/// Loads the agent and judge models named by `args`, reporting the error and quitting
+/// when either isn't available.
+fn load_models(args: &Args, cx: &mut App) -> Option<(ConfiguredModel, ConfiguredModel)> {
+    match load_model(&args.model, cx)
+        .and_then(|agent_model| Ok((agent_model, load_model(&args.judge_model, cx)?)))
+    {
+        Ok(models) => Some(models),
+        Err(err) => {
+            eprintln!("{err}");
+            cx.quit();
+            None
+        }
+    }
+}
+
 pub fn load_model(model_name: &str, cx: &mut App) -> anyhow::Result<ConfiguredModel> {
     let model = {
         let model_registry = LanguageModelRegistry::read_global(cx);
@@ -569,6 +635,7 @@ fn print_report(
     let mut thread_scores = Vec::new();
     let mut programmatic_scores = Vec::new();
     let mut error_count = 0;
+    let mut timeout_count = 0;
 
     for (example_name, results) in results_by_example_name.iter_mut() {
         print_h2(example_name);
@@ -582,7 +649,11 @@ fn print_report(
             match result {
                 Err(err) => {
                     display_error_row(&mut table_rows, example.repetition, err.to_string())?;
-                    error_count += 1;
+                    if err.is::<ExampleTimedOut>() {
+                        timeout_count += 1;
+                    } else {
+                        error_count += 1;
+                    }
                     programmatic_scores.push(0.0);
                     diff_scores.push(0.0);
                     thread_scores.push(0.0);
@@ -680,6 +751,10 @@ fn print_report(
             println!("\n{error_count} examples failed to run!");
         }
 
+        if timeout_count > 0 {
+            println!("\n{timeout_count} examples timed out!");
+        }
+
         let programmatic_score_count = programmatic_scores.len();
         if programmatic_score_count > 0 {
             let average_programmatic_score = (programmatic_scores.into_iter().sum::<f32>()
@@ -727,3 +802,77 @@ fn print_report(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_args() {
+        let args = Args::parse_from(["eval"]);
+        assert_eq!(args.model, "anthropic/claude-3-7-sonnet-latest");
+        assert_eq!(args.judge_model, "anthropic/claude-3-7-sonnet-latest");
+
+        let args = Args::parse_from([
+            "eval",
+            "--model",
+            "openai/gpt-4o",
+            "--judge-model",
+            "anthropic/claude-sonnet-4",
+        ]);
+        assert_eq!(args.model, "openai/gpt-4o");
+        assert_eq!(args.judge_model, "anthropic/claude-sonnet-4");
+    }
+
+    #[gpui::test]
+    fn test_load_models(cx: &mut gpui::TestAppContext) {
+        cx.update(|cx| {
+            LanguageModelRegistry::test(cx);
+
+            let args =
+                Args::parse_from(["eval", "--model", "fake/fake", "--judge-model", "fake/fake"]);
+            let (agent_model, judge_model) = load_models(&args, cx).unwrap();
+            assert_eq!(agent_model.model.id().0, "fake");
+            assert_eq!(agent_model.provider.id().0, "fake");
+            assert_eq!(judge_model.model.id().0, "fake");
+
+            let args = Args::parse_from([
+                "eval",
+                "--model",
+                "fake/missing",
+                "--judge-model",
+                "fake/fake",
+            ]);
+            assert!(load_models(&args, cx).is_none());
+            let args = Args::parse_from([
+                "eval",
+                "--model",
+                "fake/fake",
+                "--judge-model",
+                "missing/fake",
+            ]);
+            assert!(load_models(&args, cx).is_none());
+        });
+    }
+
+    #[test]
+    fn test_example_timeout() {
+        let timeout = Duration::from_secs(600);
+
+        let result = futures::executor::block_on(run_with_timeout(
+            future::pending::<anyhow::Result<()>>(),
+            future::ready(()),
+            timeout,
+        ));
+        let err = result.unwrap_err();
+        assert!(err.is::<ExampleTimedOut>());
+        assert_eq!(err.to_string(), "⏱ Timed out after 600s");
+
+        let result = futures::executor::block_on(run_with_timeout(
+            async { anyhow::Ok(42) },
+            future::pending(),
+            timeout,
+        ));
+        assert_eq!(result.unwrap(), 42);
+    }
+}
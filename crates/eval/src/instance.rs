@@ -35,6 +35,9 @@ use crate::{AgentAppState, ToolMetrics};
 
 pub const ZED_REPO_URL: &str = "https://github.com/zed-industries/zed.git";
 
+/// Touched whenever an example uses the worktrees of a commit, so stale ones can be removed.
+const WORKTREE_LAST_USED_FILE: &str = ".last-used";
+
 #[derive(Clone)]
 pub struct ExampleInstance {
     pub thread: Rc<dyn Example>,
@@ -183,15 +186,26 @@ impl ExampleInstance {
             std::fs::write(worktree_path.join(".rules"), std::fs::read(".rules")?)?;
         }
 
+        std::fs::write(
+            self.worktrees_dir
+                .join(&meta.revision)
+                .join(WORKTREE_LAST_USED_FILE),
+            [],
+        )?;
+
         std::fs::create_dir_all(&self.run_directory)?;
 
         Ok(())
     }
 
+    /// Worktrees are grouped by the commit they check out, so that switching an example to a
+    /// new base starts from a fresh worktree and the old one can be garbage-collected.
     pub fn worktree_path(&self) -> PathBuf {
+        let meta = self.thread.meta();
         self.worktrees_dir
+            .join(&meta.revision)
             .join(self.worktree_name())
-            .join(self.thread.meta().repo_name())
+            .join(meta.repo_name())
     }
 
     pub fn run(
@@ -807,6 +821,24 @@ pub fn repo_path_for_url(repos_dir: &Path, repo_url: &str) -> PathBuf {
     Path::new(repos_dir).join(repo_name)
 }
 
+/// Removes the worktrees of every commit that no example has used within `max_age`.
+pub fn remove_stale_worktrees(worktrees_dir: &Path, max_age: Duration) -> Result<()> {
+    for entry in fs::read_dir(worktrees_dir)? {
+        let commit_dir = entry?.path();
+        if !commit_dir.is_dir() {
+            continue;
+        }
+        let last_used = fs::metadata(commit_dir.join(WORKTREE_LAST_USED_FILE))
+            .or_else(|_| fs::metadata(&commit_dir))?
+            .modified()?;
+        if last_used.elapsed().is_ok_and(|age| age > max_age) {
+            fs::remove_dir_all(&commit_dir)
+                .with_context(|| format!("removing {}", commit_dir.display()))?;
+        }
+    }
+    Ok(())
+}
+
 pub async fn run_git(repo_path: &Path, args: &[&str]) -> Result<String> {
     let output = new_smol_command("git")
         .current_dir(repo_path)
@@ -1175,6 +1207,111 @@ impl ThreadDialog {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::example::ExampleMetadata;
+    use agent_settings::AgentProfileId;
+    use async_trait::async_trait;
+    use std::time::SystemTime;
+
+    struct StubExample {
+        url: String,
+        revision: String,
+    }
+
+    #[async_trait(?Send)]
+    impl Example for StubExample {
+        fn meta(&self) -> ExampleMetadata {
+            ExampleMetadata {
+                name: "stub".to_string(),
+                url: self.url.clone(),
+                revision: self.revision.clone(),
+                language_server: None,
+                max_assertions: None,
+                profile_id: AgentProfileId::default(),
+                existing_thread_json: None,
+                max_turns: None,
+            }
+        }
+
+        async fn conversation(&self, _cx: &mut ExampleContext) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("eval-{name}-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_fetch_skips_known_revision() {
+        let temp_dir = temp_dir("fetch");
+        let origin_path = temp_dir.join("origin");
+        fs::create_dir_all(&origin_path).unwrap();
+
+        smol::block_on(async {
+            run_git(&origin_path, &["init"]).await.unwrap();
+            run_git(
+                &origin_path,
+                &[
+                    "-c",
+                    "user.name=eval",
+                    "-c",
+                    "user.email=eval@example.com",
+                    "commit",
+                    "--allow-empty",
+                    "-m",
+                    "base",
+                ],
+            )
+            .await
+            .unwrap();
+            let revision = run_git(&origin_path, &["rev-parse", "HEAD"]).await.unwrap();
+            let url = format!("file://{}", origin_path.display());
+
+            let mut example = ExampleInstance::new(
+                Rc::new(StubExample {
+                    url: url.clone(),
+                    revision,
+                }),
+                &temp_dir.join("repos"),
+                &temp_dir.join("runs"),
+                &temp_dir.join("worktrees"),
+                0,
+            );
+            fs::create_dir_all(&example.repo_path).unwrap();
+            run_git(&example.repo_path, &["init"]).await.unwrap();
+            run_git(&example.repo_path, &["remote", "add", "origin", &url])
+                .await
+                .unwrap();
+            example.fetch().await.unwrap();
+
+            // With the origin gone, any `git fetch` in the second run would fail.
+            fs::remove_dir_all(&origin_path).unwrap();
+            example.fetch().await.unwrap();
+        });
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_remove_stale_worktrees() {
+        let worktrees_dir = temp_dir("worktrees");
+        for (revision, age_in_days) in [("fresh", 1), ("stale", 30)] {
+            let commit_dir = worktrees_dir.join(revision);
+            fs::create_dir_all(commit_dir.join("example-0")).unwrap();
+            File::create(commit_dir.join(WORKTREE_LAST_USED_FILE))
+                .unwrap()
+                .set_modified(SystemTime::now() - Duration::from_secs(age_in_days * 24 * 60 * 60))
+                .unwrap();
+        }
+
+        remove_stale_worktrees(&worktrees_dir, Duration::from_secs(7 * 24 * 60 * 60)).unwrap();
+
+        assert!(worktrees_dir.join("fresh").is_dir());
+        assert!(!worktrees_dir.join("stale").exists());
+        fs::remove_dir_all(&worktrees_dir).unwrap();
+    }
 
     #[test]
     fn test_parse_judge_output() {
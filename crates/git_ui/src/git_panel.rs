@@ -64,6 +64,7 @@ use ui::{
 use util::paths::PathStyle;
 use util::{ResultExt, TryFutureExt, maybe};
 use workspace::SERIALIZATION_THROTTLE_TIME;
+use zed_actions::git_panel::ToggleFocus;
 
 use cloud_llm_client::CompletionIntent;
 use workspace::{
@@ -77,8 +78,6 @@ actions!(
     [
         /// Closes the git panel.
         Close,
-        /// Toggles focus on the git panel.
-        ToggleFocus,
         /// Opens the git panel menu.
         OpenMenu,
         /// Focuses on the commit message editor.
@@ -128,6 +128,11 @@ impl EntityMap {
     }
 
     pub fn read<T: 'static>(&self, entity: &Entity<T>) -> &T {
+        self.read_any(entity)
+    }
+
+    /// Like [EntityMap::read], for a dynamically typed handle already known to hold a `T`.
+    pub(crate) fn read_any<T: 'static>(&self, entity: &AnyEntity) -> &T {
         self.assert_valid_context(entity);
         let mut accessed_entities = self.accessed_entities.borrow_mut();
         accessed_entities.insert(entity.entity_id);
@@ -9,8 +9,16 @@ use collections::FxHashSet;
 use refineable::Refineable;
 use std::mem;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 use std::{any::TypeId, fmt, ops::Range};
 
+/// Called with a view's entity id each time an [`AnyView`] is drawn, along with how long its
+/// render function took and whether its cached layout and paint were reused instead. Reused
+/// views don't render, so they're reported with a zero duration.
+///
+/// Installed with [`App::set_view_render_hook`]. Nothing is measured while no hook is set.
+pub type ViewRenderHook = fn(EntityId, Duration, bool);
+
 struct AnyViewState {
     prepaint_range: Range<PrepaintStateIndex>,
     paint_range: Range<PaintIndex>,
@@ -23,6 +31,7 @@ struct ViewCacheKey {
     bounds: Bounds<Pixels>,
     content_mask: ContentMask<Pixels>,
     text_style: TextStyle,
+    content_hash: Option<u64>,
 }
 
 impl<V: Render> Element for Entity<V> {
@@ -84,6 +93,7 @@ pub struct AnyView {
     entity: AnyEntity,
     render: fn(&AnyView, &mut Window, &mut App) -> AnyElement,
     cached_style: Option<Rc<StyleRefinement>>,
+    content_hash: Option<u64>,
 }
 
 impl<V: Render> From<Entity<V>> for AnyView {
@@ -92,6 +102,7 @@ impl<V: Render> From<Entity<V>> for AnyView {
             entity: value.into_any(),
             render: any_view::render::<V>,
             cached_style: None,
+            content_hash: None,
         }
     }
 }
@@ -105,6 +116,45 @@ impl AnyView {
         self
     }
 
+    /// Like [AnyView::cached], but the caller supplies a hash of everything the view renders,
+    /// including anything read from globals such as the theme. The view is rendered again
+    /// whenever the hash changes, even without [Context::notify], and its cache is kept
+    /// across [Window::refresh] as long as the hash stays the same. It is still rendered
+    /// again whenever a cached view it's nested in is.
+    pub fn cached_by_hash(mut self, style: StyleRefinement, hash: u64) -> Self {
+        self.cached_style = Some(style.into());
+        self.content_hash = Some(hash);
+        self
+    }
+
+    /// Forces this view to be rendered again on the next frame, discarding the layout and
+    /// paint cached by [AnyView::cached], without notifying the view's observers.
+    /// This method should only be called outside of element drawing.
+    #[track_caller]
+    pub fn invalidate_cache(&self, window: &mut Window) {
+        window.invalidator.invalidate_view_cache(self.entity_id());
+    }
+
+    /// Returns whether this view's cached layout and paint were reused in the last frame
+    /// drawn by the window, rather than being rendered again. Only views using
+    /// [AnyView::cached] or [AnyView::cached_by_hash] are ever reused.
+    pub fn was_cached_last_frame(&self, window: &Window) -> bool {
+        window
+            .rendered_frame
+            .reused_views
+            .contains(&self.entity_id())
+    }
+
+    fn render_element(&self, window: &mut Window, cx: &mut App) -> AnyElement {
+        let Some(hook) = cx.view_render_hook else {
+            return (self.render)(self, window, cx);
+        };
+        let start = Instant::now();
+        let element = (self.render)(self, window, cx);
+        hook(self.entity_id(), start.elapsed(), false);
+        element
+    }
+
     /// Convert this to a weak handle.
     pub fn downgrade(&self) -> AnyWeakView {
         AnyWeakView {
@@ -122,10 +172,23 @@ impl AnyView {
                 entity,
                 render: self.render,
                 cached_style: self.cached_style,
+                content_hash: self.content_hash,
             }),
         }
     }
 
+    /// Returns whether the underlying view is of type `T`, without consuming this handle.
+    pub fn entity_type_is<T: 'static>(&self) -> bool {
+        self.entity.entity_type == TypeId::of::<T>()
+    }
+
+    /// Borrows the underlying view as a `T`, without consuming or cloning this handle.
+    /// Returns `None` if the view is not of the specified type.
+    pub fn downcast_ref<'a, T: 'static>(&self, cx: &'a App) -> Option<&'a T> {
+        self.entity_type_is::<T>()
+            .then(|| cx.entities.read_any(&self.entity))
+    }
+
     /// Gets the [TypeId] of the underlying view.
     pub fn entity_type(&self) -> TypeId {
         self.entity.entity_type
@@ -175,7 +238,7 @@ impl Element for AnyView {
                     (layout_id, None)
                 }
                 _ => {
-                    let mut element = (self.render)(self, window, cx);
+                    let mut element = self.render_element(window, cx);
                     let layout_id = element.request_layout(window, cx);
                     (layout_id, Some(element))
                 }
@@ -209,8 +272,10 @@ impl Element for AnyView {
                         && element_state.cache_key.bounds == bounds
                         && element_state.cache_key.content_mask == content_mask
                         && element_state.cache_key.text_style == text_style
+                        && element_state.cache_key.content_hash == self.content_hash
                         && !window.dirty_views.contains(&self.entity_id())
-                        && !window.refreshing
+                        && (!window.refreshing
+                            || (self.content_hash.is_some() && !window.rendering_cached_ancestor))
                     {
                         let prepaint_start = window.prepaint_index();
                         window.reuse_prepaint(element_state.prepaint_range.clone());
@@ -218,14 +283,20 @@ impl Element for AnyView {
                             .extend_accessed(&element_state.accessed_entities);
                         let prepaint_end = window.prepaint_index();
                         element_state.prepaint_range = prepaint_start..prepaint_end;
+                        window.next_frame.reused_views.insert(self.entity_id());
+                        if let Some(hook) = cx.view_render_hook {
+                            hook(self.entity_id(), Duration::ZERO, true);
+                        }
 
                         return (None, element_state);
                     }
 
                     let refreshing = mem::replace(&mut window.refreshing, true);
+                    let rendering_cached_ancestor =
+                        mem::replace(&mut window.rendering_cached_ancestor, true);
                     let prepaint_start = window.prepaint_index();
                     let (mut element, accessed_entities) = cx.detect_accessed_entities(|cx| {
-                        let mut element = (self.render)(self, window, cx);
+                        let mut element = self.render_element(window, cx);
                         element.layout_as_root(bounds.size.into(), window, cx);
                         element.prepaint_at(bounds.origin, window, cx);
                         element
@@ -233,6 +304,7 @@ impl Element for AnyView {
 
                     let prepaint_end = window.prepaint_index();
                     window.refreshing = refreshing;
+                    window.rendering_cached_ancestor = rendering_cached_ancestor;
 
                     (
                         Some(element),
@@ -244,6 +316,7 @@ impl Element for AnyView {
                                 bounds,
                                 content_mask,
                                 text_style,
+                                content_hash: self.content_hash,
                             },
                         },
                     )
@@ -323,6 +396,7 @@ impl AnyWeakView {
             entity,
             render: self.render,
             cached_style: None,
+            content_hash: None,
         })
     }
 }
@@ -371,3 +445,267 @@ impl Render for EmptyView {
         Empty
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+        time::Duration,
+    };
+
+    use crate::{
+        self as gpui, AnyView, AppContext as _, AvailableSpace, Context, EntityId, IntoElement,
+        ParentElement, Render, StyleRefinement, Styled, TestAppContext, Window, div, px, size,
+    };
+
+    struct CountingView {
+        render_count: Rc<Cell<usize>>,
+    }
+
+    impl Render for CountingView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            self.render_count.set(self.render_count.get() + 1);
+            div().size_full()
+        }
+    }
+
+    struct CachedParentView {
+        child: AnyView,
+    }
+
+    impl Render for CachedParentView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().size_full().child(
+                self.child
+                    .clone()
+                    .cached(StyleRefinement::default().size_full()),
+            )
+        }
+    }
+
+    struct HashCachedParentView {
+        child: AnyView,
+        content_hash: u64,
+    }
+
+    impl Render for HashCachedParentView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().size_full().child(
+                self.child
+                    .clone()
+                    .cached_by_hash(StyleRefinement::default().size_full(), self.content_hash),
+            )
+        }
+    }
+
+    #[gpui::test]
+    fn test_invalidate_cache(cx: &mut TestAppContext) {
+        let render_count = Rc::new(Cell::new(0));
+        let (parent, cx) = cx.add_window_view(|_, cx| CachedParentView {
+            child: cx
+                .new(|_| CountingView {
+                    render_count: render_count.clone(),
+                })
+                .into(),
+        });
+        let initial_render_count = render_count.get();
+        assert!(initial_render_count > 0);
+
+        parent.update(cx, |_, cx| cx.notify());
+        cx.run_until_parked();
+        assert_eq!(render_count.get(), initial_render_count);
+
+        cx.update(|window, cx| parent.read(cx).child.invalidate_cache(window));
+        cx.run_until_parked();
+        assert_eq!(render_count.get(), initial_render_count + 1);
+    }
+
+    #[gpui::test]
+    fn test_cached_by_hash(cx: &mut TestAppContext) {
+        let render_count = Rc::new(Cell::new(0));
+        let (parent, cx) = cx.add_window_view(|_, cx| HashCachedParentView {
+            child: cx
+                .new(|_| CountingView {
+                    render_count: render_count.clone(),
+                })
+                .into(),
+            content_hash: 1,
+        });
+        let initial_render_count = render_count.get();
+        assert!(initial_render_count > 0);
+
+        parent.update(cx, |_, cx| cx.notify());
+        cx.run_until_parked();
+        assert_eq!(render_count.get(), initial_render_count);
+
+        cx.update(|window, _| window.refresh());
+        cx.run_until_parked();
+        assert_eq!(render_count.get(), initial_render_count);
+
+        parent.update(cx, |parent, cx| {
+            parent.content_hash = 2;
+            cx.notify();
+        });
+        cx.run_until_parked();
+        assert_eq!(render_count.get(), initial_render_count + 1);
+
+        parent.update(cx, |_, cx| cx.notify());
+        cx.run_until_parked();
+        assert_eq!(render_count.get(), initial_render_count + 1);
+    }
+
+    #[gpui::test]
+    fn test_cached_by_hash_nested_in_cached_view(cx: &mut TestAppContext) {
+        let render_count = Rc::new(Cell::new(0));
+        let (parent, cx) = cx.add_window_view(|_, cx| CachedParentView {
+            child: cx
+                .new(|cx| HashCachedParentView {
+                    child: cx
+                        .new(|_| CountingView {
+                            render_count: render_count.clone(),
+                        })
+                        .into(),
+                    content_hash: 1,
+                })
+                .into(),
+        });
+        let initial_render_count = render_count.get();
+        assert!(initial_render_count > 0);
+
+        // Rendering the cached view again forces its hash-cached child to render again,
+        // even though the hash is unchanged.
+        let middle = cx.update(|_, cx| {
+            parent
+                .read(cx)
+                .child
+                .clone()
+                .downcast::<HashCachedParentView>()
+                .unwrap()
+        });
+        middle.update(cx, |_, cx| cx.notify());
+        cx.run_until_parked();
+        assert_eq!(render_count.get(), initial_render_count + 1);
+
+        parent.update(cx, |_, cx| cx.notify());
+        cx.run_until_parked();
+        assert_eq!(render_count.get(), initial_render_count + 1);
+    }
+
+    #[gpui::test]
+    fn test_was_cached_last_frame(cx: &mut TestAppContext) {
+        let render_count = Rc::new(Cell::new(0));
+        let (parent, cx) = cx.add_window_view(|_, cx| CachedParentView {
+            child: cx
+                .new(|_| CountingView {
+                    render_count: render_count.clone(),
+                })
+                .into(),
+        });
+        let child = cx.update(|_, cx| parent.read(cx).child.clone());
+
+        parent.update(cx, |_, cx| cx.notify());
+        cx.run_until_parked();
+        assert!(cx.update(|window, _| child.was_cached_last_frame(window)));
+
+        parent.update(cx, |_, cx| cx.notify());
+        cx.run_until_parked();
+        assert!(cx.update(|window, _| child.was_cached_last_frame(window)));
+
+        let child_entity = child.clone().downcast::<CountingView>().unwrap();
+        child_entity.update(cx, |_, cx| cx.notify());
+        cx.run_until_parked();
+        assert!(!cx.update(|window, _| child.was_cached_last_frame(window)));
+    }
+
+    #[gpui::test]
+    fn test_view_render_hook(cx: &mut TestAppContext) {
+        thread_local! {
+            static RENDERS: RefCell<Vec<(EntityId, Duration, bool)>> = const { RefCell::new(Vec::new()) };
+        }
+        fn record_render(entity_id: EntityId, duration: Duration, cached: bool) {
+            RENDERS.with_borrow_mut(|renders| renders.push((entity_id, duration, cached)));
+        }
+
+        let render_count = Rc::new(Cell::new(0));
+        let (parent, cx) = cx.add_window_view(|_, cx| CachedParentView {
+            child: cx
+                .new(|_| CountingView {
+                    render_count: render_count.clone(),
+                })
+                .into(),
+        });
+        let child = cx.update(|_, cx| parent.read(cx).child.clone());
+        let child_renders = || {
+            RENDERS.with_borrow(|renders| {
+                renders
+                    .iter()
+                    .filter(|(entity_id, _, _)| *entity_id == child.entity_id())
+                    .map(|(_, duration, cached)| (*duration, *cached))
+                    .collect::<Vec<_>>()
+            })
+        };
+        cx.update(|_, cx| cx.set_view_render_hook(Some(record_render)));
+
+        let child_entity = child.clone().downcast::<CountingView>().unwrap();
+        child_entity.update(cx, |_, cx| cx.notify());
+        cx.run_until_parked();
+        parent.update(cx, |_, cx| cx.notify());
+        cx.run_until_parked();
+
+        let renders = child_renders();
+        assert_eq!(
+            renders
+                .iter()
+                .map(|(_, cached)| *cached)
+                .collect::<Vec<_>>(),
+            [false, true]
+        );
+        assert_eq!(renders[1].0, Duration::ZERO);
+
+        cx.update(|_, cx| cx.set_view_render_hook(None));
+        child_entity.update(cx, |_, cx| cx.notify());
+        cx.run_until_parked();
+        assert_eq!(child_renders().len(), 2);
+    }
+
+    #[gpui::test]
+    fn test_downcast_ref(cx: &mut TestAppContext) {
+        let render_count = Rc::new(Cell::new(3));
+        let view: AnyView = cx
+            .new(|_| CountingView {
+                render_count: render_count.clone(),
+            })
+            .into();
+
+        assert!(view.entity_type_is::<CountingView>());
+        assert!(!view.entity_type_is::<CachedParentView>());
+        cx.update(|cx| {
+            let counting_view = view.downcast_ref::<CountingView>(cx).unwrap();
+            assert_eq!(counting_view.render_count.get(), 3);
+            assert!(view.downcast_ref::<CachedParentView>(cx).is_none());
+        });
+    }
+
+    struct FixedSizeView;
+
+    impl Render for FixedSizeView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().w(px(120.)).h(px(30.))
+        }
+    }
+
+    #[gpui::test]
+    fn test_measure(cx: &mut TestAppContext) {
+        let (view, cx) = cx.add_window_view(|_, _| FixedSizeView);
+
+        let measured_size = cx.update(|window, cx| {
+            window.measure(
+                &view,
+                size(AvailableSpace::MinContent, AvailableSpace::MinContent),
+                cx,
+            )
+        });
+        assert_eq!(measured_size, size(px(120.), px(30.)));
+    }
+}
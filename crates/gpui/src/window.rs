@@ -125,6 +125,18 @@ impl WindowInvalidator {
         }
     }
 
+    /// Marks the view as dirty for the next frame without notifying its observers.
+    #[track_caller]
+    pub fn invalidate_view_cache(&self, entity: EntityId) {
+        let mut inner = self.inner.borrow_mut();
+        debug_assert!(
+            inner.draw_phase == DrawPhase::None,
+            "this method cannot be called during element drawing"
+        );
+        inner.dirty_views.insert(entity);
+        inner.dirty = true;
+    }
+
     pub fn is_dirty(&self) -> bool {
         self.inner.borrow().dirty
     }
@@ -678,6 +690,8 @@ pub(crate) struct Frame {
     pub(crate) input_handlers: Vec<Option<PlatformInputHandler>>,
     pub(crate) tooltip_requests: Vec<Option<TooltipRequest>>,
     pub(crate) cursor_styles: Vec<CursorStyleRequest>,
+    /// Views whose cached prepaint was reused in this frame rather than rendered again.
+    pub(crate) reused_views: FxHashSet<EntityId>,
     #[cfg(any(test, feature = "test-support"))]
     pub(crate) debug_bounds: FxHashMap<String, Bounds<Pixels>>,
     #[cfg(any(feature = "inspector", debug_assertions))]
@@ -724,6 +738,7 @@ impl Frame {
             input_handlers: Vec::new(),
             tooltip_requests: Vec::new(),
             cursor_styles: Vec::new(),
+            reused_views: FxHashSet::default(),
 
             #[cfg(any(test, feature = "test-support"))]
             debug_bounds: FxHashMap::default(),
@@ -746,6 +761,7 @@ impl Frame {
         self.input_handlers.clear();
         self.tooltip_requests.clear();
         self.cursor_styles.clear();
+        self.reused_views.clear();
         self.hitboxes.clear();
         self.window_control_hitboxes.clear();
         self.deferred_draws.clear();
@@ -864,6 +880,9 @@ pub struct Window {
     pub(crate) needs_present: Rc<Cell<bool>>,
     pub(crate) last_input_timestamp: Rc<Cell<Instant>>,
     pub(crate) refreshing: bool,
+    /// Whether a cached view is being rendered again, which forces its cached descendants
+    /// to render again too, whatever their content hash.
+    pub(crate) rendering_cached_ancestor: bool,
     pub(crate) activation_observers: SubscriberSet<(), AnyObserver>,
     pub(crate) focus: Option<FocusId>,
     focus_enabled: bool,
@@ -1247,6 +1266,7 @@ impl Window {
             needs_present,
             last_input_timestamp,
             refreshing: false,
+            rendering_cached_ancestor: false,
             activation_observers: SubscriberSet::new(),
             focus: None,
             focus_enabled: true,
@@ -3260,6 +3280,29 @@ impl Window {
         self.layout_engine = Some(layout_engine);
     }
 
+    /// Lay out the given view within the given available space and return its size, without painting it.
+    ///
+    /// This method should only be called outside of element drawing.
+    pub fn measure<V: Render>(
+        &mut self,
+        view: &Entity<V>,
+        available_space: Size<AvailableSpace>,
+        cx: &mut App,
+    ) -> Size<Pixels> {
+        debug_assert!(
+            self.invalidator.not_drawing(),
+            "this method cannot be called during element drawing"
+        );
+
+        self.invalidator.set_phase(DrawPhase::Prepaint);
+        let mut element = AnyElement::new(view.clone());
+        let size = element.layout_as_root(available_space, self, cx);
+        drop(element);
+        self.layout_engine.as_mut().unwrap().clear();
+        self.invalidator.set_phase(DrawPhase::None);
+        size
+    }
+
     /// Obtain the bounds computed for the given LayoutId relative to the window. This method will usually be invoked by
     /// GPUI itself automatically in order to pass your element its `Bounds` automatically.
     ///
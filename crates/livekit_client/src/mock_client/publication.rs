@@ -1,6 +1,12 @@
+use std::sync::atomic::Ordering::SeqCst;
+
 use gpui::App;
+use postage::sink::Sink as _;
 
-use crate::{RemoteTrack, TrackSid, test::WeakRoom};
+use crate::{
+    Participant, ParticipantIdentity, RemoteParticipant, RemoteTrack, RoomEvent, TrackPublication,
+    TrackSid, test::WeakRoom,
+};
 
 #[derive(Clone, Debug)]
 pub struct LocalTrackPublication {
@@ -61,6 +67,9 @@ impl RemoteTrackPublication {
     }
 
     pub fn is_muted(&self) -> bool {
+        if self.is_interrupted() {
+            return true;
+        }
         if let Some(room) = self.room.upgrade() {
             room.test_server()
                 .is_track_muted(&room.token(), &self.sid)
@@ -71,21 +80,70 @@ impl RemoteTrackPublication {
     }
 
     pub fn is_enabled(&self) -> bool {
+        if self.is_interrupted() {
+            return false;
+        }
         if let Some(room) = self.room.upgrade() {
-            !room.0.lock().paused_audio_tracks.contains(&self.sid)
+            let state = room.0.lock();
+            let paused_tracks = match self.track {
+                RemoteTrack::Audio(_) => &state.paused_audio_tracks,
+                RemoteTrack::Video(_) => &state.paused_video_tracks,
+            };
+            !paused_tracks.contains(&self.sid)
         } else {
             false
         }
     }
 
+    fn is_interrupted(&self) -> bool {
+        match &self.track {
+            RemoteTrack::Audio(track) => track.server_track.interrupted.load(SeqCst),
+            RemoteTrack::Video(_) => false,
+        }
+    }
+
     pub fn set_enabled(&self, enabled: bool, _cx: &App) {
+        self.update_enabled(enabled);
+    }
+
+    pub(crate) fn update_enabled(&self, enabled: bool) {
         if let Some(room) = self.room.upgrade() {
-            let paused_audio_tracks = &mut room.0.lock().paused_audio_tracks;
-            if enabled {
-                paused_audio_tracks.remove(&self.sid);
+            let mut state = room.0.lock();
+            let paused_tracks = match self.track {
+                RemoteTrack::Audio(_) => &mut state.paused_audio_tracks,
+                RemoteTrack::Video(_) => &mut state.paused_video_tracks,
+            };
+            let changed = if enabled {
+                paused_tracks.remove(&self.sid)
             } else {
-                paused_audio_tracks.insert(self.sid.clone());
+                paused_tracks.insert(self.sid.clone())
+            };
+            if changed {
+                let participant = Participant::Remote(RemoteParticipant {
+                    identity: self.publisher_id(),
+                    room: self.room.clone(),
+                });
+                let publication = TrackPublication::Remote(self.clone());
+                let event = if enabled {
+                    RoomEvent::TrackUnmuted {
+                        participant,
+                        publication,
+                    }
+                } else {
+                    RoomEvent::TrackMuted {
+                        participant,
+                        publication,
+                    }
+                };
+                state.updates_tx.blocking_send(event).ok();
             }
         }
     }
+
+    fn publisher_id(&self) -> ParticipantIdentity {
+        match &self.track {
+            RemoteTrack::Audio(track) => track.publisher_id(),
+            RemoteTrack::Video(track) => track.publisher_id(),
+        }
+    }
 }
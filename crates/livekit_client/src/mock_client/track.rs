@@ -1,7 +1,7 @@
-use std::sync::Arc;
+use std::sync::{Arc, atomic::Ordering::SeqCst};
 
 use crate::{
-    ParticipantIdentity, TrackSid,
+    ParticipantIdentity, RemoteTrack, RemoteTrackPublication, TrackSid,
     test::{TestServerAudioTrack, TestServerVideoTrack, WeakRoom},
 };
 
@@ -14,7 +14,7 @@ pub struct LocalAudioTrack {}
 #[derive(Clone, Debug)]
 pub struct RemoteVideoTrack {
     pub(crate) server_track: Arc<TestServerVideoTrack>,
-    pub(crate) _room: WeakRoom,
+    pub(crate) room: WeakRoom,
 }
 
 #[derive(Clone, Debug)]
@@ -33,6 +33,9 @@ impl RemoteAudioTrack {
     }
 
     pub fn enabled(&self) -> bool {
+        if self.server_track.interrupted.load(SeqCst) {
+            return false;
+        }
         if let Some(room) = self.room.upgrade() {
             !room
                 .0
@@ -53,4 +56,25 @@ impl RemoteVideoTrack {
     pub fn publisher_id(&self) -> ParticipantIdentity {
         self.server_track.publisher_id.clone()
     }
+
+    pub fn enabled(&self) -> bool {
+        if let Some(room) = self.room.upgrade() {
+            !room
+                .0
+                .lock()
+                .paused_video_tracks
+                .contains(&self.server_track.sid)
+        } else {
+            false
+        }
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        RemoteTrackPublication {
+            sid: self.sid(),
+            room: self.room.clone(),
+            track: RemoteTrack::Video(self.clone()),
+        }
+        .update_enabled(enabled);
+    }
 }
@@ -8,9 +8,12 @@ use gpui::{App, AsyncApp, BackgroundExecutor};
 use livekit_api::{proto, token};
 use parking_lot::Mutex;
 use postage::{mpsc, sink::Sink};
-use std::sync::{
-    Arc, Weak,
-    atomic::{AtomicBool, Ordering::SeqCst},
+use std::{
+    sync::{
+        Arc, Weak,
+        atomic::{AtomicBool, Ordering::SeqCst},
+    },
+    time::Duration,
 };
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
@@ -130,7 +133,7 @@ impl TestServer {
             for server_track in &room.video_tracks {
                 let track = RemoteTrack::Video(RemoteVideoTrack {
                     server_track: server_track.clone(),
-                    _room: client_room.downgrade(),
+                    room: client_room.downgrade(),
                 });
                 client_room
                     .0
@@ -319,7 +322,7 @@ impl TestServer {
             if *room_identity != identity {
                 let track = RemoteTrack::Video(RemoteVideoTrack {
                     server_track: server_track.clone(),
-                    _room: client_room.downgrade(),
+                    room: client_room.downgrade(),
                 });
                 let publication = RemoteTrackPublication {
                     sid: sid.clone(),
@@ -376,6 +379,7 @@ impl TestServer {
             sid: sid.clone(),
             publisher_id: identity.clone(),
             muted: AtomicBool::new(false),
+            interrupted: AtomicBool::new(false),
         });
 
         room.audio_tracks.push(server_track.clone());
@@ -430,46 +434,53 @@ impl TestServer {
             .with_context(|| format!("room {room_name} does not exist"))?;
         if let Some(track) = room
             .audio_tracks
-            .iter_mut()
+            .iter()
             .find(|track| track.sid == *track_sid)
         {
             track.muted.store(muted, SeqCst);
-            for (id, client_room) in room.client_rooms.iter() {
-                if *id != identity {
-                    let participant = Participant::Remote(RemoteParticipant {
-                        identity: identity.clone(),
-                        room: client_room.downgrade(),
-                    });
-                    let track = RemoteTrack::Audio(RemoteAudioTrack {
-                        server_track: track.clone(),
-                        room: client_room.downgrade(),
-                    });
-                    let publication = TrackPublication::Remote(RemoteTrackPublication {
-                        sid: track_sid.clone(),
-                        room: client_room.downgrade(),
-                        track,
-                    });
-
-                    let event = if muted {
-                        RoomEvent::TrackMuted {
-                            participant,
-                            publication,
-                        }
-                    } else {
-                        RoomEvent::TrackUnmuted {
-                            participant,
-                            publication,
-                        }
-                    };
-
-                    client_room
-                        .0
-                        .lock()
-                        .updates_tx
-                        .blocking_send(event)
-                        .unwrap();
-                }
-            }
+            room.broadcast_audio_track_state(track, &identity, muted);
+        }
+        Ok(())
+    }
+
+    /// Simulates a transient network interruption of an audio track: subscribers
+    /// see the track disabled and muted until `duration` has elapsed.
+    pub async fn interrupt_track(&self, track_sid: &TrackSid, duration: Duration) -> Result<()> {
+        self.set_track_interrupted(track_sid, true)?;
+        self.executor.timer(duration).await;
+        self.set_track_interrupted(track_sid, false)
+    }
+
+    /// Interrupts an audio track `count` times in a row, keeping it interrupted
+    /// and then restored for `interval` each time.
+    pub async fn flap_track(
+        &self,
+        track_sid: &TrackSid,
+        count: usize,
+        interval: Duration,
+    ) -> Result<()> {
+        for _ in 0..count {
+            self.interrupt_track(track_sid, interval).await?;
+            self.executor.timer(interval).await;
+        }
+        Ok(())
+    }
+
+    fn set_track_interrupted(&self, track_sid: &TrackSid, interrupted: bool) -> Result<()> {
+        let server_rooms = self.rooms.lock();
+        let (room, track) = server_rooms
+            .values()
+            .find_map(|room| {
+                let track = room
+                    .audio_tracks
+                    .iter()
+                    .find(|track| track.sid == *track_sid)?;
+                Some((room, track))
+            })
+            .with_context(|| format!("audio track {track_sid} does not exist"))?;
+        // A track that is muted anyway looks the same to subscribers either way.
+        if track.interrupted.swap(interrupted, SeqCst) != interrupted && !track.muted.load(SeqCst) {
+            room.broadcast_audio_track_state(track, &track.publisher_id, interrupted);
         }
         Ok(())
     }
@@ -507,7 +518,7 @@ impl TestServer {
             .iter()
             .map(|track| RemoteVideoTrack {
                 server_track: track.clone(),
-                _room: client_room.downgrade(),
+                room: client_room.downgrade(),
             })
             .collect())
     }
@@ -535,6 +546,31 @@ impl TestServer {
             .collect())
     }
 
+    fn published_tracks(
+        &self,
+        token: &str,
+        identity: &ParticipantIdentity,
+    ) -> Result<Vec<TrackSid>> {
+        let claims = livekit_api::token::validate(token, &self.secret_key)?;
+        let room_name = claims.video.room.unwrap();
+
+        let server_rooms = self.rooms.lock();
+        let room = server_rooms
+            .get(&*room_name)
+            .with_context(|| format!("room {room_name} does not exist"))?;
+        let audio = room
+            .audio_tracks
+            .iter()
+            .filter(|track| track.publisher_id == *identity)
+            .map(|track| track.sid.clone());
+        let video = room
+            .video_tracks
+            .iter()
+            .filter(|track| track.publisher_id == *identity)
+            .map(|track| track.sid.clone());
+        Ok(audio.chain(video).collect())
+    }
+
     async fn simulate_random_delay(&self) {
         #[cfg(any(test, feature = "test-support"))]
         self.executor.simulate_random_delay().await;
@@ -549,6 +585,52 @@ struct TestServerRoom {
     participant_permissions: HashMap<ParticipantIdentity, proto::ParticipantPermission>,
 }
 
+impl TestServerRoom {
+    fn broadcast_audio_track_state(
+        &self,
+        track: &Arc<TestServerAudioTrack>,
+        publisher: &ParticipantIdentity,
+        muted: bool,
+    ) {
+        for (id, client_room) in self.client_rooms.iter() {
+            if id != publisher {
+                let participant = Participant::Remote(RemoteParticipant {
+                    identity: publisher.clone(),
+                    room: client_room.downgrade(),
+                });
+                let remote_track = RemoteTrack::Audio(RemoteAudioTrack {
+                    server_track: track.clone(),
+                    room: client_room.downgrade(),
+                });
+                let publication = TrackPublication::Remote(RemoteTrackPublication {
+                    sid: track.sid.clone(),
+                    room: client_room.downgrade(),
+                    track: remote_track,
+                });
+
+                let event = if muted {
+                    RoomEvent::TrackMuted {
+                        participant,
+                        publication,
+                    }
+                } else {
+                    RoomEvent::TrackUnmuted {
+                        participant,
+                        publication,
+                    }
+                };
+
+                client_room
+                    .0
+                    .lock()
+                    .updates_tx
+                    .blocking_send(event)
+                    .unwrap();
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct TestServerVideoTrack {
     pub(crate) sid: TrackSid,
@@ -561,6 +643,7 @@ pub(crate) struct TestServerAudioTrack {
     pub(crate) sid: TrackSid,
     pub(crate) publisher_id: ParticipantIdentity,
     pub(crate) muted: AtomicBool,
+    pub(crate) interrupted: AtomicBool,
 }
 
 pub struct TestApiClient {
@@ -633,6 +716,7 @@ pub(crate) struct RoomState {
     pub(crate) local_identity: ParticipantIdentity,
     pub(crate) connection_state: ConnectionState,
     pub(crate) paused_audio_tracks: HashSet<TrackSid>,
+    pub(crate) paused_video_tracks: HashSet<TrackSid>,
     pub(crate) updates_tx: mpsc::Sender<RoomEvent>,
 }
 
@@ -650,6 +734,7 @@ impl std::fmt::Debug for RoomState {
             .field("local_identity", &self.local_identity)
             .field("connection_state", &self.connection_state)
             .field("paused_audio_tracks", &self.paused_audio_tracks)
+            .field("paused_video_tracks", &self.paused_video_tracks)
             .finish()
     }
 }
@@ -684,6 +769,7 @@ impl Room {
             token: token.to_string(),
             connection_state: ConnectionState::Disconnected,
             paused_audio_tracks: Default::default(),
+            paused_video_tracks: Default::default(),
             updates_tx,
         })));
 
@@ -706,6 +792,12 @@ impl Room {
             .unwrap()
     }
 
+    pub fn tracks_for_participant(&self, identity: &ParticipantIdentity) -> Vec<TrackSid> {
+        self.test_server()
+            .published_tracks(&self.token(), identity)
+            .unwrap()
+    }
+
     pub(crate) fn test_server(&self) -> Arc<TestServer> {
         TestServer::get(&self.0.lock().url).unwrap()
     }
@@ -755,3 +847,256 @@ impl WeakRoom {
         self.0.upgrade().map(Room)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use livekit_api::Client as _;
+    use postage::stream::Stream as _;
+
+    fn drain_track_mute_events(events: &mut mpsc::Receiver<RoomEvent>) -> Vec<(TrackSid, bool)> {
+        let mut mute_events = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            match event {
+                RoomEvent::TrackMuted { publication, .. } => {
+                    mute_events.push((publication.sid(), true))
+                }
+                RoomEvent::TrackUnmuted { publication, .. } => {
+                    mute_events.push((publication.sid(), false))
+                }
+                _ => {}
+            }
+        }
+        mute_events
+    }
+
+    /// Starts a server at `url` with a single room, and connects "user-a" and "user-b" to it.
+    async fn connect_two_participants(
+        url: &str,
+        cx: &mut TestAppContext,
+    ) -> (
+        Arc<TestServer>,
+        (Room, mpsc::Receiver<RoomEvent>),
+        (Room, mpsc::Receiver<RoomEvent>),
+    ) {
+        let server =
+            TestServer::create(url.into(), "devkey".into(), "secret".into(), cx.executor())
+                .unwrap();
+        let api_client = server.create_api_client();
+        api_client.create_room("room".into()).await.unwrap();
+
+        let participant_a = Room::connect(
+            server.url.clone(),
+            api_client.room_token("room", "user-a").unwrap(),
+            &mut cx.to_async(),
+        )
+        .await
+        .unwrap();
+        let participant_b = Room::connect(
+            server.url.clone(),
+            api_client.room_token("room", "user-b").unwrap(),
+            &mut cx.to_async(),
+        )
+        .await
+        .unwrap();
+        (server, participant_a, participant_b)
+    }
+
+    #[gpui::test]
+    async fn test_interrupt_audio_track(cx: &mut TestAppContext) {
+        let (server, (room_a, _events_a), (room_b, mut events_b)) =
+            connect_two_participants("http://livekit.interrupt.test", cx).await;
+
+        let (publication, _stream) = room_a
+            .publish_local_microphone_track("microphone".into(), false, &mut cx.to_async())
+            .await
+            .unwrap();
+        let sid = publication.sid();
+        let track = server.audio_tracks(room_b.token()).unwrap().remove(0);
+        let remote_publication = room_b.remote_participants()
+            [&ParticipantIdentity("user-a".to_string())]
+            .track_publications()
+            .remove(&sid)
+            .unwrap();
+        assert!(track.enabled());
+        assert!(remote_publication.is_enabled() && !remote_publication.is_muted());
+        assert!(drain_track_mute_events(&mut events_b).is_empty());
+
+        let interruption = cx.executor().spawn({
+            let server = server.clone();
+            let sid = sid.clone();
+            async move { server.interrupt_track(&sid, Duration::from_secs(1)).await }
+        });
+        cx.run_until_parked();
+        assert!(!track.enabled());
+        assert!(!remote_publication.is_enabled() && remote_publication.is_muted());
+        assert_eq!(
+            drain_track_mute_events(&mut events_b),
+            [(sid.clone(), true)]
+        );
+
+        cx.executor().advance_clock(Duration::from_secs(1));
+        cx.run_until_parked();
+        interruption.await.unwrap();
+        assert!(track.enabled());
+        assert!(remote_publication.is_enabled() && !remote_publication.is_muted());
+        assert_eq!(
+            drain_track_mute_events(&mut events_b),
+            [(sid.clone(), false)]
+        );
+
+        let flapping = cx.executor().spawn({
+            let server = server.clone();
+            let sid = sid.clone();
+            async move { server.flap_track(&sid, 2, Duration::from_millis(100)).await }
+        });
+        let mut observed = Vec::new();
+        for _ in 0..4 {
+            cx.run_until_parked();
+            observed.push(track.enabled());
+            cx.executor().advance_clock(Duration::from_millis(100));
+        }
+        flapping.await.unwrap();
+        assert_eq!(observed, [false, true, false, true]);
+        assert_eq!(
+            drain_track_mute_events(&mut events_b),
+            [
+                (sid.clone(), true),
+                (sid.clone(), false),
+                (sid.clone(), true),
+                (sid, false),
+            ]
+        );
+
+        drop((room_a, room_b));
+        cx.run_until_parked();
+        server.teardown().unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_remote_publication_set_enabled_emits_mute_events(cx: &mut TestAppContext) {
+        let (server, (room_a, _events_a), (room_b, mut events_b)) =
+            connect_two_participants("http://livekit.set-enabled.test", cx).await;
+
+        let (local_publication, _stream) = room_a
+            .publish_local_microphone_track("microphone".into(), false, &mut cx.to_async())
+            .await
+            .unwrap();
+        let sid = local_publication.sid();
+        let Ok(RoomEvent::TrackSubscribed { publication, .. }) = events_b.try_recv() else {
+            panic!("expected a track subscription event");
+        };
+        assert_eq!(publication.sid(), sid);
+        assert!(publication.is_enabled());
+
+        cx.update(|cx| publication.set_enabled(false, cx));
+        assert!(!publication.is_enabled());
+        let Ok(RoomEvent::TrackMuted {
+            participant,
+            publication: muted_publication,
+        }) = events_b.try_recv()
+        else {
+            panic!("expected a track muted event");
+        };
+        assert_eq!(participant.identity(), ParticipantIdentity("user-a".into()));
+        assert_eq!(muted_publication.sid(), sid);
+
+        cx.update(|cx| publication.set_enabled(false, cx));
+        assert!(drain_track_mute_events(&mut events_b).is_empty());
+
+        cx.update(|cx| publication.set_enabled(true, cx));
+        assert!(publication.is_enabled());
+        assert_eq!(drain_track_mute_events(&mut events_b), [(sid, false)]);
+
+        drop((room_a, room_b));
+        cx.run_until_parked();
+        server.teardown().unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_remote_video_track_set_enabled(cx: &mut TestAppContext) {
+        let (server, (room_a, _events_a), (room_b, mut events_b)) =
+            connect_two_participants("http://livekit.video-enabled.test", cx).await;
+
+        let sid = server
+            .publish_video_track(room_a.token(), LocalVideoTrack {})
+            .await
+            .unwrap();
+        let Ok(RoomEvent::TrackSubscribed { publication, .. }) = events_b.try_recv() else {
+            panic!("expected a track subscription event");
+        };
+        let track = server.video_tracks(room_b.token()).unwrap().remove(0);
+        assert_eq!(track.sid(), sid);
+        assert!(track.enabled());
+        assert!(publication.is_enabled());
+
+        track.set_enabled(false);
+        assert!(!track.enabled());
+        assert!(!publication.is_enabled());
+        assert!(!room_b.0.lock().paused_audio_tracks.contains(&sid));
+        assert_eq!(
+            drain_track_mute_events(&mut events_b),
+            [(sid.clone(), true)]
+        );
+
+        cx.update(|cx| publication.set_enabled(true, cx));
+        assert!(track.enabled());
+        assert_eq!(
+            drain_track_mute_events(&mut events_b),
+            [(sid.clone(), false)]
+        );
+
+        server.disconnect_client("user-b".into()).await;
+        drop(room_b);
+        assert!(!track.enabled());
+        assert!(!publication.is_enabled());
+
+        drop(room_a);
+        cx.run_until_parked();
+        server.teardown().unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_tracks_for_participant(cx: &mut TestAppContext) {
+        let (server, (room_a, _events_a), (room_b, _events_b)) =
+            connect_two_participants("http://livekit.participant-tracks.test", cx).await;
+
+        let (publication_a, _stream_a) = room_a
+            .publish_local_microphone_track("microphone".into(), false, &mut cx.to_async())
+            .await
+            .unwrap();
+        let video_sid_a = server
+            .publish_video_track(room_a.token(), LocalVideoTrack {})
+            .await
+            .unwrap();
+        let (publication_b, _stream_b) = room_b
+            .publish_local_microphone_track("microphone".into(), false, &mut cx.to_async())
+            .await
+            .unwrap();
+
+        let user_a = ParticipantIdentity("user-a".into());
+        let user_b = ParticipantIdentity("user-b".into());
+        assert_eq!(
+            room_b.tracks_for_participant(&user_a),
+            [publication_a.sid(), video_sid_a]
+        );
+        assert_eq!(
+            room_a.tracks_for_participant(&user_b),
+            [publication_b.sid()]
+        );
+        assert_eq!(
+            room_a.tracks_for_participant(&user_a),
+            room_b.tracks_for_participant(&user_a)
+        );
+        assert!(
+            room_a
+                .tracks_for_participant(&ParticipantIdentity("user-c".into()))
+                .is_empty()
+        );
+
+        drop((room_a, room_b));
+        cx.run_until_parked();
+        server.teardown().unwrap();
+    }
+}
@@ -12,7 +12,7 @@ use buffer_diff::{
     DiffHunkStatusKind,
 };
 use clock::ReplicaId;
-use collections::{BTreeMap, Bound, HashMap, HashSet};
+use collections::{BTreeMap, Bound, HashMap, HashSet, VecDeque};
 use gpui::{App, AppContext as _, Context, Entity, EntityId, EventEmitter, Task};
 use itertools::Itertools;
 use language::{
@@ -28,7 +28,7 @@ use rope::DimensionPair;
 use smallvec::SmallVec;
 use smol::future::yield_now;
 use std::{
-    any::type_name,
+    any::{Any, type_name},
     borrow::Cow,
     cell::{Cell, Ref, RefCell},
     cmp, fmt,
@@ -37,6 +37,7 @@ use std::{
     iter::{self, FromIterator},
     mem,
     ops::{Range, RangeBounds, Sub},
+    path::Path,
     rc::Rc,
     str,
     sync::Arc,
@@ -77,6 +78,8 @@ pub struct MultiBuffer {
     title: Option<String>,
     capability: Capability,
     buffer_changed_since_sync: Rc<Cell<bool>>,
+    #[cfg(test)]
+    excerpt_rebuild_count: Cell<usize>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -224,6 +227,13 @@ struct Transaction {
 
 pub trait ToOffset: 'static + fmt::Debug {
     fn to_offset(&self, snapshot: &MultiBufferSnapshot) -> usize;
+
+    /// Converts this position to an offset that is guaranteed to be valid in the
+    /// given snapshot, clipping positions that are out of range or that fall inside
+    /// a multi-byte character in the given direction.
+    fn to_offset_with_bias(&self, snapshot: &MultiBufferSnapshot, bias: Bias) -> usize {
+        snapshot.clip_offset(self.to_offset(snapshot), bias)
+    }
 }
 
 pub trait ToOffsetUtf16: 'static + fmt::Debug {
@@ -332,6 +342,7 @@ pub struct ExcerptInfo {
     pub buffer_id: BufferId,
     pub range: ExcerptRange<text::Anchor>,
     pub end_row: MultiBufferRow,
+    pub tag: Option<ExcerptTag>,
 }
 
 impl std::fmt::Debug for ExcerptInfo {
@@ -396,6 +407,28 @@ struct Excerpt {
     /// A summary of the text in the excerpt
     text_summary: TextSummary,
     has_trailing_newline: bool,
+    /// Data attached by whoever inserted the excerpt
+    tag: Option<ExcerptTag>,
+}
+
+/// Data attached to an excerpt when it's inserted, such as the diagnostic it
+/// shows, so that whoever renders its header can tell excerpts apart.
+///
+/// When excerpts for a path are merged, the merged excerpt keeps the tag of the
+/// earliest excerpt that had one.
+pub type ExcerptTag = Arc<dyn Any + Send + Sync>;
+
+/// How [`MultiBuffer::set_excerpts_for_path_with_merge_behavior`] treats ranges
+/// of the same buffer that overlap.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MergeBehavior {
+    /// Overlapping ranges are merged into a single excerpt.
+    #[default]
+    Merge,
+    /// Every range gets its own excerpt, even when it overlaps another one, so
+    /// the same lines (and headers for the same path) may be shown repeatedly.
+    /// Excerpts are still ordered by their position in the buffer.
+    Keep,
 }
 
 /// A public view into an `Excerpt` in a [`MultiBuffer`].
@@ -442,6 +475,7 @@ pub struct ExcerptSummary {
     excerpt_id: ExcerptId,
     /// The location of the last [`Excerpt`] being summarized
     excerpt_locator: Locator,
+    excerpt_count: usize,
     widest_line_number: u32,
     text: TextSummary,
 }
@@ -469,6 +503,7 @@ pub struct MultiBufferChunks<'a> {
     range: Range<usize>,
     excerpt_offset_range: Range<ExcerptOffset>,
     excerpt_chunks: Option<ExcerptChunks<'a>>,
+    buffer_id: Option<BufferId>,
     language_aware: bool,
 }
 
@@ -617,6 +652,8 @@ impl MultiBuffer {
             excerpts_by_path: Default::default(),
             paths_by_excerpt: Default::default(),
             buffer_changed_since_sync: Default::default(),
+            #[cfg(test)]
+            excerpt_rebuild_count: Default::default(),
             history: History {
                 next_transaction_id: clock::Lamport::default(),
                 undo_stack: Vec::new(),
@@ -638,6 +675,8 @@ impl MultiBuffer {
             singleton: false,
             capability,
             buffer_changed_since_sync: Default::default(),
+            #[cfg(test)]
+            excerpt_rebuild_count: Default::default(),
             history: History {
                 next_transaction_id: Default::default(),
                 undo_stack: Default::default(),
@@ -686,6 +725,8 @@ impl MultiBuffer {
             history: self.history.clone(),
             title: self.title.clone(),
             buffer_changed_since_sync,
+            #[cfg(test)]
+            excerpt_rebuild_count: Default::default(),
         }
     }
 
@@ -1479,6 +1520,26 @@ impl MultiBuffer {
         self.insert_excerpts_after(ExcerptId::max(), buffer, ranges, cx)
     }
 
+    /// Appends an excerpt spanning all of `buffer`, unless the multibuffer already contains one,
+    /// in which case its id is returned instead.
+    pub fn insert_whole_buffer(
+        &mut self,
+        buffer: Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Option<ExcerptId> {
+        let whole_buffer = text::Anchor::MIN..text::Anchor::MAX;
+        let buffer_id = buffer.read(cx).remote_id();
+        if let Some((excerpt_id, _)) = self
+            .excerpts_for_buffer(buffer_id, cx)
+            .into_iter()
+            .find(|(_, range)| range.context == whole_buffer)
+        {
+            return Some(excerpt_id);
+        }
+        self.push_excerpts(buffer, [ExcerptRange::new(whole_buffer)], cx)
+            .pop()
+    }
+
     pub fn location_for_path(&self, path: &PathKey, cx: &App) -> Option<Anchor> {
         let excerpt_id = self.excerpts_by_path.get(path)?.first()?;
         let snapshot = self.snapshot(cx);
@@ -1572,7 +1633,14 @@ impl MultiBuffer {
             };
 
             let buffer_snapshot = buffer.read(cx).snapshot();
-            self.update_path_excerpts(path.clone(), buffer, &buffer_snapshot, merged_ranges, cx);
+            self.update_path_excerpts(
+                path.clone(),
+                buffer,
+                &buffer_snapshot,
+                merged_ranges,
+                MergeBehavior::Merge,
+                cx,
+            );
         }
     }
 
@@ -1584,11 +1652,26 @@ impl MultiBuffer {
         ranges: impl IntoIterator<Item = Range<Point>>,
         context_line_count: u32,
         cx: &mut Context<Self>,
+    ) -> (Vec<Range<Anchor>>, bool) {
+        self.set_excerpts_for_path_merging_within(path, buffer, ranges, context_line_count, 0, cx)
+    }
+
+    /// Like [`Self::set_excerpts_for_path`], but also merges excerpts separated by
+    /// at most `max_gap_lines` lines, so dense results don't get a header between
+    /// every pair of nearby matches.
+    pub fn set_excerpts_for_path_merging_within(
+        &mut self,
+        path: PathKey,
+        buffer: Entity<Buffer>,
+        ranges: impl IntoIterator<Item = Range<Point>>,
+        context_line_count: u32,
+        max_gap_lines: u32,
+        cx: &mut Context<Self>,
     ) -> (Vec<Range<Anchor>>, bool) {
         let buffer_snapshot = buffer.read(cx).snapshot();
         let excerpt_ranges = build_excerpt_ranges(ranges, context_line_count, &buffer_snapshot);
 
-        let (new, counts) = Self::merge_excerpt_ranges(&excerpt_ranges);
+        let (new, counts) = Self::merge_excerpt_ranges(&excerpt_ranges, max_gap_lines);
         self.set_merged_excerpt_ranges_for_path(
             path,
             buffer,
@@ -1596,6 +1679,38 @@ impl MultiBuffer {
             &buffer_snapshot,
             new,
             counts,
+            MergeBehavior::Merge,
+            cx,
+        )
+    }
+
+    /// Like [`Self::set_excerpts_for_path`], but with [`MergeBehavior::Keep`] ranges
+    /// that overlap are kept as distinct excerpts, e.g. to show the same region once
+    /// for each diagnostic reported in it.
+    pub fn set_excerpts_for_path_with_merge_behavior(
+        &mut self,
+        path: PathKey,
+        buffer: Entity<Buffer>,
+        ranges: impl IntoIterator<Item = Range<Point>>,
+        context_line_count: u32,
+        merge_behavior: MergeBehavior,
+        cx: &mut Context<Self>,
+    ) -> (Vec<Range<Anchor>>, bool) {
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        let mut excerpt_ranges = build_excerpt_ranges(ranges, context_line_count, &buffer_snapshot);
+        excerpt_ranges.sort_by_key(|range| (range.context.start, range.context.end));
+        let (new, counts) = match merge_behavior {
+            MergeBehavior::Merge => Self::merge_excerpt_ranges(&excerpt_ranges, 0),
+            MergeBehavior::Keep => (excerpt_ranges.clone(), vec![1; excerpt_ranges.len()]),
+        };
+        self.set_merged_excerpt_ranges_for_path(
+            path,
+            buffer,
+            excerpt_ranges,
+            &buffer_snapshot,
+            new,
+            counts,
+            merge_behavior,
             cx,
         )
     }
@@ -1608,7 +1723,7 @@ impl MultiBuffer {
         excerpt_ranges: Vec<ExcerptRange<Point>>,
         cx: &mut Context<Self>,
     ) -> (Vec<Range<Anchor>>, bool) {
-        let (new, counts) = Self::merge_excerpt_ranges(&excerpt_ranges);
+        let (new, counts) = Self::merge_excerpt_ranges(&excerpt_ranges, 0);
         self.set_merged_excerpt_ranges_for_path(
             path,
             buffer,
@@ -1616,6 +1731,7 @@ impl MultiBuffer {
             buffer_snapshot,
             new,
             counts,
+            MergeBehavior::Merge,
             cx,
         )
     }
@@ -1636,7 +1752,7 @@ impl MultiBuffer {
                     let ranges = ranges.into_iter().map(|range| range.to_point(&snapshot));
                     let excerpt_ranges =
                         build_excerpt_ranges(ranges, context_line_count, &snapshot);
-                    let (new, counts) = Self::merge_excerpt_ranges(&excerpt_ranges);
+                    let (new, counts) = Self::merge_excerpt_ranges(&excerpt_ranges, 0);
                     (excerpt_ranges, new, counts)
                 })
                 .await;
@@ -1650,6 +1766,7 @@ impl MultiBuffer {
                         &buffer_snapshot,
                         new,
                         counts,
+                        MergeBehavior::Merge,
                         cx,
                     );
                     ranges
@@ -1668,10 +1785,11 @@ impl MultiBuffer {
         buffer_snapshot: &BufferSnapshot,
         new: Vec<ExcerptRange<Point>>,
         counts: Vec<usize>,
+        merge_behavior: MergeBehavior,
         cx: &mut Context<Self>,
     ) -> (Vec<Range<Anchor>>, bool) {
         let (excerpt_ids, added_a_new_excerpt) =
-            self.update_path_excerpts(path, buffer, buffer_snapshot, new, cx);
+            self.update_path_excerpts(path, buffer, buffer_snapshot, new, merge_behavior, cx);
 
         let mut result = Vec::new();
         let mut ranges = ranges.into_iter();
@@ -1691,6 +1809,7 @@ impl MultiBuffer {
 
     fn merge_excerpt_ranges<'a>(
         expanded_ranges: impl IntoIterator<Item = &'a ExcerptRange<Point>> + 'a,
+        max_gap_lines: u32,
     ) -> (Vec<ExcerptRange<Point>>, Vec<usize>) {
         let mut merged_ranges: Vec<ExcerptRange<Point>> = Vec::new();
         let mut counts: Vec<usize> = Vec::new();
@@ -1701,7 +1820,9 @@ impl MultiBuffer {
                     "Last range: {last_range:?} Range: {range:?}"
                 );
                 if last_range.context.end >= range.context.start
-                    || last_range.context.end.row + 1 == range.context.start.row
+                    || (range.context.start.row > last_range.context.end.row
+                        && range.context.start.row - last_range.context.end.row - 1
+                            <= max_gap_lines)
                 {
                     last_range.context.end = range.context.end.max(last_range.context.end);
                     *counts.last_mut().unwrap() += 1;
@@ -1720,6 +1841,7 @@ impl MultiBuffer {
         buffer: Entity<Buffer>,
         buffer_snapshot: &BufferSnapshot,
         new: Vec<ExcerptRange<Point>>,
+        merge_behavior: MergeBehavior,
         cx: &mut Context<Self>,
     ) -> (Vec<ExcerptId>, bool) {
         let mut insert_after = self
@@ -1740,7 +1862,7 @@ impl MultiBuffer {
 
         let mut excerpt_ids = Vec::new();
         let mut to_remove = Vec::new();
-        let mut to_insert: Vec<(ExcerptId, ExcerptRange<Point>)> = Vec::new();
+        let mut to_insert: Vec<(ExcerptId, ExcerptRange<Point>, Option<ExcerptTag>)> = Vec::new();
         let mut added_a_new_excerpt = false;
         let snapshot = self.snapshot(cx);
 
@@ -1770,6 +1892,7 @@ impl MultiBuffer {
                     Some((
                         *existing_id,
                         excerpt.range.context.to_point(buffer_snapshot),
+                        excerpt.tag.clone(),
                     ))
                 } else {
                     None
@@ -1778,7 +1901,9 @@ impl MultiBuffer {
                 None
             };
 
-            if let Some((last_id, last)) = to_insert.last_mut() {
+            if merge_behavior == MergeBehavior::Merge
+                && let Some((last_id, last, last_tag)) = to_insert.last_mut()
+            {
                 if let Some(new) = new
                     && last.context.end >= new.context.start
                 {
@@ -1787,10 +1912,13 @@ impl MultiBuffer {
                     new_iter.next();
                     continue;
                 }
-                if let Some((existing_id, existing_range)) = &existing
+                if let Some((existing_id, existing_range, existing_tag)) = &existing
                     && last.context.end >= existing_range.start
                 {
                     last.context.end = last.context.end.max(existing_range.end);
+                    if last_tag.is_none() {
+                        *last_tag = existing_tag.clone();
+                    }
                     to_remove.push(*existing_id);
                     self.snapshot
                         .borrow_mut()
@@ -1803,7 +1931,7 @@ impl MultiBuffer {
 
             match (new, existing) {
                 (None, None) => break,
-                (None, Some((existing_id, _))) => {
+                (None, Some((existing_id, _, _))) => {
                     existing_iter.next();
                     to_remove.push(existing_id);
                     continue;
@@ -1812,25 +1940,38 @@ impl MultiBuffer {
                     added_a_new_excerpt = true;
                     let new_id = next_excerpt_id();
                     excerpt_ids.push(new_id);
-                    to_insert.push((new_id, new_iter.next().unwrap()));
+                    to_insert.push((new_id, new_iter.next().unwrap(), None));
                     continue;
                 }
-                (Some(new), Some((_, existing_range))) => {
-                    if existing_range.end < new.context.start {
+                (Some(new), Some((_, existing_range, existing_tag))) => {
+                    // When keeping overlaps, only an identical range can reuse an
+                    // existing excerpt, so any other pair is ordered like disjoint ranges.
+                    let (existing_is_before, existing_is_after) = match merge_behavior {
+                        MergeBehavior::Merge => (
+                            existing_range.end < new.context.start,
+                            existing_range.start > new.context.end,
+                        ),
+                        MergeBehavior::Keep => {
+                            let existing_key = (existing_range.start, existing_range.end);
+                            let new_key = (new.context.start, new.context.end);
+                            (existing_key < new_key, existing_key > new_key)
+                        }
+                    };
+                    if existing_is_before {
                         let existing_id = existing_iter.next().unwrap();
                         to_remove.push(existing_id);
                         continue;
-                    } else if existing_range.start > new.context.end {
+                    } else if existing_is_after {
                         let new_id = next_excerpt_id();
                         excerpt_ids.push(new_id);
-                        to_insert.push((new_id, new_iter.next().unwrap()));
+                        to_insert.push((new_id, new_iter.next().unwrap(), None));
                         continue;
                     }
 
                     if existing_range.start == new.context.start
                         && existing_range.end == new.context.end
                     {
-                        self.insert_excerpts_with_ids_after(
+                        self.insert_tagged_excerpts_with_ids_after(
                             insert_after,
                             buffer.clone(),
                             mem::take(&mut to_insert),
@@ -1851,13 +1992,13 @@ impl MultiBuffer {
                         range.context.start = range.context.start.min(existing_range.start);
                         range.context.end = range.context.end.max(existing_range.end);
                         excerpt_ids.push(new_id);
-                        to_insert.push((new_id, range));
+                        to_insert.push((new_id, range, existing_tag));
                     }
                 }
             };
         }
 
-        self.insert_excerpts_with_ids_after(insert_after, buffer, to_insert, cx);
+        self.insert_tagged_excerpts_with_ids_after(insert_after, buffer, to_insert, cx);
         self.remove_excerpts(to_remove, cx);
         if excerpt_ids.is_empty() {
             self.excerpts_by_path.remove(&path);
@@ -1889,6 +2030,26 @@ impl MultiBuffer {
         ranges: impl IntoIterator<Item = ExcerptRange<O>>,
         cx: &mut Context<Self>,
     ) -> Vec<ExcerptId>
+    where
+        O: text::ToOffset,
+    {
+        self.insert_tagged_excerpts_after(
+            prev_excerpt_id,
+            buffer,
+            ranges.into_iter().map(|range| (range, None)),
+            cx,
+        )
+    }
+
+    /// Like [`Self::insert_excerpts_after`], but attaches a tag to each excerpt,
+    /// available from [`MultiBufferExcerpt::tag`] and [`ExcerptInfo::tag`].
+    pub fn insert_tagged_excerpts_after<O>(
+        &mut self,
+        prev_excerpt_id: ExcerptId,
+        buffer: Entity<Buffer>,
+        ranges: impl IntoIterator<Item = (ExcerptRange<O>, Option<ExcerptTag>)>,
+        cx: &mut Context<Self>,
+    ) -> Vec<ExcerptId>
     where
         O: text::ToOffset,
     {
@@ -1899,13 +2060,13 @@ impl MultiBuffer {
             } else {
                 1
             };
-        self.insert_excerpts_with_ids_after(
+        self.insert_tagged_excerpts_with_ids_after(
             prev_excerpt_id,
             buffer,
-            ranges.into_iter().map(|range| {
+            ranges.into_iter().map(|(range, tag)| {
                 let id = ExcerptId(post_inc(&mut next_excerpt_id));
                 ids.push(id);
-                (id, range)
+                (id, range, tag)
             }),
             cx,
         );
@@ -1920,6 +2081,23 @@ impl MultiBuffer {
         cx: &mut Context<Self>,
     ) where
         O: text::ToOffset,
+    {
+        self.insert_tagged_excerpts_with_ids_after(
+            prev_excerpt_id,
+            buffer,
+            ranges.into_iter().map(|(id, range)| (id, range, None)),
+            cx,
+        )
+    }
+
+    fn insert_tagged_excerpts_with_ids_after<O>(
+        &mut self,
+        prev_excerpt_id: ExcerptId,
+        buffer: Entity<Buffer>,
+        ranges: impl IntoIterator<Item = (ExcerptId, ExcerptRange<O>, Option<ExcerptTag>)>,
+        cx: &mut Context<Self>,
+    ) where
+        O: text::ToOffset,
     {
         assert_eq!(self.history.transaction_depth, 0);
         let mut ranges = ranges.into_iter().peekable();
@@ -1933,22 +2111,9 @@ impl MultiBuffer {
         let buffer_id = buffer_snapshot.remote_id();
 
         let mut buffers = self.buffers.borrow_mut();
-        let buffer_state = buffers.entry(buffer_id).or_insert_with(|| {
-            self.buffer_changed_since_sync.replace(true);
-            buffer.update(cx, |buffer, _| {
-                buffer.record_changes(Rc::downgrade(&self.buffer_changed_since_sync));
-            });
-            BufferState {
-                last_version: buffer_snapshot.version().clone(),
-                last_non_text_state_update_count: buffer_snapshot.non_text_state_update_count(),
-                excerpts: Default::default(),
-                _subscriptions: [
-                    cx.observe(&buffer, |_, _, cx| cx.notify()),
-                    cx.subscribe(&buffer, Self::on_buffer_event),
-                ],
-                buffer: buffer.clone(),
-            }
-        });
+        let buffer_state = buffers
+            .entry(buffer_id)
+            .or_insert_with(|| self.new_buffer_state(&buffer, &buffer_snapshot, cx));
 
         let mut snapshot = self.snapshot.borrow_mut();
 
@@ -1973,7 +2138,7 @@ impl MultiBuffer {
         };
 
         let mut excerpts = Vec::new();
-        while let Some((id, range)) = ranges.next() {
+        while let Some((id, range, tag)) = ranges.next() {
             let locator = Locator::between(&prev_locator, &next_locator);
             if let Err(ix) = buffer_state.excerpts.binary_search(&locator) {
                 buffer_state.excerpts.insert(ix, locator.clone());
@@ -1992,6 +2157,7 @@ impl MultiBuffer {
                 buffer_snapshot.clone(),
                 range,
                 ranges.peek().is_some() || cursor.item().is_some(),
+                tag,
             );
             new_excerpts.push(excerpt, ());
             prev_locator = locator.clone();
@@ -2034,6 +2200,292 @@ impl MultiBuffer {
         cx.notify();
     }
 
+    fn new_buffer_state(
+        &self,
+        buffer: &Entity<Buffer>,
+        buffer_snapshot: &BufferSnapshot,
+        cx: &mut Context<Self>,
+    ) -> BufferState {
+        self.buffer_changed_since_sync.replace(true);
+        buffer.update(cx, |buffer, _| {
+            buffer.record_changes(Rc::downgrade(&self.buffer_changed_since_sync));
+        });
+        BufferState {
+            last_version: buffer_snapshot.version().clone(),
+            last_non_text_state_update_count: buffer_snapshot.non_text_state_update_count(),
+            excerpts: Default::default(),
+            _subscriptions: [
+                cx.observe(buffer, |_, _, cx| cx.notify()),
+                cx.subscribe(buffer, Self::on_buffer_event),
+            ],
+            buffer: buffer.clone(),
+        }
+    }
+
+    /// Replaces all excerpts with the given ranges, in order, with a single update.
+    ///
+    /// Existing excerpts showing the same range of the same buffer are kept in
+    /// place, so only the excerpts that actually changed are reported as removed
+    /// or added. Buffers left without excerpts are dropped. Returns the ids of
+    /// the resulting excerpts.
+    pub fn set_excerpts(
+        &mut self,
+        new: impl IntoIterator<Item = (Entity<Buffer>, Range<text::Anchor>)>,
+        cx: &mut Context<Self>,
+    ) -> Vec<ExcerptId> {
+        assert_eq!(self.history.transaction_depth, 0);
+        self.sync(cx);
+
+        let new = new
+            .into_iter()
+            .map(|(buffer, range)| {
+                let buffer_snapshot = buffer.read(cx).snapshot();
+                (buffer, buffer_snapshot, range)
+            })
+            .collect::<Vec<_>>();
+        {
+            let mut buffers = self.buffers.borrow_mut();
+            for (buffer, buffer_snapshot, _) in &new {
+                buffers
+                    .entry(buffer_snapshot.remote_id())
+                    .or_insert_with(|| self.new_buffer_state(buffer, buffer_snapshot, cx));
+            }
+        }
+
+        let mut buffers = self.buffers.borrow_mut();
+        let mut snapshot = self.snapshot.borrow_mut();
+
+        // Match new ranges to existing excerpts in order, so that kept excerpts
+        // never need to move.
+        let mut old_indices_by_range =
+            HashMap::<(BufferId, Range<usize>), VecDeque<usize>>::default();
+        let mut old_locators = Vec::new();
+        for (old_ix, excerpt) in snapshot.excerpts.iter().enumerate() {
+            old_indices_by_range
+                .entry((
+                    excerpt.buffer_id,
+                    excerpt.range.context.to_offset(&excerpt.buffer),
+                ))
+                .or_default()
+                .push_back(old_ix);
+            old_locators.push(excerpt.locator.clone());
+        }
+        let mut last_kept_ix = None;
+        let kept_old_indices = new
+            .iter()
+            .map(|(_, buffer_snapshot, range)| {
+                let candidates = old_indices_by_range.get_mut(&(
+                    buffer_snapshot.remote_id(),
+                    range.to_offset(buffer_snapshot),
+                ))?;
+                while candidates
+                    .front()
+                    .is_some_and(|old_ix| last_kept_ix.is_some_and(|last| *old_ix <= last))
+                {
+                    candidates.pop_front();
+                }
+                let old_ix = candidates.pop_front()?;
+                last_kept_ix = Some(old_ix);
+                Some(old_ix)
+            })
+            .collect::<Vec<_>>();
+        let mut next_locators = Vec::with_capacity(new.len());
+        let mut next_locator = Locator::max();
+        for kept_old_ix in kept_old_indices.iter().rev() {
+            next_locators.push(next_locator.clone());
+            if let Some(old_ix) = kept_old_ix {
+                next_locator = old_locators[*old_ix].clone();
+            }
+        }
+        next_locators.reverse();
+
+        let mut new_excerpt_ids = mem::take(&mut snapshot.excerpt_ids);
+        let mut next_excerpt_id = new_excerpt_ids.last().map_or(1, |mapping| mapping.id.0 + 1);
+        let mut cursor = snapshot.excerpts.cursor::<ExcerptOffset>(());
+        cursor.next();
+        let mut new_excerpts = SumTree::default();
+        let mut edits = Vec::new();
+        let mut pending_edit_start: Option<(ExcerptOffset, ExcerptOffset)> = None;
+        let mut excerpt_ids = Vec::with_capacity(new.len());
+        let mut removed_ids = Vec::new();
+        let mut added = Vec::<(
+            Entity<Buffer>,
+            ExcerptId,
+            Vec<(ExcerptId, ExcerptRange<text::Anchor>)>,
+        )>::new();
+        let mut prev_locator = Locator::min();
+        let mut prev_excerpt_id = ExcerptId::min();
+        let mut old_ix = 0;
+        let new_count = new.len();
+
+        for (ix, ((buffer, buffer_snapshot, range), kept_old_ix)) in
+            new.into_iter().zip(kept_old_indices).enumerate()
+        {
+            let has_trailing_newline = ix + 1 < new_count;
+            if let Some(kept_old_ix) = kept_old_ix {
+                while old_ix < kept_old_ix {
+                    if let Some(excerpt) = cursor.item() {
+                        pending_edit_start.get_or_insert((
+                            *cursor.start(),
+                            ExcerptOffset::new(new_excerpts.summary().text.len),
+                        ));
+                        if let Some(buffer_state) = buffers.get_mut(&excerpt.buffer_id) {
+                            buffer_state.excerpts.retain(|l| l != &excerpt.locator);
+                        }
+                        removed_ids.push(excerpt.id);
+                    }
+                    cursor.next();
+                    old_ix += 1;
+                }
+                let Some(excerpt) = cursor.item() else {
+                    continue;
+                };
+                let mut excerpt = excerpt.clone();
+                if excerpt.has_trailing_newline == has_trailing_newline {
+                    if let Some((old_start, new_start)) = pending_edit_start.take() {
+                        edits.push(Edit {
+                            old: old_start..*cursor.start(),
+                            new: new_start..ExcerptOffset::new(new_excerpts.summary().text.len),
+                        });
+                    }
+                } else {
+                    pending_edit_start.get_or_insert((
+                        *cursor.start(),
+                        ExcerptOffset::new(new_excerpts.summary().text.len),
+                    ));
+                    excerpt.has_trailing_newline = has_trailing_newline;
+                }
+                prev_locator = excerpt.locator.clone();
+                prev_excerpt_id = excerpt.id;
+                excerpt_ids.push(excerpt.id);
+                new_excerpts.push(excerpt, ());
+                cursor.next();
+                old_ix += 1;
+            } else {
+                pending_edit_start.get_or_insert((
+                    *cursor.start(),
+                    ExcerptOffset::new(new_excerpts.summary().text.len),
+                ));
+                let id = ExcerptId(post_inc(&mut next_excerpt_id));
+                let locator = Locator::between(&prev_locator, &next_locators[ix]);
+                let buffer_id = buffer_snapshot.remote_id();
+                if let Some(buffer_state) = buffers.get_mut(&buffer_id)
+                    && let Err(insert_ix) = buffer_state.excerpts.binary_search(&locator)
+                {
+                    buffer_state.excerpts.insert(insert_ix, locator.clone());
+                }
+                let range = ExcerptRange::new(
+                    buffer_snapshot.anchor_before(&range.start)
+                        ..buffer_snapshot.anchor_after(&range.end),
+                );
+                match added.last_mut() {
+                    Some((added_buffer, _, excerpts))
+                        if added_buffer.entity_id() == buffer.entity_id()
+                            && excerpts.last().map(|(id, _)| *id) == Some(prev_excerpt_id) =>
+                    {
+                        excerpts.push((id, range.clone()));
+                    }
+                    _ => added.push((buffer, prev_excerpt_id, vec![(id, range.clone())])),
+                }
+                new_excerpts.push(
+                    Excerpt::new(
+                        id,
+                        locator.clone(),
+                        buffer_id,
+                        buffer_snapshot,
+                        range,
+                        has_trailing_newline,
+                        None,
+                    ),
+                    (),
+                );
+                new_excerpt_ids.push(
+                    ExcerptIdMapping {
+                        id,
+                        locator: locator.clone(),
+                    },
+                    (),
+                );
+                excerpt_ids.push(id);
+                prev_locator = locator;
+                prev_excerpt_id = id;
+            }
+        }
+        while let Some(excerpt) = cursor.item() {
+            pending_edit_start.get_or_insert((
+                *cursor.start(),
+                ExcerptOffset::new(new_excerpts.summary().text.len),
+            ));
+            if let Some(buffer_state) = buffers.get_mut(&excerpt.buffer_id) {
+                buffer_state.excerpts.retain(|l| l != &excerpt.locator);
+            }
+            removed_ids.push(excerpt.id);
+            cursor.next();
+        }
+        if let Some((old_start, new_start)) = pending_edit_start {
+            edits.push(Edit {
+                old: old_start..*cursor.start(),
+                new: new_start..ExcerptOffset::new(new_excerpts.summary().text.len),
+            });
+        }
+        drop(cursor);
+
+        let changed_trailing_excerpt = snapshot.excerpts.last().map(|excerpt| excerpt.id)
+            != new_excerpts.last().map(|excerpt| excerpt.id);
+        snapshot.excerpts = new_excerpts;
+        snapshot.excerpt_ids = new_excerpt_ids;
+        if changed_trailing_excerpt {
+            snapshot.trailing_excerpt_update_count += 1;
+        }
+
+        let removed_buffer_ids = buffers
+            .iter()
+            .filter(|(_, buffer_state)| buffer_state.excerpts.is_empty())
+            .map(|(buffer_id, _)| *buffer_id)
+            .collect::<Vec<_>>();
+        for buffer_id in &removed_buffer_ids {
+            log::debug!("removing buffer and diff for buffer {}", buffer_id);
+            buffers.remove(buffer_id);
+            self.diffs.remove(buffer_id);
+            snapshot.diffs.remove(buffer_id);
+        }
+        for excerpt_id in &removed_ids {
+            if let Some(path) = self.paths_by_excerpt.remove(excerpt_id)
+                && let Some(path_excerpt_ids) = self.excerpts_by_path.get_mut(&path)
+            {
+                path_excerpt_ids.retain(|id| id != excerpt_id);
+                if path_excerpt_ids.is_empty() {
+                    self.excerpts_by_path.remove(&path);
+                }
+            }
+        }
+
+        if edits.is_empty() {
+            return excerpt_ids;
+        }
+        self.sync_diff_transforms(&mut snapshot, edits, DiffChangeKind::BufferEdited);
+        self.buffer_changed_since_sync.replace(true);
+        cx.emit(Event::Edited {
+            singleton_buffer_edited: false,
+            edited_buffer: None,
+        });
+        if !removed_ids.is_empty() {
+            cx.emit(Event::ExcerptsRemoved {
+                ids: removed_ids,
+                removed_buffer_ids,
+            });
+        }
+        for (buffer, predecessor, excerpts) in added {
+            cx.emit(Event::ExcerptsAdded {
+                buffer,
+                predecessor,
+                excerpts,
+            });
+        }
+        cx.notify();
+        excerpt_ids
+    }
+
     pub fn clear(&mut self, cx: &mut Context<Self>) {
         self.sync(cx);
         let ids = self.excerpt_ids();
@@ -2042,10 +2494,14 @@ impl MultiBuffer {
             .borrow_mut()
             .drain()
             .map(|(id, _)| id)
-            .collect();
+            .collect::<Vec<_>>();
         self.excerpts_by_path.clear();
         self.paths_by_excerpt.clear();
         let mut snapshot = self.snapshot.borrow_mut();
+        for buffer_id in &removed_buffer_ids {
+            self.diffs.remove(buffer_id);
+            snapshot.diffs.remove(buffer_id);
+        }
         let start = ExcerptOffset::new(0);
         let prev_len = ExcerptOffset::new(snapshot.excerpts.summary().text.len);
         snapshot.excerpts = Default::default();
@@ -2152,6 +2608,16 @@ impl MultiBuffer {
             .collect()
     }
 
+    /// The number of excerpts, after overlapping ranges have been merged.
+    pub fn excerpt_count(&self) -> usize {
+        self.snapshot.borrow().excerpt_count()
+    }
+
+    /// The number of distinct buffers shown by at least one excerpt.
+    pub fn buffer_count(&self) -> usize {
+        self.buffers.borrow().len()
+    }
+
     pub fn excerpt_containing(
         &self,
         position: impl ToOffset,
@@ -2255,6 +2721,32 @@ impl MultiBuffer {
         })
     }
 
+    /// Replaces the tag of an existing excerpt, e.g. one added by
+    /// [`Self::set_excerpts_for_path`], which doesn't take tags.
+    pub fn set_excerpt_tag(
+        &mut self,
+        excerpt_id: ExcerptId,
+        tag: Option<ExcerptTag>,
+        cx: &mut Context<Self>,
+    ) {
+        self.sync(cx);
+        let mut snapshot = self.snapshot.borrow_mut();
+        let locator = snapshot.excerpt_locator_for_id(excerpt_id).clone();
+        let mut cursor = snapshot.excerpts.cursor::<Option<&Locator>>(());
+        let mut new_excerpts = cursor.slice(&Some(&locator), Bias::Left);
+        let Some(excerpt) = cursor.item().filter(|excerpt| excerpt.id == excerpt_id) else {
+            return;
+        };
+        let mut excerpt = excerpt.clone();
+        excerpt.tag = tag;
+        new_excerpts.push(excerpt, ());
+        cursor.next();
+        new_excerpts.append(cursor.suffix(), ());
+        drop(cursor);
+        snapshot.excerpts = new_excerpts;
+        cx.notify();
+    }
+
     pub fn remove_excerpts(
         &mut self,
         excerpt_ids: impl IntoIterator<Item = ExcerptId>,
@@ -2535,6 +3027,15 @@ impl MultiBuffer {
         self.buffers.borrow().keys().copied().collect()
     }
 
+    /// Finds the buffer whose file currently has the given full path. Unlike the [`PathKey`]s,
+    /// which are assigned when excerpts are set, this follows files renamed since then.
+    pub fn buffer_for_path(&self, path: &Path, cx: &App) -> Option<Entity<Buffer>> {
+        self.buffers.borrow().values().find_map(|state| {
+            let file = state.buffer.read(cx).file()?;
+            (file.full_path(cx) == path).then(|| state.buffer.clone())
+        })
+    }
+
     pub fn buffer(&self, buffer_id: BufferId) -> Option<Entity<Buffer>> {
         self.buffers
             .borrow()
@@ -2677,6 +3178,31 @@ impl MultiBuffer {
         self.diffs.insert(buffer_id, DiffState::new(diff, cx));
     }
 
+    /// Diffs a buffer against `base` rather than against its git index, e.g. to show
+    /// changes made by an agent. This replaces any diff previously added for the buffer
+    /// in this multibuffer with a new one, leaving diffs shared with other multibuffers
+    /// untouched. The returned task completes once the hunks relative to `base` are available.
+    pub fn set_diff_base(
+        &mut self,
+        buffer_id: BufferId,
+        base: language::BufferSnapshot,
+        cx: &mut Context<Self>,
+    ) -> Task<()> {
+        let Some(buffer) = self.buffer(buffer_id) else {
+            return Task::ready(());
+        };
+        let diff = cx.new(|cx| BufferDiff::new(&buffer.read(cx).text_snapshot(), cx));
+        self.add_diff(diff.clone(), cx);
+        let language_registry = buffer.read(cx).language_registry();
+        let buffer_snapshot = buffer.read(cx).text_snapshot();
+        let diff_updated = diff.update(cx, |diff, cx| {
+            diff.set_base_text(base, language_registry, buffer_snapshot, cx)
+        });
+        cx.background_spawn(async move {
+            diff_updated.await.ok();
+        })
+    }
+
     pub fn diff_for(&self, buffer_id: BufferId) -> Option<Entity<BufferDiff>> {
         self.diffs.get(&buffer_id).map(|state| state.diff.clone())
     }
@@ -3051,6 +3577,15 @@ impl MultiBuffer {
             }
         }
 
+        // Adding or removing excerpts also marks the multibuffer as changed, in which case the
+        // buffers are often all up to date and the excerpts don't need to be rebuilt.
+        if excerpts_to_edit.is_empty() {
+            return;
+        }
+        #[cfg(test)]
+        self.excerpt_rebuild_count
+            .set(self.excerpt_rebuild_count.get() + 1);
+
         excerpts_to_edit.sort_unstable_by_key(|(locator, _, _)| *locator);
 
         let mut edits = Vec::new();
@@ -3095,6 +3630,7 @@ impl MultiBuffer {
                     buffer.snapshot(),
                     old_excerpt.range.clone(),
                     old_excerpt.has_trailing_newline,
+                    old_excerpt.tag.clone(),
                 );
             } else {
                 new_excerpt = old_excerpt.clone();
@@ -3759,6 +4295,32 @@ impl MultiBufferSnapshot {
             .collect()
     }
 
+    /// Returns the text of the multibuffer with a `-- path --` line before the
+    /// first excerpt of each run of excerpts from the same buffer.
+    pub fn text_with_headers(&self) -> String {
+        let mut text = String::new();
+        let mut offset = 0;
+        for boundary in self.excerpt_boundaries_in_range(0..self.len()) {
+            if !boundary.starts_new_buffer() {
+                continue;
+            }
+            let boundary_offset = self.point_to_offset(Point::new(boundary.row.0, 0));
+            text.extend(self.text_for_range(offset..boundary_offset));
+            offset = boundary_offset;
+
+            let path = boundary
+                .next
+                .buffer
+                .file()
+                .map_or("untitled", |file| file.path().as_unix_str());
+            text.push_str("-- ");
+            text.push_str(path);
+            text.push_str(" --\n");
+        }
+        text.extend(self.text_for_range(offset..self.len()));
+        text
+    }
+
     pub fn reversed_chars_at<T: ToOffset>(&self, position: T) -> impl Iterator<Item = char> + '_ {
         self.reversed_chunks_in_range(0..position.to_offset(self))
             .flat_map(|c| c.chars().rev())
@@ -4279,10 +4841,25 @@ impl MultiBufferSnapshot {
         self.diff_transforms.summary().output.len
     }
 
+    /// Whether the multibuffer contains no text. A multibuffer holding a single empty
+    /// excerpt is empty; use [`Self::excerpt_count`] to check for the absence of excerpts.
     pub fn is_empty(&self) -> bool {
         self.excerpts.summary().text.len == 0
     }
 
+    pub fn excerpt_count(&self) -> usize {
+        self.excerpts.summary().excerpt_count
+    }
+
+    /// The number of distinct buffers shown by at least one excerpt.
+    pub fn buffer_count(&self) -> usize {
+        self.excerpts
+            .iter()
+            .map(|excerpt| excerpt.buffer_id)
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
     pub fn widest_line_number(&self) -> u32 {
         // widest_line_number is 0-based, so 1 is added to get the displayed line number.
         self.excerpts.summary().widest_line_number + 1
@@ -4365,6 +4942,7 @@ impl MultiBufferSnapshot {
             diff_base_chunks: None,
             excerpt_chunks: None,
             buffer_chunk: None,
+            buffer_id: None,
             language_aware,
         };
         let range = range.start.to_offset(self)..range.end.to_offset(self);
@@ -4685,6 +5263,18 @@ impl MultiBufferSnapshot {
         MultiBufferRow(self.text_summary().lines.row)
     }
 
+    /// The number of lines in the multibuffer, including the newlines that
+    /// separate excerpts, read from the summary without materializing text.
+    pub fn line_count(&self) -> u32 {
+        self.max_row().0 + 1
+    }
+
+    /// Returns the row containing the most characters, among the rows currently
+    /// visible in the multibuffer.
+    pub fn longest_row(&self) -> MultiBufferRow {
+        MultiBufferRow(self.text_summary().longest_row)
+    }
+
     pub fn text_summary(&self) -> TextSummary {
         self.diff_transforms.summary().output
     }
@@ -5271,6 +5861,24 @@ impl MultiBufferSnapshot {
             .map(|excerpt| (excerpt.id, &excerpt.buffer, excerpt.range.clone()))
     }
 
+    /// Like [`Self::excerpts`], but starting from the last excerpt.
+    pub fn excerpts_rev(
+        &self,
+    ) -> impl Iterator<Item = (ExcerptId, &BufferSnapshot, ExcerptRange<text::Anchor>)> {
+        let mut cursor = self.excerpts.cursor::<()>(());
+        iter::from_fn(move || {
+            cursor.prev();
+            let excerpt = cursor.item()?;
+            Some((excerpt.id, &excerpt.buffer, excerpt.range.clone()))
+        })
+    }
+
+    pub fn last_excerpt(&self) -> Option<(ExcerptId, &BufferSnapshot, ExcerptRange<text::Anchor>)> {
+        self.excerpts
+            .last()
+            .map(|excerpt| (excerpt.id, &excerpt.buffer, excerpt.range.clone()))
+    }
+
     fn cursor<D: TextDimension + Default>(&self) -> MultiBufferCursor<'_, D> {
         let excerpts = self.excerpts.cursor(());
         let diff_transforms = self.diff_transforms.cursor(());
@@ -5306,6 +5914,23 @@ impl MultiBufferSnapshot {
         })
     }
 
+    /// Returns the offset where the first excerpt starting after `offset` begins.
+    pub fn next_excerpt_boundary(&self, offset: usize) -> Option<usize> {
+        if offset >= self.len() {
+            return None;
+        }
+        self.excerpt_boundaries_in_range(offset + 1..)
+            .next()
+            .map(|boundary| self.point_to_offset(Point::new(boundary.row.0, 0)))
+    }
+
+    /// Returns the offset where the last excerpt starting before `offset` begins.
+    pub fn prev_excerpt_boundary(&self, offset: usize) -> Option<usize> {
+        self.excerpt_boundaries_in_range(..offset.min(self.len()))
+            .last()
+            .map(|boundary| self.point_to_offset(Point::new(boundary.row.0, 0)))
+    }
+
     pub fn excerpt_boundaries_in_range<R, T>(
         &self,
         range: R,
@@ -5379,6 +6004,7 @@ impl MultiBufferSnapshot {
                     buffer_id: region.excerpt.buffer_id,
                     range: region.excerpt.range.clone(),
                     end_row: MultiBufferRow(next_region_start.row),
+                    tag: region.excerpt.tag.clone(),
                 });
 
                 let next = ExcerptInfo {
@@ -5391,6 +6017,7 @@ impl MultiBufferSnapshot {
                     } else {
                         MultiBufferRow(next_region_end.row)
                     },
+                    tag: next_region.excerpt.tag.clone(),
                 };
 
                 let row = MultiBufferRow(next_region_start.row);
@@ -6197,6 +6824,25 @@ impl MultiBufferSnapshot {
         Some(&self.excerpt(excerpt_id)?.buffer)
     }
 
+    pub fn excerpt_tag(&self, excerpt_id: ExcerptId) -> Option<&ExcerptTag> {
+        self.excerpt(excerpt_id)?.tag.as_ref()
+    }
+
+    /// Returns the snapshot of a buffer with excerpts in this multibuffer, as of
+    /// when those excerpts were last synced rather than the buffer's latest state.
+    pub fn buffer_snapshot(&self, buffer_id: BufferId) -> Option<&BufferSnapshot> {
+        self.excerpts
+            .iter()
+            .find(|excerpt| excerpt.buffer_id == buffer_id)
+            .map(|excerpt| &excerpt.buffer)
+    }
+
+    /// Returns the snapshot of the buffer whose text is rendered at `offset`.
+    pub fn buffer_snapshot_for_offset(&self, offset: usize) -> Option<&BufferSnapshot> {
+        self.point_to_buffer_offset(offset)
+            .map(|(buffer, _)| buffer)
+    }
+
     pub fn range_for_excerpt(&self, excerpt_id: ExcerptId) -> Option<Range<Point>> {
         let mut cursor = self
             .excerpts
@@ -6405,6 +7051,17 @@ impl MultiBufferSnapshot {
             }
         }
 
+        let summary = self.excerpts.summary();
+        if let Some(last_excerpt) = excerpts.last()
+            && (summary.excerpt_id != last_excerpt.id
+                || summary.excerpt_locator != last_excerpt.locator)
+        {
+            panic!(
+                "excerpt summary ends at {:?} ({:?}), but the last excerpt is {:?} ({:?})",
+                summary.excerpt_id, summary.excerpt_locator, last_excerpt.id, last_excerpt.locator
+            );
+        }
+
         for (ix, entry) in excerpt_ids.iter().enumerate() {
             if ix == 0 {
                 if entry.id.cmp(&ExcerptId::min(), self).is_le() {
@@ -6921,6 +7578,7 @@ impl Excerpt {
         buffer: BufferSnapshot,
         range: ExcerptRange<text::Anchor>,
         has_trailing_newline: bool,
+        tag: Option<ExcerptTag>,
     ) -> Self {
         Excerpt {
             id,
@@ -6932,6 +7590,7 @@ impl Excerpt {
             buffer,
             range,
             has_trailing_newline,
+            tag,
         }
     }
 
@@ -7045,6 +7704,10 @@ impl<'a> MultiBufferExcerpt<'a> {
         &self.excerpt.buffer
     }
 
+    pub fn tag(&self) -> Option<&'a ExcerptTag> {
+        self.excerpt.tag.as_ref()
+    }
+
     pub fn buffer_range(&self) -> Range<usize> {
         self.buffer_offset
             ..self
@@ -7178,6 +7841,7 @@ impl fmt::Debug for Excerpt {
             .field("range", &self.range)
             .field("text_summary", &self.text_summary)
             .field("has_trailing_newline", &self.has_trailing_newline)
+            .field("has_tag", &self.tag.is_some())
             .finish()
     }
 }
@@ -7193,6 +7857,7 @@ impl sum_tree::Item for Excerpt {
         ExcerptSummary {
             excerpt_id: self.id,
             excerpt_locator: self.locator.clone(),
+            excerpt_count: 1,
             widest_line_number: self.max_buffer_row,
             text,
         }
@@ -7280,7 +7945,9 @@ impl sum_tree::ContextLessSummary for ExcerptSummary {
 
     fn add_summary(&mut self, summary: &Self) {
         debug_assert!(summary.excerpt_locator > self.excerpt_locator);
+        self.excerpt_id = summary.excerpt_id;
         self.excerpt_locator = summary.excerpt_locator.clone();
+        self.excerpt_count += summary.excerpt_count;
         Summary::add_summary(&mut self.text, &summary.text, ());
         self.widest_line_number = cmp::max(self.widest_line_number, summary.widest_line_number);
     }
@@ -7587,6 +8254,12 @@ impl<'a> MultiBufferChunks<'a> {
         self.range.start
     }
 
+    /// The buffer that the most recently returned chunk belongs to. This includes the newline
+    /// separating an excerpt from the next one, and the base text of deleted hunks.
+    pub fn buffer_id(&self) -> Option<BufferId> {
+        self.buffer_id
+    }
+
     pub fn seek(&mut self, range: Range<usize>) {
         self.diff_transforms.seek(&range.end, Bias::Right);
         let mut excerpt_end = self.diff_transforms.start().1;
@@ -7705,6 +8378,7 @@ impl<'a> Iterator for MultiBufferChunks<'a> {
 
                 let chunk_end = self.range.start + chunk.text.len();
                 let diff_transform_end = diff_transform_end.min(self.range.end);
+                self.buffer_id = self.excerpts.item().map(|excerpt| excerpt.buffer_id);
 
                 if diff_transform_end < chunk_end {
                     let split_idx = diff_transform_end - self.range.start;
@@ -7756,6 +8430,7 @@ impl<'a> Iterator for MultiBufferChunks<'a> {
                     base_buffer.chunks(base_text_start..base_text_end, self.language_aware)
                 };
 
+                self.buffer_id = Some(*buffer_id);
                 let chunk = if let Some(chunk) = chunks.next() {
                     self.range.start += chunk.text.len();
                     self.diff_base_chunks = Some((*buffer_id, chunks));
@@ -7879,6 +8554,10 @@ impl ToOffset for Point {
     fn to_offset<'a>(&self, snapshot: &MultiBufferSnapshot) -> usize {
         snapshot.point_to_offset(*self)
     }
+
+    fn to_offset_with_bias(&self, snapshot: &MultiBufferSnapshot, bias: Bias) -> usize {
+        snapshot.point_to_offset(snapshot.clip_point(*self, bias))
+    }
 }
 
 impl ToOffset for usize {
@@ -7892,6 +8571,10 @@ impl ToOffset for usize {
         );
         *self
     }
+
+    fn to_offset_with_bias(&self, snapshot: &MultiBufferSnapshot, bias: Bias) -> usize {
+        snapshot.clip_offset(*self, bias)
+    }
 }
 
 impl ToOffset for OffsetUtf16 {
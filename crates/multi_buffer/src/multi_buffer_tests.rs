@@ -69,6 +69,22 @@ fn test_singleton(cx: &mut App) {
     assert_consistent_line_numbers(&snapshot);
 }
 
+#[gpui::test]
+fn test_sync_skips_unchanged_buffers(cx: &mut App) {
+    let buffer = cx.new(|cx| Buffer::local("one\ntwo", cx));
+    let multibuffer = cx.new(|cx| MultiBuffer::singleton(buffer.clone(), cx));
+    multibuffer.read(cx).snapshot(cx);
+    multibuffer.read(cx).snapshot(cx);
+    assert_eq!(multibuffer.read(cx).excerpt_rebuild_count.get(), 0);
+
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "zero\n")], None, cx));
+    assert_eq!(multibuffer.read(cx).snapshot(cx).text(), "zero\none\ntwo");
+    assert_eq!(multibuffer.read(cx).excerpt_rebuild_count.get(), 1);
+
+    multibuffer.read(cx).snapshot(cx);
+    assert_eq!(multibuffer.read(cx).excerpt_rebuild_count.get(), 1);
+}
+
 #[gpui::test]
 fn test_remote(cx: &mut App) {
     let host_buffer = cx.new(|cx| Buffer::local("a", cx));
@@ -98,6 +114,776 @@ fn test_remote(cx: &mut App) {
     assert_eq!(snapshot.text(), "abc");
 }
 
+#[gpui::test]
+fn test_text_with_headers(cx: &mut App) {
+    let buffer_1 = cx.new(|cx| {
+        let mut buffer = Buffer::local("one\ntwo\nthree\nfour", cx);
+        buffer.file_updated(test_file("src/main.rs"), cx);
+        buffer
+    });
+    let buffer_2 = cx.new(|cx| {
+        let mut buffer = Buffer::local("alpha\nbeta", cx);
+        buffer.file_updated(test_file("src/lib.rs"), cx);
+        buffer
+    });
+    let buffer_3 = cx.new(|cx| Buffer::local("scratch", cx));
+    let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
+    multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.push_excerpts(
+            buffer_1.clone(),
+            [
+                ExcerptRange::new(Point::new(0, 0)..Point::new(1, 3)),
+                ExcerptRange::new(Point::new(3, 0)..Point::new(3, 4)),
+            ],
+            cx,
+        );
+        multibuffer.push_excerpts(
+            buffer_2.clone(),
+            [ExcerptRange::new(Point::new(0, 0)..Point::new(1, 4))],
+            cx,
+        );
+        multibuffer.push_excerpts(
+            buffer_3.clone(),
+            [ExcerptRange::new(Point::new(0, 0)..Point::new(0, 7))],
+            cx,
+        );
+    });
+
+    let snapshot = multibuffer.read(cx).snapshot(cx);
+    assert_eq!(snapshot.text(), "one\ntwo\nfour\nalpha\nbeta\nscratch");
+    assert_eq!(
+        snapshot.text_with_headers(),
+        indoc! {"
+            -- src/main.rs --
+            one
+            two
+            four
+            -- src/lib.rs --
+            alpha
+            beta
+            -- untitled --
+            scratch"
+        }
+    );
+}
+
+#[gpui::test]
+fn test_seek_excerpts_by_offset(cx: &mut App) {
+    let buffer_1 = cx.new(|cx| Buffer::local("one\ntwo\nthree", cx));
+    let buffer_2 = cx.new(|cx| Buffer::local("four\nfive", cx));
+    let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
+    let (excerpt_1, excerpt_2) = multibuffer.update(cx, |multibuffer, cx| {
+        let excerpt_1 = multibuffer.push_excerpts(
+            buffer_1.clone(),
+            [ExcerptRange::new(Point::new(0, 0)..Point::new(1, 3))],
+            cx,
+        )[0];
+        let excerpt_2 = multibuffer.push_excerpts(
+            buffer_2.clone(),
+            [ExcerptRange::new(Point::new(0, 0)..Point::new(1, 4))],
+            cx,
+        )[0];
+        (excerpt_1, excerpt_2)
+    });
+    let snapshot = multibuffer.read(cx).snapshot(cx);
+    assert_eq!(snapshot.text(), "one\ntwo\nfour\nfive");
+
+    let mut cursor = snapshot.excerpts.cursor::<ExcerptOffset>(());
+    for (offset, bias, expected_excerpt, expected_start) in [
+        (5, Bias::Right, excerpt_1, 0),
+        // The newline separating the excerpts belongs to the first one.
+        (7, Bias::Right, excerpt_1, 0),
+        (8, Bias::Left, excerpt_1, 0),
+        (8, Bias::Right, excerpt_2, 8),
+        (11, Bias::Right, excerpt_2, 8),
+    ] {
+        cursor.seek(&ExcerptOffset::new(offset), bias);
+        assert_eq!(
+            cursor.item().map(|excerpt| excerpt.id),
+            Some(expected_excerpt),
+            "seeking to {offset} with {bias:?}"
+        );
+        assert_eq!(cursor.start().value, expected_start);
+    }
+}
+
+#[gpui::test]
+fn test_excerpt_boundary_offsets(cx: &mut App) {
+    let buffer_1 = cx.new(|cx| Buffer::local("one\nskipped", cx));
+    let buffer_2 = cx.new(|cx| Buffer::local("two\nthree", cx));
+    let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
+    multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.push_excerpts(
+            buffer_1.clone(),
+            [ExcerptRange::new(Point::new(0, 0)..Point::new(0, 3))],
+            cx,
+        );
+        multibuffer.push_excerpts(
+            buffer_2.clone(),
+            [ExcerptRange::new(Point::new(0, 0)..Point::new(1, 5))],
+            cx,
+        );
+        multibuffer.push_excerpts(
+            buffer_1.clone(),
+            [ExcerptRange::new(Point::new(1, 0)..Point::new(1, 7))],
+            cx,
+        );
+    });
+    let snapshot = multibuffer.read(cx).snapshot(cx);
+    assert_eq!(snapshot.text(), "one\ntwo\nthree\nskipped");
+
+    assert_eq!(snapshot.next_excerpt_boundary(0), Some(4));
+    assert_eq!(snapshot.next_excerpt_boundary(3), Some(4));
+    assert_eq!(snapshot.next_excerpt_boundary(4), Some(14));
+    assert_eq!(snapshot.next_excerpt_boundary(10), Some(14));
+    assert_eq!(snapshot.next_excerpt_boundary(14), None);
+    assert_eq!(snapshot.next_excerpt_boundary(snapshot.len()), None);
+
+    assert_eq!(snapshot.prev_excerpt_boundary(0), None);
+    assert_eq!(snapshot.prev_excerpt_boundary(3), Some(0));
+    assert_eq!(snapshot.prev_excerpt_boundary(4), Some(0));
+    assert_eq!(snapshot.prev_excerpt_boundary(5), Some(4));
+    assert_eq!(snapshot.prev_excerpt_boundary(14), Some(4));
+    assert_eq!(snapshot.prev_excerpt_boundary(snapshot.len()), Some(14));
+}
+
+#[gpui::test]
+fn test_buffer_snapshot_for_offset(cx: &mut App) {
+    let buffer_1 = cx.new(|cx| Buffer::local("fn one() {}\nfn two() {}", cx));
+    let buffer_2 = cx.new(|cx| Buffer::local("three", cx));
+    let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
+    multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.push_excerpts(
+            buffer_1.clone(),
+            [ExcerptRange::new(Point::new(1, 0)..Point::new(1, 11))],
+            cx,
+        );
+        multibuffer.push_excerpts(
+            buffer_2.clone(),
+            [ExcerptRange::new(Point::new(0, 0)..Point::new(0, 5))],
+            cx,
+        );
+    });
+    let snapshot = multibuffer.read(cx).snapshot(cx);
+    assert_eq!(snapshot.text(), "fn two() {}\nthree");
+
+    let buffer_1_snapshot = snapshot.buffer_snapshot_for_offset(3).unwrap();
+    assert_eq!(buffer_1_snapshot.remote_id(), buffer_1.read(cx).remote_id());
+    assert_eq!(
+        buffer_1_snapshot
+            .text_for_range(Point::new(1, 0)..Point::new(1, 11))
+            .collect::<String>(),
+        &snapshot.text()[0..11]
+    );
+    let buffer_2_snapshot = snapshot.buffer_snapshot_for_offset(14).unwrap();
+    assert_eq!(buffer_2_snapshot.text(), &snapshot.text()[12..]);
+    assert_eq!(
+        snapshot
+            .buffer_snapshot(buffer_2.read(cx).remote_id())
+            .map(|buffer| buffer.text()),
+        Some("three".to_string())
+    );
+
+    buffer_2.update(cx, |buffer, cx| buffer.edit([(0..0, "edited ")], None, cx));
+    assert_eq!(
+        snapshot
+            .buffer_snapshot(buffer_2.read(cx).remote_id())
+            .map(|buffer| buffer.text()),
+        Some("three".to_string()),
+        "the snapshot keeps the buffer state its excerpts were built from"
+    );
+    assert!(
+        snapshot
+            .buffer_snapshot(BufferId::new(999).unwrap())
+            .is_none()
+    );
+}
+
+#[gpui::test]
+fn test_set_excerpts_for_path_merging_within(cx: &mut App) {
+    let buffer = cx.new(|cx| Buffer::local(sample_text(8, 3, 'a'), cx));
+    for (max_gap_lines, expected_text) in
+        [(0, "bbb\nddd"), (1, "bbb\nccc\nddd"), (2, "bbb\nccc\nddd")]
+    {
+        let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
+        multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.set_excerpts_for_path_merging_within(
+                PathKey::for_buffer(&buffer, cx),
+                buffer.clone(),
+                [
+                    Point::new(1, 0)..Point::new(1, 3),
+                    Point::new(3, 0)..Point::new(3, 3),
+                ],
+                0,
+                max_gap_lines,
+                cx,
+            )
+        });
+        let snapshot = multibuffer.read(cx).snapshot(cx);
+        assert_eq!(snapshot.text(), expected_text, "max gap {max_gap_lines}");
+        assert_eq!(
+            snapshot.excerpt_count(),
+            if max_gap_lines == 0 { 2 } else { 1 },
+            "max gap {max_gap_lines}"
+        );
+    }
+}
+
+#[gpui::test]
+fn test_set_excerpts_for_path_keeping_overlaps(cx: &mut App) {
+    let buffer = cx.new(|cx| Buffer::local(sample_text(8, 3, 'a'), cx));
+    let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
+    let set_excerpts = |merge_behavior, cx: &mut App| {
+        multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.set_excerpts_for_path_with_merge_behavior(
+                PathKey::for_buffer(&buffer, cx),
+                buffer.clone(),
+                [
+                    Point::new(2, 0)..Point::new(3, 3),
+                    Point::new(1, 0)..Point::new(2, 3),
+                ],
+                0,
+                merge_behavior,
+                cx,
+            )
+        })
+    };
+
+    let (ranges, added_a_new_excerpt) = set_excerpts(MergeBehavior::Keep, cx);
+    assert!(added_a_new_excerpt);
+    assert_eq!(ranges.len(), 2);
+    let snapshot = multibuffer.read(cx).snapshot(cx);
+    assert_eq!(snapshot.text(), "bbb\nccc\nccc\nddd");
+    let excerpt_ids = snapshot.excerpts().map(|(id, _, _)| id).collect::<Vec<_>>();
+    assert_eq!(excerpt_ids.len(), 2);
+    assert_eq!(
+        ranges
+            .iter()
+            .map(|range| range.start.excerpt_id)
+            .collect::<Vec<_>>(),
+        excerpt_ids
+    );
+
+    let (_, added_a_new_excerpt) = set_excerpts(MergeBehavior::Keep, cx);
+    assert!(!added_a_new_excerpt);
+    let snapshot = multibuffer.read(cx).snapshot(cx);
+    assert_eq!(snapshot.text(), "bbb\nccc\nccc\nddd");
+    assert_eq!(
+        snapshot.excerpts().map(|(id, _, _)| id).collect::<Vec<_>>(),
+        excerpt_ids,
+        "identical ranges should reuse their excerpts"
+    );
+
+    set_excerpts(MergeBehavior::Merge, cx);
+    let snapshot = multibuffer.read(cx).snapshot(cx);
+    assert_eq!(snapshot.text(), "bbb\nccc\nddd");
+    assert_eq!(snapshot.excerpt_count(), 1);
+}
+
+#[gpui::test]
+fn test_excerpt_and_buffer_counts(cx: &mut App) {
+    let buffer_1 = cx.new(|cx| Buffer::local(sample_text(8, 3, 'a'), cx));
+    let buffer_2 = cx.new(|cx| Buffer::local(sample_text(8, 3, 'j'), cx));
+    let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
+    let assert_counts = |excerpt_count: usize, buffer_count: usize, cx: &mut App| {
+        let multibuffer = multibuffer.read(cx);
+        let snapshot = multibuffer.snapshot(cx);
+        assert_eq!(multibuffer.excerpt_count(), excerpt_count);
+        assert_eq!(snapshot.excerpt_count(), excerpt_count);
+        assert_eq!(multibuffer.buffer_count(), buffer_count);
+        assert_eq!(snapshot.buffer_count(), buffer_count);
+    };
+    assert_counts(0, 0, cx);
+
+    multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.set_excerpts_for_path(
+            PathKey::for_buffer(&buffer_1, cx),
+            buffer_1.clone(),
+            [
+                Point::new(1, 0)..Point::new(2, 3),
+                Point::new(2, 0)..Point::new(3, 3),
+            ],
+            0,
+            cx,
+        );
+    });
+    assert_counts(1, 1, cx);
+
+    let buffer_2_excerpts = multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.push_excerpts(
+            buffer_2.clone(),
+            [
+                ExcerptRange::new(Point::new(0, 0)..Point::new(0, 3)),
+                ExcerptRange::new(Point::new(4, 0)..Point::new(4, 3)),
+            ],
+            cx,
+        )
+    });
+    assert_counts(3, 2, cx);
+
+    multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.remove_excerpts(buffer_2_excerpts, cx);
+    });
+    assert_counts(1, 1, cx);
+}
+
+#[gpui::test]
+fn test_excerpt_tags(cx: &mut App) {
+    fn tag_of(snapshot: &MultiBufferSnapshot, excerpt_id: ExcerptId) -> Option<&'static str> {
+        snapshot
+            .excerpt_tag(excerpt_id)
+            .and_then(|tag| tag.downcast_ref::<&'static str>())
+            .copied()
+    }
+
+    let buffer = cx.new(|cx| Buffer::local(sample_text(8, 3, 'a'), cx));
+    let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
+    let tagged_ids = multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.insert_tagged_excerpts_after(
+            ExcerptId::min(),
+            buffer.clone(),
+            [
+                (
+                    ExcerptRange::new(Point::new(1, 0)..Point::new(1, 3)),
+                    Some(Arc::new("error") as ExcerptTag),
+                ),
+                (ExcerptRange::new(Point::new(3, 0)..Point::new(3, 3)), None),
+            ],
+            cx,
+        )
+    });
+    let untagged_ids = multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.push_excerpts(
+            buffer.clone(),
+            [ExcerptRange::new(Point::new(5, 0)..Point::new(5, 3))],
+            cx,
+        )
+    });
+    let snapshot = multibuffer.read(cx).snapshot(cx);
+    assert_eq!(tag_of(&snapshot, tagged_ids[0]), Some("error"));
+    assert_eq!(tag_of(&snapshot, tagged_ids[1]), None);
+    assert_eq!(tag_of(&snapshot, untagged_ids[0]), None);
+    assert_eq!(
+        snapshot
+            .excerpt_boundaries_in_range(0..snapshot.len())
+            .map(|boundary| boundary.next.tag.is_some())
+            .collect::<Vec<_>>(),
+        [true, false, false]
+    );
+
+    // A merged excerpt keeps the tag of the earliest excerpt that had one.
+    for (tags, expected_tag) in [
+        ([Some("error"), Some("warning")], Some("error")),
+        ([None, Some("warning")], Some("warning")),
+        ([None, None], None),
+    ] {
+        let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
+        let path = PathKey::for_buffer(&buffer, cx);
+        multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.set_excerpts_for_path(
+                path.clone(),
+                buffer.clone(),
+                [
+                    Point::new(1, 0)..Point::new(1, 3),
+                    Point::new(5, 0)..Point::new(5, 3),
+                ],
+                0,
+                cx,
+            );
+            for (excerpt_id, tag) in multibuffer.excerpt_ids().into_iter().zip(tags) {
+                multibuffer.set_excerpt_tag(
+                    excerpt_id,
+                    tag.map(|tag| Arc::new(tag) as ExcerptTag),
+                    cx,
+                );
+            }
+            multibuffer.set_excerpts_for_path(
+                path.clone(),
+                buffer.clone(),
+                [Point::new(1, 0)..Point::new(5, 3)],
+                0,
+                cx,
+            );
+        });
+        let snapshot = multibuffer.read(cx).snapshot(cx);
+        let excerpt_ids = snapshot.excerpts().map(|(id, _, _)| id).collect::<Vec<_>>();
+        assert_eq!(excerpt_ids.len(), 1, "tags {tags:?}");
+        assert_eq!(
+            tag_of(&snapshot, excerpt_ids[0]),
+            expected_tag,
+            "tags {tags:?}"
+        );
+    }
+}
+
+#[gpui::test]
+fn test_set_excerpts(cx: &mut App) {
+    let buffer_1 = cx.new(|cx| Buffer::local(sample_text(8, 3, 'a'), cx));
+    let buffer_2 = cx.new(|cx| Buffer::local("xyz", cx));
+    let rows = |buffer: &Entity<Buffer>, rows: Range<u32>, cx: &App| {
+        let buffer_snapshot = buffer.read(cx);
+        buffer_snapshot.anchor_before(Point::new(rows.start, 0))
+            ..buffer_snapshot.anchor_after(Point::new(rows.end, 3))
+    };
+    let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
+    let old_ids = multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.push_excerpts(
+            buffer_1.clone(),
+            [1, 3, 5].map(|row| ExcerptRange::new(Point::new(row, 0)..Point::new(row, 3))),
+            cx,
+        )
+    });
+    assert_eq!(multibuffer.read(cx).snapshot(cx).text(), "bbb\nddd\nfff");
+
+    let events = Arc::new(RwLock::new(Vec::<Event>::new()));
+    multibuffer.update(cx, |_, cx| {
+        let events = events.clone();
+        cx.subscribe(&multibuffer, move |_, _, event, _| {
+            events.write().push(event.clone())
+        })
+        .detach();
+    });
+    let subscription = multibuffer.update(cx, |multibuffer, _| multibuffer.subscribe());
+
+    let new_ranges = [
+        (buffer_1.clone(), rows(&buffer_1, 3..3, cx)),
+        (buffer_1.clone(), rows(&buffer_1, 5..6, cx)),
+    ];
+    let new_ids = multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.set_excerpts(new_ranges.clone(), cx)
+    });
+    assert_eq!(new_ids[0], old_ids[1], "unchanged excerpts are kept");
+    assert!(!old_ids.contains(&new_ids[1]));
+    assert_eq!(multibuffer.read(cx).snapshot(cx).text(), "ddd\nfff\nggg");
+    assert_eq!(
+        subscription.consume().into_inner(),
+        [
+            Edit {
+                old: 0..4,
+                new: 0..0
+            },
+            Edit {
+                old: 8..11,
+                new: 4..11
+            }
+        ]
+    );
+    {
+        let events = mem::take(&mut *events.write());
+        assert_eq!(events.len(), 3, "{events:?}");
+        assert!(matches!(events[0], Event::Edited { .. }));
+        assert!(matches!(
+            &events[1],
+            Event::ExcerptsRemoved { ids, removed_buffer_ids }
+                if *ids == [old_ids[0], old_ids[2]] && removed_buffer_ids.is_empty()
+        ));
+        assert!(matches!(
+            &events[2],
+            Event::ExcerptsAdded { predecessor, excerpts, .. }
+                if *predecessor == old_ids[1]
+                    && excerpts.iter().map(|(id, _)| *id).collect::<Vec<_>>() == [new_ids[1]]
+        ));
+    }
+
+    let unchanged_ids = multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.set_excerpts(new_ranges.clone(), cx)
+    });
+    assert_eq!(unchanged_ids, new_ids);
+    assert!(subscription.consume().into_inner().is_empty());
+    assert!(events.read().is_empty());
+
+    let buffer_2_range = rows(&buffer_2, 0..0, cx);
+    let buffer_2_ids = multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.set_excerpts([(buffer_2.clone(), buffer_2_range)], cx)
+    });
+    assert_eq!(multibuffer.read(cx).snapshot(cx).text(), "xyz");
+    let buffer_1_id = buffer_1.read(cx).remote_id();
+    assert!(events.read().iter().any(|event| matches!(
+        event,
+        Event::ExcerptsRemoved { ids, removed_buffer_ids }
+            if *ids == new_ids && *removed_buffer_ids == [buffer_1_id]
+    )));
+    assert_eq!(
+        multibuffer.read(cx).excerpt_ids(),
+        buffer_2_ids,
+        "buffers without excerpts are dropped"
+    );
+    assert!(multibuffer.read(cx).buffer(buffer_1_id).is_none());
+}
+
+#[gpui::test]
+fn test_reversed_chars_at(cx: &mut App) {
+    let buffer_1 = cx.new(|cx| Buffer::local("one\ntwö\nthree", cx));
+    let buffer_2 = cx.new(|cx| Buffer::local("fóur\nfive", cx));
+    let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
+    multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.push_excerpts(
+            buffer_1.clone(),
+            [
+                ExcerptRange::new(Point::new(0, 0)..Point::new(1, 4)),
+                ExcerptRange::new(Point::new(2, 0)..Point::new(2, 5)),
+            ],
+            cx,
+        );
+        multibuffer.push_excerpts(
+            buffer_2.clone(),
+            [ExcerptRange::new(Point::new(0, 0)..Point::new(1, 4))],
+            cx,
+        );
+    });
+
+    let snapshot = multibuffer.read(cx).snapshot(cx);
+    let text = snapshot.text();
+    assert_eq!(text, "one\ntwö\nthree\nfóur\nfive");
+    for (offset, _) in text.char_indices().chain([(text.len(), ' ')]) {
+        assert_eq!(
+            snapshot.reversed_chars_at(offset).collect::<String>(),
+            text[..offset].chars().rev().collect::<String>(),
+            "offset {offset}"
+        );
+    }
+}
+
+#[gpui::test]
+fn test_line_count(cx: &mut App) {
+    let buffer_1 = cx.new(|cx| Buffer::local(sample_text(6, 3, 'a'), cx));
+    let buffer_2 = cx.new(|cx| Buffer::local("one\ntwo\n", cx));
+    let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
+    let line_count = |multibuffer: &Entity<MultiBuffer>, cx: &App| {
+        let snapshot = multibuffer.read(cx).snapshot(cx);
+        assert_eq!(
+            snapshot.line_count() as usize,
+            snapshot.text().matches('\n').count() + 1,
+            "text: {:?}",
+            snapshot.text()
+        );
+        snapshot.line_count()
+    };
+    assert_eq!(line_count(&multibuffer, cx), 1);
+
+    multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.push_excerpts(
+            buffer_1.clone(),
+            [
+                ExcerptRange::new(Point::new(0, 0)..Point::new(1, 3)),
+                ExcerptRange::new(Point::new(3, 0)..Point::new(5, 3)),
+            ],
+            cx,
+        );
+        multibuffer.push_excerpts(
+            buffer_2.clone(),
+            [ExcerptRange::new(Point::new(0, 0)..Point::new(2, 0))],
+            cx,
+        );
+    });
+    assert_eq!(line_count(&multibuffer, cx), 8);
+    assert_eq!(
+        multibuffer.read(cx).snapshot(cx).max_row(),
+        MultiBufferRow(7)
+    );
+}
+
+#[gpui::test]
+fn test_excerpt_summary_after_removing_last_excerpt(cx: &mut App) {
+    let buffer = cx.new(|cx| Buffer::local(sample_text(20, 3, 'a'), cx));
+    let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
+    let excerpt_ids = multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.push_excerpts(
+            buffer.clone(),
+            (0..20).map(|row| ExcerptRange::new(Point::new(row, 0)..Point::new(row, 3))),
+            cx,
+        )
+    });
+
+    multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.remove_excerpts([excerpt_ids[19]], cx)
+    });
+    let snapshot = multibuffer.read(cx).snapshot(cx);
+    let last_excerpt = snapshot.excerpts.last().unwrap();
+    assert_eq!(last_excerpt.id, excerpt_ids[18]);
+    assert_eq!(snapshot.excerpts.summary().excerpt_id, excerpt_ids[18]);
+    assert_eq!(
+        snapshot.excerpts.summary().excerpt_locator,
+        last_excerpt.locator
+    );
+
+    let mut cursor = snapshot
+        .excerpts
+        .cursor::<Dimensions<ExcerptOffset, Option<ExcerptId>>>(());
+    cursor.seek(&ExcerptOffset::new(snapshot.len()), Bias::Right);
+    assert!(cursor.item().is_none());
+    assert_eq!(cursor.start().1, Some(excerpt_ids[18]));
+}
+
+#[gpui::test]
+fn test_excerpt_count(cx: &mut App) {
+    let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
+    let snapshot = multibuffer.read(cx).snapshot(cx);
+    assert!(snapshot.is_empty());
+    assert_eq!(snapshot.excerpt_count(), 0);
+
+    let buffer = cx.new(|cx| Buffer::local("one\ntwo", cx));
+    multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.push_excerpts(
+            buffer.clone(),
+            [
+                ExcerptRange::new(Point::new(0, 0)..Point::new(0, 0)),
+                ExcerptRange::new(Point::new(1, 0)..Point::new(1, 0)),
+            ],
+            cx,
+        );
+    });
+    let snapshot = multibuffer.read(cx).snapshot(cx);
+    assert_eq!(snapshot.text(), "\n");
+    assert!(!snapshot.is_empty());
+    assert_eq!(snapshot.excerpt_count(), 2);
+
+    multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.push_excerpts(
+            buffer.clone(),
+            [ExcerptRange::new(Point::new(0, 0)..Point::new(1, 3))],
+            cx,
+        );
+    });
+    let snapshot = multibuffer.read(cx).snapshot(cx);
+    assert_eq!(snapshot.text(), "\n\none\ntwo");
+    assert!(!snapshot.is_empty());
+    assert_eq!(snapshot.excerpt_count(), 3);
+
+    multibuffer.update(cx, |multibuffer, cx| multibuffer.clear(cx));
+    let snapshot = multibuffer.read(cx).snapshot(cx);
+    assert!(snapshot.is_empty());
+    assert_eq!(snapshot.excerpt_count(), 0);
+}
+
+#[gpui::test]
+fn test_chunks_buffer_ids(cx: &mut App) {
+    let buffer_1 = cx.new(|cx| Buffer::local("one\ntwo\nthree", cx));
+    let buffer_2 = cx.new(|cx| Buffer::local("alpha\nbeta", cx));
+    let buffer_1_id = buffer_1.read(cx).remote_id();
+    let buffer_2_id = buffer_2.read(cx).remote_id();
+    let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
+    multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.push_excerpts(
+            buffer_1.clone(),
+            [ExcerptRange::new(Point::new(1, 0)..Point::new(2, 5))],
+            cx,
+        );
+        multibuffer.push_excerpts(
+            buffer_2.clone(),
+            [ExcerptRange::new(Point::new(0, 0)..Point::new(1, 4))],
+            cx,
+        );
+    });
+
+    let snapshot = multibuffer.read(cx).snapshot(cx);
+    assert_eq!(snapshot.text(), "two\nthree\nalpha\nbeta");
+
+    let mut chunks = snapshot.chunks(4..15, false);
+    let mut chunks_by_buffer: Vec<(Option<BufferId>, String)> = Vec::new();
+    while let Some(chunk) = chunks.next() {
+        let buffer_id = chunks.buffer_id();
+        match chunks_by_buffer.last_mut() {
+            Some((last_buffer_id, text)) if *last_buffer_id == buffer_id => {
+                text.push_str(chunk.text)
+            }
+            _ => chunks_by_buffer.push((buffer_id, chunk.text.to_string())),
+        }
+    }
+    assert_eq!(
+        chunks_by_buffer,
+        [
+            (Some(buffer_1_id), "three\n".to_string()),
+            (Some(buffer_2_id), "alpha".to_string()),
+        ]
+    );
+}
+
+#[gpui::test]
+fn test_insert_whole_buffer(cx: &mut App) {
+    let buffer_1 = cx.new(|cx| Buffer::local("one\ntwo", cx));
+    let buffer_2 = cx.new(|cx| Buffer::local("three", cx));
+    let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
+
+    let excerpt_id = multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.insert_whole_buffer(buffer_1.clone(), cx)
+    });
+    assert!(excerpt_id.is_some());
+    assert_eq!(multibuffer.read(cx).snapshot(cx).text(), "one\ntwo");
+
+    let repeated_excerpt_id = multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.insert_whole_buffer(buffer_1.clone(), cx)
+    });
+    assert_eq!(repeated_excerpt_id, excerpt_id);
+    assert_eq!(multibuffer.read(cx).snapshot(cx).text(), "one\ntwo");
+
+    multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.insert_whole_buffer(buffer_2.clone(), cx);
+        multibuffer.insert_whole_buffer(buffer_1.clone(), cx);
+    });
+    assert_eq!(multibuffer.read(cx).snapshot(cx).text(), "one\ntwo\nthree");
+}
+
+#[gpui::test]
+fn test_buffer_for_path(cx: &mut App) {
+    let buffer_1 = cx.new(|cx| {
+        let mut buffer = Buffer::local("one", cx);
+        buffer.file_updated(test_file("src/main.rs"), cx);
+        buffer
+    });
+    let buffer_2 = cx.new(|cx| {
+        let mut buffer = Buffer::local("two", cx);
+        buffer.file_updated(test_file("src/lib.rs"), cx);
+        buffer
+    });
+    let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
+    multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.insert_whole_buffer(buffer_1.clone(), cx);
+        multibuffer.insert_whole_buffer(buffer_2.clone(), cx);
+    });
+
+    assert_eq!(
+        multibuffer.read(cx).all_buffers(),
+        HashSet::from_iter([buffer_1.clone(), buffer_2.clone()])
+    );
+    assert_eq!(
+        multibuffer
+            .read(cx)
+            .buffer_for_path(Path::new("zed/src/lib.rs"), cx),
+        Some(buffer_2.clone())
+    );
+    assert_eq!(
+        multibuffer
+            .read(cx)
+            .buffer_for_path(Path::new("zed/src/other.rs"), cx),
+        None
+    );
+
+    buffer_2.update(cx, |buffer, cx| {
+        buffer.file_updated(test_file("src/renamed.rs"), cx)
+    });
+    assert_eq!(
+        multibuffer
+            .read(cx)
+            .buffer_for_path(Path::new("zed/src/renamed.rs"), cx),
+        Some(buffer_2.clone())
+    );
+    assert_eq!(
+        multibuffer
+            .read(cx)
+            .buffer_for_path(Path::new("zed/src/lib.rs"), cx),
+        None
+    );
+}
+
+fn test_file(path: &str) -> Arc<dyn language::File> {
+    Arc::new(language::TestFile {
+        path: Arc::from(util::rel_path::rel_path(path)),
+        root_name: "zed".into(),
+        local_root: None,
+    })
+}
+
 #[gpui::test]
 fn test_excerpt_boundaries_and_clipping(cx: &mut App) {
     let buffer_1 = cx.new(|cx| Buffer::local(sample_text(6, 6, 'a'), cx));
@@ -347,6 +1133,121 @@ fn test_excerpt_boundaries_and_clipping(cx: &mut App) {
     }
 }
 
+#[gpui::test]
+fn test_to_offset_with_bias(cx: &mut App) {
+    let buffer_1 = cx.new(|cx| Buffer::local("αβγ\nabc", cx));
+    let buffer_2 = cx.new(|cx| Buffer::local("xyz", cx));
+    let multibuffer = cx.new(|cx| {
+        let mut multibuffer = MultiBuffer::new(Capability::ReadWrite);
+        multibuffer.push_excerpts(
+            buffer_1.clone(),
+            [ExcerptRange::new(Point::new(0, 0)..Point::new(1, 3))],
+            cx,
+        );
+        multibuffer.push_excerpts(
+            buffer_2.clone(),
+            [ExcerptRange::new(Point::new(0, 0)..Point::new(0, 3))],
+            cx,
+        );
+        multibuffer
+    });
+    let snapshot = multibuffer.read(cx).snapshot(cx);
+    assert_eq!(snapshot.text(), "αβγ\nabc\nxyz");
+
+    // Positions inside a multi-byte character snap to either side of it.
+    assert_eq!(1usize.to_offset_with_bias(&snapshot, Bias::Left), 0);
+    assert_eq!(1usize.to_offset_with_bias(&snapshot, Bias::Right), 2);
+    assert_eq!(
+        Point::new(0, 3).to_offset_with_bias(&snapshot, Bias::Left),
+        2
+    );
+    assert_eq!(
+        Point::new(0, 3).to_offset_with_bias(&snapshot, Bias::Right),
+        4
+    );
+
+    // Positions past the end of a line or the snapshot are clipped.
+    for bias in [Bias::Left, Bias::Right] {
+        assert_eq!(Point::new(0, 100).to_offset_with_bias(&snapshot, bias), 6);
+        assert_eq!(Point::new(2, 0).to_offset_with_bias(&snapshot, bias), 11);
+        assert_eq!(Point::new(5, 0).to_offset_with_bias(&snapshot, bias), 14);
+        assert_eq!(100usize.to_offset_with_bias(&snapshot, bias), 14);
+        assert_eq!(
+            snapshot
+                .anchor_before(11)
+                .to_offset_with_bias(&snapshot, bias),
+            11
+        );
+    }
+}
+
+#[gpui::test]
+fn test_removing_all_excerpts_for_buffer_drops_it(cx: &mut TestAppContext) {
+    let buffer_1 = cx.new(|cx| Buffer::local("one\ntwo", cx));
+    let buffer_2 = cx.new(|cx| Buffer::local("three\nfour", cx));
+    let buffer_1_id = buffer_1.read_with(cx, |buffer, _| buffer.remote_id());
+    let buffer_2_id = buffer_2.read_with(cx, |buffer, _| buffer.remote_id());
+    let diff_1 = cx.new(|cx| BufferDiff::new_with_base_text("one\n", &buffer_1, cx));
+    let diff_2 = cx.new(|cx| BufferDiff::new_with_base_text("three\n", &buffer_2, cx));
+    let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
+    let excerpt_ids = multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.insert_whole_buffer(buffer_1.clone(), cx);
+        let excerpt_ids = multibuffer.push_excerpts(
+            buffer_2.clone(),
+            [
+                ExcerptRange::new(Point::new(0, 0)..Point::new(0, 0)),
+                ExcerptRange::new(Point::new(1, 0)..Point::new(1, 4)),
+            ],
+            cx,
+        );
+        multibuffer.add_diff(diff_1, cx);
+        multibuffer.add_diff(diff_2, cx);
+        excerpt_ids
+    });
+
+    // A zero-length excerpt still keeps its buffer alive.
+    multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.remove_excerpts([excerpt_ids[1]], cx)
+    });
+    multibuffer.read_with(cx, |multibuffer, cx| {
+        assert!(multibuffer.all_buffer_ids().contains(&buffer_2_id));
+        assert!(multibuffer.diff_for(buffer_2_id).is_some());
+        assert!(
+            multibuffer
+                .snapshot(cx)
+                .diff_for_buffer_id(buffer_2_id)
+                .is_some()
+        );
+    });
+
+    multibuffer.update(cx, |multibuffer, cx| {
+        multibuffer.remove_excerpts([excerpt_ids[0]], cx)
+    });
+    multibuffer.read_with(cx, |multibuffer, cx| {
+        assert_eq!(multibuffer.all_buffer_ids(), [buffer_1_id]);
+        assert!(multibuffer.buffer(buffer_2_id).is_none());
+        assert!(multibuffer.diff_for(buffer_2_id).is_none());
+        assert!(
+            multibuffer
+                .snapshot(cx)
+                .diff_for_buffer_id(buffer_2_id)
+                .is_none()
+        );
+    });
+
+    multibuffer.update(cx, |multibuffer, cx| multibuffer.clear(cx));
+    multibuffer.read_with(cx, |multibuffer, cx| {
+        assert!(multibuffer.all_buffer_ids().is_empty());
+        assert!(multibuffer.diff_for(buffer_1_id).is_none());
+        assert!(
+            multibuffer
+                .snapshot(cx)
+                .diff_for_buffer_id(buffer_1_id)
+                .is_none()
+        );
+    });
+}
+
 #[gpui::test]
 fn test_diff_boundary_anchors(cx: &mut TestAppContext) {
     let base_text = "one\ntwo\nthree\n";
@@ -389,6 +1290,81 @@ fn test_diff_boundary_anchors(cx: &mut TestAppContext) {
     })
 }
 
+#[gpui::test]
+async fn test_set_diff_base(cx: &mut TestAppContext) {
+    let buffer = cx.new(|cx| Buffer::local("one\ntwo\nthree\n", cx));
+    let base = cx.new(|cx| Buffer::local("one\n2\nthree\n", cx));
+    let multibuffer = cx.new(|cx| MultiBuffer::singleton(buffer.clone(), cx));
+    let snapshot = multibuffer.read_with(cx, |multibuffer, cx| multibuffer.snapshot(cx));
+    assert_eq!(snapshot.diff_hunks().count(), 0);
+
+    let buffer_id = buffer.read_with(cx, |buffer, _| buffer.remote_id());
+    let base_snapshot = base.read_with(cx, |base, _| base.snapshot());
+    multibuffer
+        .update(cx, |multibuffer, cx| {
+            multibuffer.set_diff_base(buffer_id, base_snapshot, cx)
+        })
+        .await;
+    cx.run_until_parked();
+
+    let snapshot = multibuffer.read_with(cx, |multibuffer, cx| multibuffer.snapshot(cx));
+    assert_eq!(snapshot.text(), "one\ntwo\nthree\n");
+    let hunks = snapshot.diff_hunks().collect::<Vec<_>>();
+    assert_eq!(hunks.len(), 1);
+    assert_eq!(hunks[0].row_range, MultiBufferRow(1)..MultiBufferRow(2));
+    assert_eq!(hunks[0].diff_base_byte_range, 4..6);
+    assert_eq!(hunks[0].status().kind, DiffHunkStatusKind::Modified);
+}
+
+#[gpui::test]
+async fn test_set_diff_base_leaves_shared_diff_untouched(cx: &mut TestAppContext) {
+    let buffer = cx.new(|cx| Buffer::local("one\ntwo\nthree\n", cx));
+    let git_diff = cx.new(|cx| BufferDiff::new_with_base_text("ONE\ntwo\nthree\n", &buffer, cx));
+    let multibuffer_a = cx.new(|cx| {
+        let mut multibuffer = MultiBuffer::singleton(buffer.clone(), cx);
+        multibuffer.add_diff(git_diff.clone(), cx);
+        multibuffer
+    });
+    let multibuffer_b = cx.new(|cx| {
+        let mut multibuffer = MultiBuffer::singleton(buffer.clone(), cx);
+        multibuffer.add_diff(git_diff.clone(), cx);
+        multibuffer
+    });
+    cx.run_until_parked();
+
+    let buffer_id = buffer.read_with(cx, |buffer, _| buffer.remote_id());
+    let base = cx.new(|cx| Buffer::local("one\n2\nthree\n", cx));
+    let base_snapshot = base.read_with(cx, |base, _| base.snapshot());
+    multibuffer_a
+        .update(cx, |multibuffer, cx| {
+            multibuffer.set_diff_base(buffer_id, base_snapshot, cx)
+        })
+        .await;
+    cx.run_until_parked();
+
+    let hunk_rows = |multibuffer: &Entity<MultiBuffer>, cx: &mut TestAppContext| {
+        multibuffer.read_with(cx, |multibuffer, cx| {
+            multibuffer
+                .snapshot(cx)
+                .diff_hunks()
+                .map(|hunk| hunk.row_range)
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(
+        hunk_rows(&multibuffer_a, cx),
+        [MultiBufferRow(1)..MultiBufferRow(2)]
+    );
+    assert_eq!(
+        hunk_rows(&multibuffer_b, cx),
+        [MultiBufferRow(0)..MultiBufferRow(1)]
+    );
+    assert_eq!(
+        git_diff.read_with(cx, |diff, _| diff.base_text_string()),
+        Some("ONE\ntwo\nthree\n".to_string())
+    );
+}
+
 #[gpui::test]
 fn test_diff_hunks_in_range(cx: &mut TestAppContext) {
     let base_text = "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\n";
@@ -867,6 +1843,67 @@ async fn test_set_anchored_excerpts_for_path(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+fn test_excerpts_rev(cx: &mut App) {
+    let multibuffer = MultiBuffer::build_multi(
+        [
+            ("abc\ndef\nghi", vec![Point::new(0, 0)..Point::new(0, 3)]),
+            (
+                "jkl\nmno\npqr",
+                vec![
+                    Point::new(0, 0)..Point::new(0, 3),
+                    Point::new(2, 0)..Point::new(2, 3),
+                ],
+            ),
+        ],
+        cx,
+    );
+    let snapshot = multibuffer.read(cx).snapshot(cx);
+    let summarize = |(id, buffer, range): (ExcerptId, &BufferSnapshot, ExcerptRange<_>)| {
+        (id, buffer.remote_id(), range)
+    };
+
+    let mut forward = snapshot.excerpts().map(summarize).collect::<Vec<_>>();
+    assert_eq!(forward.len(), 3);
+    assert_eq!(
+        snapshot.last_excerpt().map(summarize),
+        forward.last().cloned()
+    );
+    forward.reverse();
+    assert_eq!(
+        snapshot.excerpts_rev().map(summarize).collect::<Vec<_>>(),
+        forward
+    );
+
+    let empty = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
+    let snapshot = empty.read(cx).snapshot(cx);
+    assert!(snapshot.excerpts_rev().next().is_none());
+    assert!(snapshot.last_excerpt().is_none());
+}
+
+#[gpui::test]
+fn test_longest_row(cx: &mut App) {
+    let multibuffer = MultiBuffer::build_multi(
+        [
+            (
+                "aa\nbbbbbbbb\ncc",
+                vec![
+                    Point::new(0, 0)..Point::new(0, 2),
+                    Point::new(2, 0)..Point::new(2, 2),
+                ],
+            ),
+            ("dddd\ne", vec![Point::new(0, 0)..Point::new(1, 1)]),
+        ],
+        cx,
+    );
+    let snapshot = multibuffer.read(cx).snapshot(cx);
+    assert_eq!(snapshot.text(), "aa\ncc\ndddd\ne");
+    assert_eq!(snapshot.max_point(), Point::new(3, 1));
+    // The longest line of the first buffer is not part of any excerpt.
+    assert_eq!(snapshot.longest_row(), MultiBufferRow(2));
+    assert_eq!(snapshot.line_len(snapshot.longest_row()), 4);
+}
+
 #[gpui::test]
 fn test_empty_multibuffer(cx: &mut App) {
     let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadWrite));
@@ -115,7 +115,7 @@ impl DapStore {
         static ADD_LOCATORS: Once = Once::new();
         ADD_LOCATORS.call_once(|| {
             let registry = DapRegistry::global(cx);
-            registry.add_locator(Arc::new(locators::cargo::CargoLocator {}));
+            registry.add_locator(Arc::new(locators::cargo::CargoLocator::default()));
             registry.add_locator(Arc::new(locators::go::GoLocator {}));
             registry.add_locator(Arc::new(locators::node::NodeLocator));
             registry.add_locator(Arc::new(locators::python::PythonLocator));
@@ -335,11 +335,14 @@ impl DapStore {
         match &self.mode {
             DapStoreMode::Local(_) => {
                 // Pre-resolve args with existing environment.
-                let locators = DapRegistry::global(cx).locators();
-                let locator = locators.get(locator_name);
-
-                if let Some(locator) = locator.cloned() {
-                    cx.background_spawn(async move {
+                match DapRegistry::global(cx).locator(locator_name) {
+                    Ok(locator) => cx.background_spawn(async move {
+                        anyhow::ensure!(
+                            locator.can_locate(&build_command),
+                            "Locator `{}` can't find a debug target for task `{}`. Check that the task's working directory is inside a project it supports",
+                            locator.name(),
+                            build_command.label
+                        );
                         let result = locator
                             .run(build_command.clone())
                             .await
@@ -352,12 +355,11 @@ impl DapStore {
                             "None of the locators for task `{}` completed successfully",
                             build_command.label
                         )
-                    })
-                } else {
-                    Task::ready(Err(anyhow!(
-                        "Couldn't find any locator for task `{}`. Specify the `attach` or `launch` arguments in your debug scenario definition",
+                    }),
+                    Err(err) => Task::ready(Err(anyhow!(
+                        "{err}. Specify the `attach` or `launch` arguments in the debug scenario for task `{}`",
                         build_command.label
-                    )))
+                    ))),
                 }
             }
             DapStoreMode::Remote(remote) => {
@@ -1,17 +1,213 @@
-use anyhow::{Context as _, Result};
+use anyhow::{Context as _, Result, anyhow};
 use async_trait::async_trait;
+use collections::HashMap;
 use dap::{DapLocator, DebugRequest, adapters::DebugAdapterName};
+use futures::{
+    FutureExt as _,
+    future::{BoxFuture, Shared},
+};
 use gpui::SharedString;
+use parking_lot::Mutex;
 use serde_json::{Value, json};
 use smol::{
     Timer,
     io::AsyncReadExt,
     process::{Command, Stdio},
 };
-use std::time::Duration;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 use task::{BuildTaskDefinition, DebugScenario, ShellBuilder, SpawnInTerminal, TaskTemplate};
 
-pub(crate) struct CargoLocator;
+#[derive(Default)]
+pub(crate) struct CargoLocator {
+    resolutions: ResolutionCache,
+}
+
+/// Identifies a cargo invocation whose located executable can be reused.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct ResolutionKey {
+    cwd: PathBuf,
+    args: Vec<String>,
+    env: Vec<(String, String)>,
+}
+
+impl ResolutionKey {
+    fn new(cwd: PathBuf, build_config: &SpawnInTerminal) -> Self {
+        let mut env: Vec<_> = build_config
+            .env
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        env.sort();
+        Self {
+            cwd,
+            args: build_config.args.clone(),
+            env,
+        }
+    }
+}
+
+/// An executable located by cargo, along with the local sources it was built from.
+#[derive(Clone)]
+struct Resolution {
+    request: DebugRequest,
+    sources: Vec<PathBuf>,
+}
+
+impl Resolution {
+    /// Returns whether the executable still exists and none of its sources changed
+    /// since it was built, as `cargo clean` or an edit would otherwise leave it stale.
+    fn is_current(&self) -> bool {
+        let DebugRequest::Launch(launch) = &self.request else {
+            return false;
+        };
+        let Some(built) = std::fs::metadata(&launch.program)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+        else {
+            return false;
+        };
+        !self.sources.is_empty()
+            && newest_modification(&self.sources).is_some_and(|newest| newest <= built)
+    }
+}
+
+type SharedResolution = Shared<BoxFuture<'static, Result<Resolution, Arc<anyhow::Error>>>>;
+
+/// Remembers located executables for as long as the sources they were built from
+/// are unchanged, so that relaunching a debug session doesn't wait on cargo again.
+///
+/// Identical requests that arrive while cargo is still running share its result.
+#[derive(Default)]
+struct ResolutionCache {
+    entries: Mutex<HashMap<ResolutionKey, SharedResolution>>,
+}
+
+impl ResolutionCache {
+    /// Returns the cached resolution for `key` while its executable is still current,
+    /// and runs `resolve` otherwise.
+    async fn get_or_resolve(
+        &self,
+        key: ResolutionKey,
+        resolve: impl FnOnce() -> BoxFuture<'static, Result<Resolution>>,
+    ) -> Result<DebugRequest> {
+        let cached = self.entries.lock().get(&key).cloned();
+        let resolution = match cached {
+            Some(resolution) => match resolution.clone().await {
+                Ok(cached) => {
+                    let request = cached.request.clone();
+                    if smol::unblock(move || cached.is_current()).await {
+                        return Ok(request);
+                    }
+                    self.start_resolution(key.clone(), resolve)
+                }
+                Err(_) => resolution,
+            },
+            None => self.start_resolution(key.clone(), resolve),
+        };
+
+        match resolution.clone().await {
+            Ok(resolution) => Ok(resolution.request),
+            Err(error) => {
+                // Failed builds are usually fixed by the user before retrying, so
+                // the failure isn't replayed to later requests.
+                let mut entries = self.entries.lock();
+                if entries
+                    .get(&key)
+                    .is_some_and(|cached| cached.ptr_eq(&resolution))
+                {
+                    entries.remove(&key);
+                }
+                Err(anyhow!("{error:#}"))
+            }
+        }
+    }
+
+    fn start_resolution(
+        &self,
+        key: ResolutionKey,
+        resolve: impl FnOnce() -> BoxFuture<'static, Result<Resolution>>,
+    ) -> SharedResolution {
+        let resolution = resolve()
+            .map(|result| result.map_err(Arc::new))
+            .boxed()
+            .shared();
+        self.entries.lock().insert(key, resolution.clone());
+        resolution
+    }
+}
+
+/// Returns the most recent modification time of `paths` and of anything under the
+/// directories among them, or `None` if any of them can't be read.
+fn newest_modification(paths: &[PathBuf]) -> Option<SystemTime> {
+    let mut newest = SystemTime::UNIX_EPOCH;
+    let mut pending_paths = paths.to_vec();
+    while let Some(path) = pending_paths.pop() {
+        let metadata = std::fs::metadata(&path).ok()?;
+        newest = newest.max(metadata.modified().ok()?);
+        if metadata.is_dir() {
+            for entry in std::fs::read_dir(&path).ok()? {
+                pending_paths.push(entry.ok()?.path());
+            }
+        }
+    }
+    Some(newest)
+}
+
+/// Collects the sources of the local packages in cargo's `--message-format=json`
+/// output, along with the manifest and lockfile of the workspace containing `cwd`.
+///
+/// Cargo only reports each target's root file, so the whole directory holding it is
+/// collected, unless that is the package root itself, as for `build.rs`.
+fn local_sources(cargo_output: &str, cwd: &Path) -> Vec<PathBuf> {
+    let mut sources = Vec::new();
+    for json in cargo_output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+    {
+        if json.get("reason").and_then(Value::as_str) != Some("compiler-artifact") {
+            continue;
+        }
+        let is_local = json
+            .get("package_id")
+            .and_then(Value::as_str)
+            .is_some_and(|package_id| package_id.contains("path+file://"));
+        let manifest_path = json.get("manifest_path").and_then(Value::as_str);
+        let src_path = json
+            .get("target")
+            .and_then(|target| target.get("src_path"))
+            .and_then(Value::as_str);
+        let (true, Some(manifest_path), Some(src_path)) = (is_local, manifest_path, src_path)
+        else {
+            continue;
+        };
+
+        let manifest_path = PathBuf::from(manifest_path);
+        let src_path = PathBuf::from(src_path);
+        match src_path.parent() {
+            Some(directory) if Some(directory) != manifest_path.parent() => {
+                sources.push(directory.to_path_buf())
+            }
+            _ => sources.push(src_path),
+        }
+        sources.push(manifest_path);
+    }
+
+    if let Some(workspace_dir) = cwd
+        .ancestors()
+        .find(|directory| directory.join("Cargo.lock").is_file())
+    {
+        sources.push(workspace_dir.join("Cargo.lock"));
+        sources.push(workspace_dir.join("Cargo.toml"));
+    }
+    sources.sort();
+    sources.dedup();
+    sources
+}
 
 async fn find_best_executable(executables: &[String], test_name: &str) -> Option<String> {
     if executables.len() == 1 {
@@ -53,6 +249,106 @@ async fn find_best_executable(executables: &[String], test_name: &str) -> Option
     }
     None
 }
+
+/// Collects the executables of the artifacts in cargo's `--message-format=json` output,
+/// keeping only test harnesses when `is_test` is set and only regular binaries otherwise.
+fn artifact_executables(cargo_output: &str, is_test: bool) -> Vec<String> {
+    cargo_output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .filter(|json: &Value| {
+            json.get("reason").and_then(Value::as_str) == Some("compiler-artifact")
+        })
+        .filter(|json| {
+            let is_test_binary = json
+                .get("profile")
+                .and_then(|profile| profile.get("test"))
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            is_test_binary == is_test
+        })
+        .filter_map(|json| {
+            json.get("executable")
+                .and_then(Value::as_str)
+                .map(String::from)
+        })
+        .collect()
+}
+
+/// Cargo options whose value is passed as a separate argument, so it isn't mistaken for
+/// the test name filter.
+const CARGO_OPTIONS_WITH_VALUES: &[&str] = &[
+    "-p",
+    "--package",
+    "--exclude",
+    "--bin",
+    "--test",
+    "--example",
+    "--bench",
+    "-F",
+    "--features",
+    "--target",
+    "--target-dir",
+    "--manifest-path",
+    "--profile",
+    "-j",
+    "--jobs",
+    "--color",
+    "--config",
+    "-Z",
+];
+
+/// Finds the test name filter in the arguments of a `cargo test` invocation: the last
+/// positional argument passed to the test harness after `--`, or else the one passed to
+/// cargo itself.
+fn test_name_filter(args: &[String]) -> Option<&String> {
+    let (cargo_args, harness_args) = match args.iter().rposition(|arg| arg == "--") {
+        Some(separator_ix) => (&args[..separator_ix], &args[separator_ix + 1..]),
+        None => (args, &[][..]),
+    };
+    if let Some(test_name) = harness_args.iter().rev().find(|arg| !arg.starts_with('-')) {
+        return Some(test_name);
+    }
+
+    let mut test_name = None;
+    // The first argument is the `test` subcommand itself.
+    let mut cargo_args = cargo_args.iter().skip(1);
+    while let Some(arg) = cargo_args.next() {
+        if CARGO_OPTIONS_WITH_VALUES.contains(&arg.as_str()) {
+            cargo_args.next();
+        } else if !arg.starts_with('-') {
+            test_name = Some(arg);
+        }
+    }
+    test_name
+}
+
+async fn select_test_executable(executables: &[String], test_name: Option<&str>) -> Result<String> {
+    match executables {
+        [] => anyhow::bail!("Cargo didn't build a test executable to debug"),
+        [executable] => Ok(executable.clone()),
+        _ => {
+            let Some(test_name) = test_name else {
+                anyhow::bail!(
+                    "Cargo built {} test executables ({}), pick one by naming a test \
+                    or passing `--lib`, `--bin <name>` or `--test <name>`",
+                    executables.len(),
+                    executables.join(", ")
+                );
+            };
+            find_best_executable(executables, test_name)
+                .await
+                .with_context(|| {
+                    format!(
+                        "None of the {} test executables built by cargo contain a test matching `{test_name}`",
+                        executables.len()
+                    )
+                })
+        }
+    }
+}
+
 #[async_trait]
 impl DapLocator for CargoLocator {
     fn name(&self) -> SharedString {
@@ -112,106 +408,430 @@ impl DapLocator for CargoLocator {
         })
     }
 
+    fn can_locate(&self, build_config: &SpawnInTerminal) -> bool {
+        build_config.cwd.as_deref().is_some_and(|cwd| {
+            cwd.ancestors()
+                .any(|directory| directory.join("Cargo.toml").is_file())
+        })
+    }
+
     async fn run(&self, build_config: SpawnInTerminal) -> Result<DebugRequest> {
         let cwd = build_config
             .cwd
             .clone()
             .context("Couldn't get cwd from debug config which is needed for locators")?;
-        let builder = ShellBuilder::new(None, &build_config.shell).non_interactive();
-        let (program, args) = builder.build(
-            Some("cargo".into()),
-            &build_config
-                .args
-                .iter()
-                .cloned()
-                .take_while(|arg| arg != "--")
-                .chain(Some("--message-format=json".to_owned()))
-                .collect::<Vec<_>>(),
-        );
-        let mut child = util::command::new_smol_command(program)
-            .args(args)
-            .envs(build_config.env.iter().map(|(k, v)| (k.clone(), v.clone())))
-            .current_dir(cwd)
-            .stdout(Stdio::piped())
-            .spawn()?;
+        let key = ResolutionKey::new(cwd.clone(), &build_config);
+        self.resolutions
+            .get_or_resolve(key, move || build_and_locate(cwd, build_config).boxed())
+            .await
+    }
+}
 
-        let mut output = String::new();
-        if let Some(mut stdout) = child.stdout.take() {
-            stdout.read_to_string(&mut output).await?;
-        }
+async fn build_and_locate(cwd: PathBuf, build_config: SpawnInTerminal) -> Result<Resolution> {
+    let builder = ShellBuilder::new(None, &build_config.shell).non_interactive();
+    let (program, args) = builder.build(
+        Some("cargo".into()),
+        &build_config
+            .args
+            .iter()
+            .cloned()
+            .take_while(|arg| arg != "--")
+            .chain(Some("--message-format=json".to_owned()))
+            .collect::<Vec<_>>(),
+    );
+    let mut child = util::command::new_smol_command(program)
+        .args(args)
+        .envs(build_config.env.iter().map(|(k, v)| (k.clone(), v.clone())))
+        .current_dir(&cwd)
+        .stdout(Stdio::piped())
+        .spawn()?;
 
-        let status = child.status().await?;
-        anyhow::ensure!(status.success(), "Cargo command failed");
+    let mut output = String::new();
+    if let Some(mut stdout) = child.stdout.take() {
+        stdout.read_to_string(&mut output).await?;
+    }
 
-        let is_test = build_config
-            .args
+    let status = child.status().await?;
+    anyhow::ensure!(status.success(), "Cargo command failed");
+
+    let is_test = build_config
+        .args
+        .first()
+        .is_some_and(|arg| arg == "test" || arg == "t");
+
+    let executables = artifact_executables(&output, is_test);
+
+    let mut test_name = None;
+    if is_test {
+        test_name = test_name_filter(&build_config.args).cloned();
+    }
+    let executable = if is_test {
+        let test_name = test_name.as_ref().and_then(|name| {
+            name.strip_prefix('$')
+                .map(|name| build_config.env.get(name))
+                .unwrap_or(Some(name))
+        });
+        select_test_executable(&executables, test_name.map(String::as_str)).await?
+    } else {
+        executables
             .first()
-            .is_some_and(|arg| arg == "test" || arg == "t");
-
-        let executables = output
-            .lines()
-            .filter(|line| !line.trim().is_empty())
-            .filter_map(|line| serde_json::from_str(line).ok())
-            .filter(|json: &Value| {
-                let is_test_binary = json
-                    .get("profile")
-                    .and_then(|profile| profile.get("test"))
-                    .and_then(Value::as_bool)
-                    .unwrap_or(false);
-
-                if is_test {
-                    is_test_binary
-                } else {
-                    !is_test_binary
-                }
-            })
-            .filter_map(|json: Value| {
-                json.get("executable")
-                    .and_then(Value::as_str)
-                    .map(String::from)
+            .cloned()
+            .context("Couldn't get executable in cargo locator")?
+    };
+
+    let mut args: Vec<_> = test_name.into_iter().collect();
+    if is_test {
+        args.push("--nocapture".to_owned());
+    }
+
+    Ok(Resolution {
+        request: DebugRequest::Launch(task::LaunchRequest {
+            program: executable,
+            cwd: build_config.cwd,
+            args,
+            env: build_config.env.into_iter().collect(),
+        }),
+        sources: local_sources(&output, &cwd),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+    use util::test::TempTree;
+
+    #[test]
+    fn test_can_locate_requires_cargo_manifest() {
+        let tree = TempTree::new(json!({
+            "crate": {
+                "Cargo.toml": "[package]",
+                "src": {
+                    "main.rs": "fn main() {}",
+                },
+            },
+            "not_a_crate": {
+                "main.rs": "fn main() {}",
+            },
+        }));
+        let build_config = |cwd: Option<&str>| SpawnInTerminal {
+            command: Some("cargo".into()),
+            args: vec!["build".into()],
+            cwd: cwd.map(|cwd| tree.path().join(cwd)),
+            ..SpawnInTerminal::default()
+        };
+
+        assert!(CargoLocator::default().can_locate(&build_config(Some("crate"))));
+        assert!(CargoLocator::default().can_locate(&build_config(Some("crate/src"))));
+        assert!(!CargoLocator::default().can_locate(&build_config(Some("not_a_crate"))));
+        assert!(!CargoLocator::default().can_locate(&build_config(None)));
+    }
+
+    #[test]
+    fn test_test_name_filter() {
+        let filter = |args: &[&str]| {
+            let args = args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+            test_name_filter(&args).cloned()
+        };
+
+        assert_eq!(filter(&["test"]), None);
+        assert_eq!(filter(&["test", "-p", "demo"]), None);
+        assert_eq!(filter(&["test", "--package", "demo", "--lib"]), None);
+        assert_eq!(filter(&["test", "--test", "integration"]), None);
+        assert_eq!(
+            filter(&["test", "-p", "demo", "it_works"]).as_deref(),
+            Some("it_works")
+        );
+        assert_eq!(
+            filter(&["test", "--package=demo", "it_works", "--", "--nocapture"]).as_deref(),
+            Some("it_works")
+        );
+        assert_eq!(
+            filter(&["test", "-p", "demo", "--", "tests::it_works", "--exact"]).as_deref(),
+            Some("tests::it_works")
+        );
+        assert_eq!(
+            filter(&[
+                "test",
+                "-p",
+                "$ZED_CUSTOM_RUST_PACKAGE",
+                "--",
+                "$ZED_SYMBOL"
+            ])
+            .as_deref(),
+            Some("$ZED_SYMBOL")
+        );
+    }
+
+    #[gpui::test]
+    async fn test_selects_test_executable_from_cargo_output(_: &mut gpui::TestAppContext) {
+        let cargo_output = [
+            json!({
+                "reason": "compiler-artifact",
+                "target": { "name": "demo", "kind": ["lib"] },
+                "profile": { "test": false },
+                "executable": null,
+            }),
+            json!({
+                "reason": "compiler-message",
+                "message": { "rendered": "warning: unused variable" },
+            }),
+            json!({
+                "reason": "compiler-artifact",
+                "target": { "name": "demo", "kind": ["bin"] },
+                "profile": { "test": false },
+                "executable": "/demo/target/debug/demo",
+            }),
+            json!({
+                "reason": "compiler-artifact",
+                "target": { "name": "demo", "kind": ["lib"] },
+                "profile": { "test": true },
+                "executable": "/demo/target/debug/deps/demo-4a1b2c3d4e5f6a7b",
+            }),
+            json!({ "reason": "build-finished", "success": true }),
+        ]
+        .iter()
+        .map(Value::to_string)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+        let test_executables = artifact_executables(&cargo_output, true);
+        assert_eq!(
+            test_executables,
+            ["/demo/target/debug/deps/demo-4a1b2c3d4e5f6a7b"]
+        );
+        assert_eq!(
+            select_test_executable(&test_executables, Some("tests::it_works"))
+                .await
+                .unwrap(),
+            "/demo/target/debug/deps/demo-4a1b2c3d4e5f6a7b"
+        );
+        assert_eq!(
+            artifact_executables(&cargo_output, false),
+            ["/demo/target/debug/demo"]
+        );
+
+        assert!(select_test_executable(&[], None).await.is_err());
+        let ambiguous = [
+            "/demo/target/debug/deps/demo-4a1b2c3d4e5f6a7b".to_owned(),
+            "/demo/target/debug/deps/integration-7b6a5f4e3d2c1b0a".to_owned(),
+        ];
+        assert!(select_test_executable(&ambiguous, None).await.is_err());
+    }
+
+    #[test]
+    fn test_local_sources_from_cargo_output() {
+        let tree = TempTree::new(json!({
+            "Cargo.toml": "[workspace]",
+            "Cargo.lock": "",
+            "demo": {
+                "Cargo.toml": "[package]",
+                "build.rs": "fn main() {}",
+                "src": { "main.rs": "fn main() {}" },
+                "tests": { "it.rs": "" },
+            },
+        }));
+        let workspace_dir = tree.path();
+        let demo_dir = workspace_dir.join("demo");
+        let artifact = |package_id: &str, manifest_path: &Path, src_path: &Path| {
+            json!({
+                "reason": "compiler-artifact",
+                "package_id": package_id,
+                "manifest_path": manifest_path,
+                "target": { "src_path": src_path },
             })
-            .collect::<Vec<_>>();
-        anyhow::ensure!(
-            !executables.is_empty(),
-            "Couldn't get executable in cargo locator"
+        };
+        let demo_id = format!("path+file://{}#0.1.0", demo_dir.display());
+        let cargo_output = [
+            artifact(
+                "registry+https://github.com/rust-lang/crates.io-index#serde@1.0.0",
+                Path::new("/registry/serde/Cargo.toml"),
+                Path::new("/registry/serde/src/lib.rs"),
+            ),
+            artifact(
+                &demo_id,
+                &demo_dir.join("Cargo.toml"),
+                &demo_dir.join("build.rs"),
+            ),
+            artifact(
+                &demo_id,
+                &demo_dir.join("Cargo.toml"),
+                &demo_dir.join("src/main.rs"),
+            ),
+            artifact(
+                &demo_id,
+                &demo_dir.join("Cargo.toml"),
+                &demo_dir.join("tests/it.rs"),
+            ),
+            json!({ "reason": "build-finished", "success": true }),
+        ]
+        .iter()
+        .map(Value::to_string)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+        assert_eq!(
+            local_sources(&cargo_output, &demo_dir),
+            [
+                workspace_dir.join("Cargo.lock"),
+                workspace_dir.join("Cargo.toml"),
+                demo_dir.join("Cargo.toml"),
+                demo_dir.join("build.rs"),
+                demo_dir.join("src"),
+                demo_dir.join("tests"),
+            ]
         );
+    }
 
-        let mut test_name = None;
-        if is_test {
-            test_name = build_config
-                .args
-                .iter()
-                .rev()
-                .take_while(|name| "--" != name.as_str())
-                .find(|name| !name.starts_with("-"))
-                .cloned();
-        }
-        let executable = {
-            if let Some(name) = test_name.as_ref().and_then(|name| {
-                name.strip_prefix('$')
-                    .map(|name| build_config.env.get(name))
-                    .unwrap_or(Some(name))
-            }) {
-                find_best_executable(&executables, name).await
-            } else {
-                None
+    #[gpui::test]
+    async fn test_resolution_cache(_: &mut gpui::TestAppContext) {
+        let tree = TempTree::new(json!({
+            "workspace": {
+                "Cargo.toml": "[workspace]",
+                "Cargo.lock": "",
+                "demo": {
+                    "Cargo.toml": "[package]",
+                    "build.rs": "fn main() {}",
+                    "src": {
+                        "main.rs": "fn main() {}",
+                    },
+                    "tests": {
+                        "it.rs": "",
+                    },
+                },
+                "target": {
+                    "debug": {},
+                },
+            },
+        }));
+        let workspace_dir = tree.path().join("workspace");
+        let demo_dir = workspace_dir.join("demo");
+        let touch = |path: &Path, seconds_from_now: u64| {
+            std::fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(SystemTime::now() + Duration::from_secs(seconds_from_now))
+                .unwrap();
+        };
+        let build = |name: &str, seconds_from_now: u64| {
+            let path = workspace_dir.join("target/debug").join(name);
+            std::fs::write(&path, "").unwrap();
+            touch(&path, seconds_from_now);
+            path.to_string_lossy().into_owned()
+        };
+        let sources = vec![
+            workspace_dir.join("Cargo.lock"),
+            workspace_dir.join("Cargo.toml"),
+            demo_dir.join("Cargo.toml"),
+            demo_dir.join("build.rs"),
+            demo_dir.join("src"),
+            demo_dir.join("tests"),
+        ];
+        let resolution = |program: String| Resolution {
+            request: DebugRequest::Launch(task::LaunchRequest {
+                program,
+                ..Default::default()
+            }),
+            sources: sources.clone(),
+        };
+
+        let cache = ResolutionCache::default();
+        let resolve_count = Arc::new(AtomicUsize::new(0));
+        let key = ResolutionKey::new(demo_dir.clone(), &SpawnInTerminal::default());
+        let resolve = |result: Result<String>| {
+            let resolve_count = resolve_count.clone();
+            let resolution = result.map(resolution);
+            move || {
+                resolve_count.fetch_add(1, SeqCst);
+                async move { resolution }.boxed()
             }
         };
+        let program = |request: Result<DebugRequest>| match request.unwrap() {
+            DebugRequest::Launch(launch) => launch.program,
+            DebugRequest::Attach(_) => panic!("expected a launch request"),
+        };
 
-        let Some(executable) = executable.or_else(|| executables.first().cloned()) else {
-            anyhow::bail!("Couldn't get executable in cargo locator");
+        let first = build("first", 10);
+        let (build_finished_tx, build_finished_rx) = futures::channel::oneshot::channel::<()>();
+        let first_request = cache.get_or_resolve(key.clone(), {
+            let resolve_count = resolve_count.clone();
+            let first_resolution = resolution(first.clone());
+            move || {
+                resolve_count.fetch_add(1, SeqCst);
+                async move {
+                    build_finished_rx.await.ok();
+                    Ok(first_resolution)
+                }
+                .boxed()
+            }
+        });
+        let second_request = cache.get_or_resolve(key.clone(), resolve(Ok(build("second", 10))));
+        let finish_build = async move {
+            smol::future::yield_now().await;
+            build_finished_tx.send(()).ok();
         };
+        let (first_request, second_request, ()) =
+            futures::join!(first_request, second_request, finish_build);
+        assert_eq!(program(first_request), first);
+        assert_eq!(program(second_request), first);
+        assert_eq!(resolve_count.load(SeqCst), 1);
+
+        let request = cache
+            .get_or_resolve(key.clone(), resolve(Ok(build("third", 10))))
+            .await;
+        assert_eq!(program(request), first);
+        assert_eq!(resolve_count.load(SeqCst), 1);
+
+        // A removed executable is located again.
+        std::fs::remove_file(&first).unwrap();
+        let fourth = build("fourth", 10);
+        let request = cache
+            .get_or_resolve(key.clone(), resolve(Ok(fourth.clone())))
+            .await;
+        assert_eq!(program(request), fourth);
+        assert_eq!(resolve_count.load(SeqCst), 2);
+
+        // Editing any of the sources, including the workspace's manifest and lockfile,
+        // locates the executable again.
+        let mut expected_count = 2;
+        for (ix, source) in [
+            workspace_dir.join("Cargo.toml"),
+            workspace_dir.join("Cargo.lock"),
+            demo_dir.join("Cargo.toml"),
+            demo_dir.join("build.rs"),
+            demo_dir.join("src/main.rs"),
+            demo_dir.join("tests/it.rs"),
+        ]
+        .iter()
+        .enumerate()
+        {
+            let edited_at = 1000 * (ix as u64 + 1);
+            touch(source, edited_at);
+            let rebuilt = build(&format!("rebuilt-{ix}"), edited_at + 500);
+            let request = cache
+                .get_or_resolve(key.clone(), resolve(Ok(rebuilt.clone())))
+                .await;
+            assert_eq!(program(request), rebuilt, "after editing {source:?}");
+            expected_count += 1;
+            assert_eq!(resolve_count.load(SeqCst), expected_count);
 
-        let mut args: Vec<_> = test_name.into_iter().collect();
-        if is_test {
-            args.push("--nocapture".to_owned());
+            let request = cache
+                .get_or_resolve(key.clone(), resolve(Ok(build("unused", 10))))
+                .await;
+            assert_eq!(program(request), rebuilt, "after editing {source:?}");
+            assert_eq!(resolve_count.load(SeqCst), expected_count);
         }
 
-        Ok(DebugRequest::Launch(task::LaunchRequest {
-            program: executable,
-            cwd: build_config.cwd,
-            args,
-            env: build_config.env.into_iter().collect(),
-        }))
+        // Failed builds are retried rather than replayed.
+        touch(&demo_dir.join("src/main.rs"), 8000);
+        let failed = cache
+            .get_or_resolve(key.clone(), resolve(Err(anyhow!("Cargo command failed"))))
+            .await;
+        assert!(failed.is_err());
+        let fixed = build("fixed", 10_000);
+        let request = cache.get_or_resolve(key, resolve(Ok(fixed.clone()))).await;
+        assert_eq!(program(request), fixed);
+        assert_eq!(resolve_count.load(SeqCst), expected_count + 2);
     }
 }
@@ -1226,7 +1226,10 @@ mod tests {
                     &PathWithPosition {
                         path: PathBuf::from(self.expected_hyperlink.iri_or_path.clone()),
                         row: self.expected_hyperlink.row,
-                        column: self.expected_hyperlink.column
+                        column: self.expected_hyperlink.column,
+                        end_row: None,
+                        end_column: None,
+                        offset: None
                     },
                     &self.expected_hyperlink.hyperlink_match
                 ),
@@ -156,6 +156,9 @@ fn possible_open_target(
                 path: stripped.to_owned(),
                 row: original_path.row,
                 column: original_path.column,
+                end_row: original_path.end_row,
+                end_column: original_path.end_column,
+                offset: original_path.offset,
             });
         }
         if let Some(stripped) = path_with_position.path.strip_prefix(prefix_str).ok() {
@@ -163,6 +166,9 @@ fn possible_open_target(
                 path: stripped.to_owned(),
                 row: path_with_position.row,
                 column: path_with_position.column,
+                end_row: path_with_position.end_row,
+                end_column: path_with_position.end_column,
+                offset: path_with_position.offset,
             });
         }
     }
@@ -197,6 +203,9 @@ fn possible_open_target(
                     path: worktree_root.to_path_buf(),
                     row: path_with_position.row,
                     column: path_with_position.column,
+                    end_row: path_with_position.end_row,
+                    end_column: path_with_position.end_column,
+                    offset: path_with_position.offset,
                 };
                 match worktree.read(cx).root_entry() {
                     Some(root_entry) => {
@@ -219,6 +228,9 @@ fn possible_open_target(
                         .to_owned(),
                     row: path_with_position.row,
                     column: path_with_position.column,
+                    end_row: path_with_position.end_row,
+                    end_column: path_with_position.end_column,
+                    offset: path_with_position.offset,
                 }
             };
 
@@ -239,6 +251,9 @@ fn possible_open_target(
                         path: worktree.read(cx).absolutize(&entry.path),
                         row: path_to_check.row,
                         column: path_to_check.column,
+                        end_row: path_to_check.end_row,
+                        end_column: path_to_check.end_column,
+                        offset: path_to_check.offset,
                     },
                     entry.clone(),
                     #[cfg(test)]
@@ -284,6 +299,9 @@ fn possible_open_target(
                                 path: cwd.join(&maybe_path),
                                 row: path_to_check.row,
                                 column: path_to_check.column,
+                                end_row: path_to_check.end_row,
+                                end_column: path_to_check.end_column,
+                                offset: path_to_check.offset,
                             });
                         }
                     }
@@ -308,6 +326,9 @@ fn possible_open_target(
                                         path: home_path,
                                         row: path_to_check.row,
                                         column: path_to_check.column,
+                                        end_row: path_to_check.end_row,
+                                        end_column: path_to_check.end_column,
+                                        offset: path_to_check.offset,
                                     });
                                 }
                             } else {
@@ -315,6 +336,9 @@ fn possible_open_target(
                                     path: maybe_path.clone(),
                                     row: path_to_check.row,
                                     column: path_to_check.column,
+                                    end_row: path_to_check.end_row,
+                                    end_column: path_to_check.end_column,
+                                    offset: path_to_check.offset,
                                 });
                                 if maybe_path.is_relative() {
                                     for worktree in &worktree_candidates {
@@ -323,6 +347,9 @@ fn possible_open_target(
                                                 path: worktree.read(cx).abs_path().join(maybe_path),
                                                 row: path_to_check.row,
                                                 column: path_to_check.column,
+                                                end_row: path_to_check.end_row,
+                                                end_column: path_to_check.end_column,
+                                                offset: path_to_check.offset,
                                             });
                                         }
                                     }
@@ -378,6 +405,9 @@ fn possible_open_target(
                                         path: worktree.absolutize(&entry.path),
                                         row: path_in_worktree.row,
                                         column: path_in_worktree.column,
+                                        end_row: path_in_worktree.end_row,
+                                        end_column: path_in_worktree.end_column,
+                                        offset: path_in_worktree.offset,
                                     },
                                     entry.clone(),
                                     #[cfg(test)]
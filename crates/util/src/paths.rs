@@ -1,4 +1,4 @@
-use globset::{Glob, GlobSet, GlobSetBuilder};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
@@ -35,6 +35,8 @@ pub fn home_dir() -> &'static PathBuf {
 
 pub trait PathExt {
     fn compact(&self) -> PathBuf;
+    fn relative_to(&self, base: &Path) -> Option<PathBuf>;
+    fn compact_relative(&self, base: &Path) -> PathBuf;
     fn extension_or_hidden_file_name(&self) -> Option<&str>;
     fn try_from_bytes<'a>(bytes: &'a [u8]) -> anyhow::Result<Self>
     where
@@ -70,10 +72,15 @@ impl<T: AsRef<Path>> PathExt for T {
     ///
     /// * A `PathBuf` containing the compacted file path. If the input path
     ///   does not have the user's home directory prefix, or if we are not on
-    ///   Linux or macOS, the original path is returned unchanged.
+    ///   Linux, macOS or Windows, the original path is returned unchanged.
     fn compact(&self) -> PathBuf {
-        if cfg!(any(target_os = "linux", target_os = "freebsd")) || cfg!(target_os = "macos") {
-            match self.as_ref().strip_prefix(home_dir().as_path()) {
+        if cfg!(any(target_os = "linux", target_os = "freebsd"))
+            || cfg!(target_os = "macos")
+            || cfg!(target_os = "windows")
+        {
+            // Verbatim `\\?\` paths on Windows would otherwise never share a prefix with the home directory.
+            let path = SanitizedPath::new(self.as_ref());
+            match path.strip_prefix(SanitizedPath::new(home_dir())) {
                 Ok(relative_path) => {
                     let mut shortened_path = PathBuf::new();
                     shortened_path.push("~");
@@ -87,6 +94,33 @@ impl<T: AsRef<Path>> PathExt for T {
         }
     }
 
+    /// Returns the path relative to `base`, or `.` when it is `base` itself.
+    /// Returns `None` if the path is not inside of `base`.
+    fn relative_to(&self, base: &Path) -> Option<PathBuf> {
+        let relative_path = SanitizedPath::new(self.as_ref())
+            .strip_prefix(SanitizedPath::new(base))
+            .ok()?;
+        if relative_path.as_os_str().is_empty() {
+            Some(PathBuf::from("."))
+        } else {
+            Some(relative_path.to_path_buf())
+        }
+    }
+
+    /// Returns the shorter of the path relative to `base` and the path with
+    /// the home directory replaced by a tilde, preferring the relative one.
+    fn compact_relative(&self, base: &Path) -> PathBuf {
+        let compacted_path = self.compact();
+        match self.relative_to(base) {
+            Some(relative_path)
+                if relative_path.as_os_str().len() <= compacted_path.as_os_str().len() =>
+            {
+                relative_path
+            }
+            _ => compacted_path,
+        }
+    }
+
     /// Returns a file's extension or, if the file is hidden, its name without the leading dot
     fn extension_or_hidden_file_name(&self) -> Option<&str> {
         let path = self.as_ref();
@@ -351,6 +385,8 @@ pub fn is_absolute(path_like: &str, path_style: PathStyle) -> bool {
 
 /// A delimiter to use in `path_query:row_number:column_number` strings parsing.
 pub const FILE_ROW_COLUMN_DELIMITER: char = ':';
+/// Separates a path from a byte offset into the file, as in `file.rs@1234`.
+pub const FILE_OFFSET_DELIMITER: char = '@';
 
 const ROW_COL_CAPTURE_REGEX: &str = r"(?xs)
     ([^\(]+)\:(?:
@@ -373,14 +409,45 @@ const ROW_COL_CAPTURE_REGEX: &str = r"(?xs)
         \:+()()$
     )";
 
-/// A representation of a path-like string with optional row and column numbers.
-/// Matching values example: `te`, `test.rs:22`, `te:22:5`, `test.c(22)`, `test.c(22,5)`etc.
+const ROW_COL_RANGE_CAPTURE_REGEX: &str = r"(?x)
+    ^(.+?)\:(\d+)\:(\d+)-(\d+)\:(\d+)$ # filename:row:column-row:column
+";
+
+/// Drops a single stray space or redundant trailing `.` that pasted stack traces
+/// sometimes leave between a file name and its `:row:column` suffix.
+fn trim_position_artifacts(file_name: &str) -> &str {
+    let file_name = file_name.strip_suffix(' ').unwrap_or(file_name);
+    match file_name.strip_suffix('.') {
+        Some(trimmed) if !trimmed.is_empty() && !trimmed.ends_with('.') => trimmed,
+        _ => file_name,
+    }
+}
+
+/// Whether row and column numbers in a string start counting at zero or at one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineBase {
+    Zero,
+    #[default]
+    One,
+}
+
+/// A representation of a path-like string with optional row and column numbers, or a byte offset.
+/// Matching values example: `te`, `test.rs:22`, `te:22:5`, `te:22:5-24:1`, `test.c(22)`, `test.c(22,5)`, `test.rs@1234` etc.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
 pub struct PathWithPosition {
     pub path: PathBuf,
     pub row: Option<u32>,
     // Absent if row is absent.
     pub column: Option<u32>,
+    /// The end of a `row:column-row:column` range, present only when both the start row and column are.
+    #[serde(default)]
+    pub end_row: Option<u32>,
+    // Absent if end_row is absent.
+    #[serde(default)]
+    pub end_column: Option<u32>,
+    /// A byte offset into the file from an `@offset` suffix. Absent if row is present.
+    #[serde(default)]
+    pub offset: Option<usize>,
 }
 
 impl PathWithPosition {
@@ -390,12 +457,16 @@ impl PathWithPosition {
             path,
             row: None,
             column: None,
+            end_row: None,
+            end_column: None,
+            offset: None,
         }
     }
 
     /// Parses a string that possibly has `:row:column` or `(row, column)` suffix.
     /// Parenthesis format is used by [MSBuild](https://learn.microsoft.com/en-us/visualstudio/msbuild/msbuild-diagnostic-format-for-tasks) compatible tools
     /// Ignores trailing `:`s, so `test.rs:22:` is parsed as `test.rs:22`.
+    /// An `@offset` suffix, as in `test.rs@1234`, is parsed as a byte offset instead of a row and column.
     /// If the suffix parsing fails, the whole string is parsed as a path.
     ///
     /// Be mindful that `test_file:10:1:` is a valid posix filename.
@@ -410,26 +481,41 @@ impl PathWithPosition {
     ///     path: PathBuf::from("test_file"),
     ///     row: None,
     ///     column: None,
+    ///     end_row: None,
+    ///     end_column: None,
+    ///     offset: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file:10"), PathWithPosition {
     ///     path: PathBuf::from("test_file"),
     ///     row: Some(10),
     ///     column: None,
+    ///     end_row: None,
+    ///     end_column: None,
+    ///     offset: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file.rs"), PathWithPosition {
     ///     path: PathBuf::from("test_file.rs"),
     ///     row: None,
     ///     column: None,
+    ///     end_row: None,
+    ///     end_column: None,
+    ///     offset: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file.rs:1"), PathWithPosition {
     ///     path: PathBuf::from("test_file.rs"),
     ///     row: Some(1),
     ///     column: None,
+    ///     end_row: None,
+    ///     end_column: None,
+    ///     offset: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file.rs:1:2"), PathWithPosition {
     ///     path: PathBuf::from("test_file.rs"),
     ///     row: Some(1),
     ///     column: Some(2),
+    ///     end_row: None,
+    ///     end_column: None,
+    ///     offset: None,
     /// });
     /// ```
     ///
@@ -441,41 +527,65 @@ impl PathWithPosition {
     ///     path: PathBuf::from("test_file.rs:a"),
     ///     row: None,
     ///     column: None,
+    ///     end_row: None,
+    ///     end_column: None,
+    ///     offset: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file.rs:a:b"), PathWithPosition {
     ///     path: PathBuf::from("test_file.rs:a:b"),
     ///     row: None,
     ///     column: None,
+    ///     end_row: None,
+    ///     end_column: None,
+    ///     offset: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file.rs"), PathWithPosition {
     ///     path: PathBuf::from("test_file.rs"),
     ///     row: None,
     ///     column: None,
+    ///     end_row: None,
+    ///     end_column: None,
+    ///     offset: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file.rs::1"), PathWithPosition {
     ///     path: PathBuf::from("test_file.rs"),
     ///     row: Some(1),
     ///     column: None,
+    ///     end_row: None,
+    ///     end_column: None,
+    ///     offset: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file.rs:1::"), PathWithPosition {
     ///     path: PathBuf::from("test_file.rs"),
     ///     row: Some(1),
     ///     column: None,
+    ///     end_row: None,
+    ///     end_column: None,
+    ///     offset: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file.rs::1:2"), PathWithPosition {
     ///     path: PathBuf::from("test_file.rs"),
     ///     row: Some(1),
     ///     column: Some(2),
+    ///     end_row: None,
+    ///     end_column: None,
+    ///     offset: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file.rs:1::2"), PathWithPosition {
     ///     path: PathBuf::from("test_file.rs:1"),
     ///     row: Some(2),
     ///     column: None,
+    ///     end_row: None,
+    ///     end_column: None,
+    ///     offset: None,
     /// });
     /// assert_eq!(PathWithPosition::parse_str("test_file.rs:1:2:3"), PathWithPosition {
     ///     path: PathBuf::from("test_file.rs:1"),
     ///     row: Some(2),
     ///     column: Some(3),
+    ///     end_row: None,
+    ///     end_column: None,
+    ///     offset: None,
     /// });
     /// ```
     pub fn parse_str(s: &str) -> Self {
@@ -487,12 +597,56 @@ impl PathWithPosition {
                 path: Path::new(s).to_path_buf(),
                 row: None,
                 column: None,
+                end_row: None,
+                end_column: None,
+                offset: None,
+            };
+        }
+
+        if let Some((file_name, offset)) =
+            maybe_file_name_with_row_col.rsplit_once(FILE_OFFSET_DELIMITER)
+            && !file_name.is_empty()
+            && !offset.is_empty()
+            && offset.bytes().all(|byte| byte.is_ascii_digit())
+            && let Ok(offset) = offset.parse::<usize>()
+        {
+            let suffix_length = maybe_file_name_with_row_col.len() - file_name.len();
+            return Self {
+                path: Path::new(&trimmed[..trimmed.len() - suffix_length]).to_path_buf(),
+                row: None,
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: Some(offset),
             };
         }
 
-        // Let's avoid repeated init cost on this. It is subject to thread contention, but
+        // Let's avoid repeated init cost on these. It is subject to thread contention, but
         // so far this code isn't called from multiple hot paths. Getting contention here
         // in the future seems unlikely.
+        static RANGE_SUFFIX_RE: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(ROW_COL_RANGE_CAPTURE_REGEX).unwrap());
+        if let Some((_, [file_name, row, column, end_row, end_column])) = RANGE_SUFFIX_RE
+            .captures(&maybe_file_name_with_row_col)
+            .map(|caps| caps.extract())
+            && let (Ok(row), Ok(column), Ok(end_row), Ok(end_column)) = (
+                row.parse::<u32>(),
+                column.parse::<u32>(),
+                end_row.parse::<u32>(),
+                end_column.parse::<u32>(),
+            )
+        {
+            let suffix_length = maybe_file_name_with_row_col.len() - file_name.len();
+            return Self {
+                path: Path::new(&trimmed[..trimmed.len() - suffix_length]).to_path_buf(),
+                row: Some(row),
+                column: Some(column),
+                end_row: Some(end_row),
+                end_column: Some(end_column),
+                offset: None,
+            };
+        }
+
         static SUFFIX_RE: LazyLock<Regex> =
             LazyLock::new(|| Regex::new(ROW_COL_CAPTURE_REGEX).unwrap());
         match SUFFIX_RE
@@ -503,6 +657,14 @@ impl PathWithPosition {
                 let row = maybe_row.parse::<u32>().ok();
                 let column = maybe_column.parse::<u32>().ok();
 
+                let file_name = if row.is_some()
+                    && maybe_file_name_with_row_col[file_name.len()..]
+                        .starts_with(FILE_ROW_COLUMN_DELIMITER)
+                {
+                    trim_position_artifacts(file_name)
+                } else {
+                    file_name
+                };
                 let suffix_length = maybe_file_name_with_row_col.len() - file_name.len();
                 let path_without_suffix = &trimmed[..trimmed.len() - suffix_length];
 
@@ -510,6 +672,9 @@ impl PathWithPosition {
                     path: Path::new(path_without_suffix).to_path_buf(),
                     row,
                     column,
+                    end_row: None,
+                    end_column: None,
+                    offset: None,
                 }
             }
             None => {
@@ -549,11 +714,49 @@ impl PathWithPosition {
                     path: PathBuf::from(path_string),
                     row,
                     column,
+                    end_row: None,
+                    end_column: None,
+                    offset: None,
                 }
             }
         }
     }
 
+    /// Like [`Self::parse_str`], but interprets the parsed row and column numbers in `base`.
+    /// The result always stores one-based positions, as `parse_str` does.
+    pub fn parse_str_with_base(s: &str, base: LineBase) -> Self {
+        let mut parsed = Self::parse_str(s);
+        if base == LineBase::Zero {
+            for position in [
+                &mut parsed.row,
+                &mut parsed.column,
+                &mut parsed.end_row,
+                &mut parsed.end_column,
+            ] {
+                *position = position.map(|position| position.saturating_add(1));
+            }
+        }
+        parsed
+    }
+
+    pub fn row_one_based(&self) -> Option<u32> {
+        self.row
+    }
+
+    /// Returns the zero-based row, treating a malformed row `0` as the first row.
+    pub fn row_zero_based(&self) -> Option<u32> {
+        self.row.map(|row| row.saturating_sub(1))
+    }
+
+    pub fn column_one_based(&self) -> Option<u32> {
+        self.column
+    }
+
+    /// Returns the zero-based column, treating a malformed column `0` as the first column.
+    pub fn column_zero_based(&self) -> Option<u32> {
+        self.column.map(|column| column.saturating_sub(1))
+    }
+
     pub fn map_path<E>(
         self,
         mapping: impl FnOnce(PathBuf) -> Result<PathBuf, E>,
@@ -562,28 +765,67 @@ impl PathWithPosition {
             path: mapping(self.path)?,
             row: self.row,
             column: self.column,
+            end_row: self.end_row,
+            end_column: self.end_column,
+            offset: self.offset,
         })
     }
 
     pub fn to_string(&self, path_to_string: impl Fn(&PathBuf) -> String) -> String {
         let path_string = path_to_string(&self.path);
+        if let Some(offset) = self.offset {
+            return format!("{path_string}{FILE_OFFSET_DELIMITER}{offset}");
+        }
         if let Some(row) = self.row {
             if let Some(column) = self.column {
-                format!("{path_string}:{row}:{column}")
+                match (self.end_row, self.end_column) {
+                    (Some(end_row), Some(end_column)) => {
+                        format!("{path_string}:{row}:{column}-{end_row}:{end_column}")
+                    }
+                    _ => format!("{path_string}:{row}:{column}"),
+                }
+            } else if ends_with_row_suffix(&path_string) {
+                // `parse_str` would read the path's own `:N` suffix as the row,
+                // a doubled delimiter keeps it part of the path.
+                format!("{path_string}::{row}")
             } else {
                 format!("{path_string}:{row}")
             }
+        } else if Self::parse_str(&path_string).offset.is_some() {
+            // `parse_str` would read the path's own `@N` suffix as an offset,
+            // a trailing delimiter, which it ignores, keeps it part of the path.
+            format!("{path_string}{FILE_ROW_COLUMN_DELIMITER}")
         } else {
             path_string
         }
     }
 }
 
+fn ends_with_row_suffix(path: &str) -> bool {
+    path.rsplit_once(FILE_ROW_COLUMN_DELIMITER)
+        .is_some_and(|(_, suffix)| {
+            !suffix.is_empty() && suffix.bytes().all(|byte| byte.is_ascii_digit())
+        })
+}
+
 #[derive(Clone, Debug)]
 pub struct PathMatcher {
     sources: Vec<String>,
     glob: GlobSet,
     path_style: PathStyle,
+    options: PathMatcherOptions,
+    /// Whether the source at the same index was prefixed with `!`,
+    /// re-including paths matched by earlier sources.
+    negated: Vec<bool>,
+    /// Set by [`PathMatcher::match_all`], since no list of sources can express it.
+    match_all: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PathMatcherOptions {
+    /// Match globs and literal prefixes without regard to ASCII case,
+    /// e.g. for case-insensitive file systems.
+    pub case_insensitive: bool,
 }
 
 // impl std::fmt::Display for PathMatcher {
@@ -594,45 +836,157 @@ pub struct PathMatcher {
 
 impl PartialEq for PathMatcher {
     fn eq(&self, other: &Self) -> bool {
-        self.sources.eq(&other.sources)
+        self.sources.eq(&other.sources) && self.match_all == other.match_all
     }
 }
 
 impl Eq for PathMatcher {}
 
 impl PathMatcher {
+    /// Builds a matcher from glob sources. With no sources, the matcher matches
+    /// nothing; use [`Self::match_all`] for one that matches every path.
     pub fn new(
         globs: impl IntoIterator<Item = impl AsRef<str>>,
         path_style: PathStyle,
     ) -> Result<Self, globset::Error> {
-        let globs = globs
-            .into_iter()
-            .map(|as_str| Glob::new(as_str.as_ref()))
-            .collect::<Result<Vec<_>, _>>()?;
-        let sources = globs.iter().map(|glob| glob.glob().to_owned()).collect();
+        Self::new_with_options(globs, path_style, PathMatcherOptions::default())
+    }
+
+    pub fn new_with_options(
+        globs: impl IntoIterator<Item = impl AsRef<str>>,
+        path_style: PathStyle,
+        options: PathMatcherOptions,
+    ) -> Result<Self, globset::Error> {
+        let mut sources = Vec::new();
+        let mut negated = Vec::new();
         let mut glob_builder = GlobSetBuilder::new();
-        for single_glob in globs {
-            glob_builder.add(single_glob);
+        for source in globs {
+            let source = source.as_ref();
+            let (is_negated, pattern) = match source.strip_prefix('!') {
+                Some(pattern) => (true, pattern),
+                None => (false, source),
+            };
+            glob_builder.add(
+                GlobBuilder::new(pattern)
+                    .case_insensitive(options.case_insensitive)
+                    .build()?,
+            );
+            sources.push(source.to_owned());
+            negated.push(is_negated);
         }
         let glob = glob_builder.build()?;
         Ok(PathMatcher {
             glob,
             sources,
             path_style,
+            options,
+            negated,
+            match_all: false,
         })
     }
 
+    /// A matcher with no sources that matches every path.
+    pub fn match_all(path_style: PathStyle) -> Self {
+        Self {
+            path_style,
+            match_all: true,
+            ..Self::default()
+        }
+    }
+
     pub fn sources(&self) -> &[String] {
         &self.sources
     }
 
+    /// Matches the path without knowing whether it's a file or a directory, as the
+    /// union of [`Self::is_match_dir`] and [`Self::is_match_file`]. Directory globs
+    /// like `**/src/**` therefore also match a file named `src`.
     pub fn is_match<P: AsRef<Path>>(&self, other: P) -> bool {
-        let other_path = other.as_ref();
-        self.sources.iter().any(|source| {
-            let as_bytes = other_path.as_os_str().as_encoded_bytes();
-            as_bytes.starts_with(source.as_bytes()) || as_bytes.ends_with(source.as_bytes())
-        }) || self.glob.is_match(other_path)
-            || self.check_with_end_separator(other_path)
+        let other = other.as_ref();
+        self.is_match_dir(other) || self.is_match_file(other)
+    }
+
+    /// Matches the path as a directory: globs are also tested against the path with a
+    /// trailing separator, so `**/src/**` matches the `src` directory itself, and a
+    /// source that is a literal prefix or suffix of the path matches it.
+    pub fn is_match_dir<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.is_match_inner(path.as_ref(), MatchAs::Dir)
+    }
+
+    /// Matches the path as a file, only against the globs themselves: neither a
+    /// trailing separator nor the literal prefix and suffix checks are applied.
+    pub fn is_match_file<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.is_match_inner(path.as_ref(), MatchAs::File)
+    }
+
+    /// Whether any of the matchers matches the path, stopping at the first one that does.
+    pub fn matches_any(matchers: &[PathMatcher], path: &Path) -> bool {
+        matchers.iter().any(|matcher| matcher.is_match(path))
+    }
+
+    /// Whether none of the matchers match the path, stopping at the first one that does.
+    pub fn matches_none(matchers: &[PathMatcher], path: &Path) -> bool {
+        !Self::matches_any(matchers, path)
+    }
+
+    fn is_match_inner(&self, other_path: &Path, match_as: MatchAs) -> bool {
+        if self.match_all {
+            return true;
+        }
+        if !self.negated.contains(&true) {
+            return match match_as {
+                MatchAs::Dir => {
+                    self.sources.iter().any(|source| {
+                        let as_bytes = other_path.as_os_str().as_encoded_bytes();
+                        self.starts_or_ends_with(as_bytes, source.as_bytes())
+                    }) || self.glob.is_match(other_path)
+                        || self.check_with_end_separator(other_path)
+                }
+                MatchAs::File => self.glob.is_match(other_path),
+            };
+        }
+
+        // Like gitignore, the last source that matches decides whether the path is included.
+        self.matching_indices(other_path, match_as)
+            .into_iter()
+            .max()
+            .is_some_and(|ix| !self.negated[ix])
+    }
+
+    /// Returns the sources that match the path, in source order, including negated ones.
+    /// Useful for explaining why a path was included or excluded.
+    pub fn matching_sources<P: AsRef<Path>>(&self, path: P) -> Vec<&str> {
+        let path = path.as_ref();
+        let mut matching_indices = self.matching_indices(path, MatchAs::Dir);
+        matching_indices.extend(self.matching_indices(path, MatchAs::File));
+        matching_indices.sort_unstable();
+        matching_indices.dedup();
+        matching_indices
+            .into_iter()
+            .map(|ix| self.sources[ix].as_str())
+            .collect()
+    }
+
+    fn matching_indices(&self, path: &Path, match_as: MatchAs) -> Vec<usize> {
+        let mut matching_indices = self.glob.matches(path);
+        if match_as == MatchAs::Dir {
+            let as_bytes = path.as_os_str().as_encoded_bytes();
+            matching_indices.extend(self.matches_with_end_separator(path));
+            matching_indices.extend(self.sources.iter().enumerate().filter_map(|(ix, source)| {
+                (!self.negated[ix] && self.starts_or_ends_with(as_bytes, source.as_bytes()))
+                    .then_some(ix)
+            }));
+        }
+        matching_indices
+    }
+
+    fn starts_or_ends_with(&self, path: &[u8], source: &[u8]) -> bool {
+        if !self.options.case_insensitive {
+            return path.starts_with(source) || path.ends_with(source);
+        }
+        path.len() >= source.len()
+            && (path[..source.len()].eq_ignore_ascii_case(source)
+                || path[path.len() - source.len()..].eq_ignore_ascii_case(source))
     }
 
     fn check_with_end_separator(&self, path: &Path) -> bool {
@@ -644,6 +998,22 @@ impl PathMatcher {
             self.glob.is_match(path_str.to_string() + separator)
         }
     }
+
+    fn matches_with_end_separator(&self, path: &Path) -> Vec<usize> {
+        let path_str = path.to_string_lossy();
+        let separator = self.path_style.separator();
+        if path_str.ends_with(separator) {
+            Vec::new()
+        } else {
+            self.glob.matches(path_str.to_string() + separator)
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MatchAs {
+    Dir,
+    File,
 }
 
 impl Default for PathMatcher {
@@ -652,6 +1022,9 @@ impl Default for PathMatcher {
             path_style: PathStyle::local(),
             glob: GlobSet::empty(),
             sources: vec![],
+            options: PathMatcherOptions::default(),
+            negated: vec![],
+            match_all: false,
         }
     }
 }
@@ -1049,7 +1422,10 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("test_file"),
                 row: None,
-                column: None
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: None
             }
         );
 
@@ -1058,7 +1434,10 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("a:bc:.zip"),
                 row: Some(1),
-                column: None
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: None
             }
         );
 
@@ -1067,7 +1446,10 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("one.second.zip"),
                 row: Some(1),
-                column: None
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: None
             }
         );
 
@@ -1077,7 +1459,10 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("test_file"),
                 row: Some(10),
-                column: Some(1)
+                column: Some(1),
+                end_row: None,
+                end_column: None,
+                offset: None
             }
         );
 
@@ -1086,7 +1471,10 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("test_file.rs"),
                 row: None,
-                column: None
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: None
             }
         );
 
@@ -1095,7 +1483,10 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("test_file.rs"),
                 row: Some(1),
-                column: None
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: None
             }
         );
 
@@ -1104,7 +1495,10 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("ab\ncd"),
                 row: None,
-                column: None
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: None
             }
         );
 
@@ -1113,7 +1507,10 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("👋\nab"),
                 row: None,
-                column: None
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: None
             }
         );
 
@@ -1123,6 +1520,9 @@ mod tests {
                 path: PathBuf::from("Types.hs"),
                 row: Some(617),
                 column: Some(9),
+                end_row: None,
+                end_column: None,
+                offset: None,
             }
         );
     }
@@ -1136,6 +1536,9 @@ mod tests {
                 path: PathBuf::from("foo/bar"),
                 row: Some(34),
                 column: None,
+                end_row: None,
+                end_column: None,
+                offset: None,
             }
         );
         assert_eq!(
@@ -1143,7 +1546,10 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("foo/bar.rs:1902"),
                 row: Some(15),
-                column: None
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: None
             }
         );
 
@@ -1153,6 +1559,9 @@ mod tests {
                 path: PathBuf::from("app-editors:zed-0.143.6:20240710-201212.log"),
                 row: Some(34),
                 column: None,
+                end_row: None,
+                end_column: None,
+                offset: None,
             }
         );
 
@@ -1162,6 +1571,9 @@ mod tests {
                 path: PathBuf::from("crates/file_finder/src/file_finder.rs"),
                 row: Some(1902),
                 column: Some(13),
+                end_row: None,
+                end_column: None,
+                offset: None,
             }
         );
 
@@ -1171,6 +1583,9 @@ mod tests {
                 path: PathBuf::from("crate/utils/src/test:today.log"),
                 row: Some(34),
                 column: None,
+                end_row: None,
+                end_column: None,
+                offset: None,
             }
         );
         assert_eq!(
@@ -1179,6 +1594,158 @@ mod tests {
                 path: PathBuf::from("/testing/out/src/file_finder.odin"),
                 row: Some(7),
                 column: Some(15),
+                end_row: None,
+                end_column: None,
+                offset: None,
+            }
+        );
+    }
+
+    #[perf]
+    fn path_with_position_parse_range() {
+        let range = PathWithPosition::parse_str("file.rs:10:5-12:3");
+        assert_eq!(
+            range,
+            PathWithPosition {
+                path: PathBuf::from("file.rs"),
+                row: Some(10),
+                column: Some(5),
+                end_row: Some(12),
+                end_column: Some(3),
+                offset: None,
+            }
+        );
+        assert_eq!(
+            range.to_string(|path| path.to_string_lossy().into_owned()),
+            "file.rs:10:5-12:3"
+        );
+
+        assert_eq!(
+            PathWithPosition::parse_str("/root/dir/file.rs:1:1-1:20"),
+            PathWithPosition {
+                path: PathBuf::from("/root/dir/file.rs"),
+                row: Some(1),
+                column: Some(1),
+                end_row: Some(1),
+                end_column: Some(20),
+                offset: None,
+            }
+        );
+
+        // Malformed ranges fall back to the single-position parsing.
+        assert_eq!(
+            PathWithPosition::parse_str("file.rs:10:5-"),
+            PathWithPosition {
+                path: PathBuf::from("file.rs"),
+                row: Some(10),
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: None,
+            }
+        );
+        assert_eq!(
+            PathWithPosition::parse_str("file.rs:10-12:3"),
+            PathWithPosition {
+                path: PathBuf::from("file.rs:10-12"),
+                row: Some(3),
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: None,
+            }
+        );
+        assert_eq!(
+            PathWithPosition::parse_str("file.rs:10:5")
+                .to_string(|path| path.to_string_lossy().into_owned()),
+            "file.rs:10:5"
+        );
+    }
+
+    #[perf]
+    fn path_with_position_line_base() {
+        let one_based = PathWithPosition::parse_str_with_base("file:0", LineBase::One);
+        assert_eq!(one_based, PathWithPosition::parse_str("file:0"));
+        assert_eq!(one_based.row_one_based(), Some(0));
+        assert_eq!(one_based.row_zero_based(), Some(0));
+
+        let zero_based = PathWithPosition::parse_str_with_base("file:0", LineBase::Zero);
+        assert_eq!(zero_based.path, PathBuf::from("file"));
+        assert_eq!(zero_based.row_one_based(), Some(1));
+        assert_eq!(zero_based.row_zero_based(), Some(0));
+        assert_eq!(zero_based.column_zero_based(), None);
+
+        let zero_based = PathWithPosition::parse_str_with_base("file.rs:4:0", LineBase::Zero);
+        assert_eq!(zero_based.row_one_based(), Some(5));
+        assert_eq!(zero_based.column_one_based(), Some(1));
+        assert_eq!(zero_based.column_zero_based(), Some(0));
+
+        let one_based = PathWithPosition::parse_str_with_base("file.rs:4:7", LineBase::One);
+        assert_eq!(one_based.row_zero_based(), Some(3));
+        assert_eq!(one_based.column_zero_based(), Some(6));
+
+        let unpositioned = PathWithPosition::parse_str_with_base("file.rs", LineBase::Zero);
+        assert_eq!(unpositioned.row_zero_based(), None);
+        assert_eq!(unpositioned.row_one_based(), None);
+    }
+
+    #[perf]
+    fn path_with_position_parse_pasted_artifacts() {
+        assert_eq!(
+            PathWithPosition::parse_str("./src/main.rs :10:2"),
+            PathWithPosition {
+                path: PathBuf::from("./src/main.rs"),
+                row: Some(10),
+                column: Some(2),
+                end_row: None,
+                end_column: None,
+                offset: None,
+            }
+        );
+        assert_eq!(
+            PathWithPosition::parse_str("src/main.rs.:10"),
+            PathWithPosition {
+                path: PathBuf::from("src/main.rs"),
+                row: Some(10),
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: None,
+            }
+        );
+
+        // Only a single artifact is dropped, and only in front of a position.
+        assert_eq!(
+            PathWithPosition::parse_str("src/main.rs  :10"),
+            PathWithPosition {
+                path: PathBuf::from("src/main.rs "),
+                row: Some(10),
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: None,
+            }
+        );
+        assert_eq!(
+            PathWithPosition::parse_str("src/..:10"),
+            PathWithPosition {
+                path: PathBuf::from("src/.."),
+                row: Some(10),
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: None,
+            }
+        );
+        assert_eq!(
+            PathWithPosition::parse_str("src/main.rs."),
+            PathWithPosition {
+                path: PathBuf::from("src/main.rs."),
+                row: None,
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: None,
             }
         );
     }
@@ -1191,7 +1758,10 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("crates\\utils\\paths.rs"),
                 row: None,
-                column: None
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: None
             }
         );
 
@@ -1200,7 +1770,10 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("C:\\Users\\someone\\test_file.rs"),
                 row: None,
-                column: None
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: None
             }
         );
     }
@@ -1213,7 +1786,10 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("crates\\utils\\paths.rs"),
                 row: Some(101),
-                column: None
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: None
             }
         );
 
@@ -1222,7 +1798,10 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("\\\\?\\C:\\Users\\someone\\test_file.rs"),
                 row: Some(1),
-                column: Some(20)
+                column: Some(20),
+                end_row: None,
+                end_column: None,
+                offset: None
             }
         );
 
@@ -1231,7 +1810,10 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("C:\\Users\\someone\\test_file.rs"),
                 row: Some(1902),
-                column: Some(13)
+                column: Some(13),
+                end_row: None,
+                end_column: None,
+                offset: None
             }
         );
 
@@ -1241,7 +1823,10 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("\\\\?\\C:\\Users\\someone\\test_file.rs"),
                 row: Some(1902),
-                column: Some(13)
+                column: Some(13),
+                end_row: None,
+                end_column: None,
+                offset: None
             }
         );
 
@@ -1250,7 +1835,10 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("\\\\?\\C:\\Users\\someone\\test_file.rs:1902"),
                 row: Some(13),
-                column: Some(15)
+                column: Some(15),
+                end_row: None,
+                end_column: None,
+                offset: None
             }
         );
 
@@ -1259,7 +1847,10 @@ mod tests {
             PathWithPosition {
                 path: PathBuf::from("\\\\?\\C:\\Users\\someone\\test_file.rs:1902"),
                 row: Some(15),
-                column: None
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: None
             }
         );
 
@@ -1269,6 +1860,9 @@ mod tests {
                 path: PathBuf::from("\\\\?\\C:\\Users\\someone\\test_file.rs"),
                 row: Some(1902),
                 column: Some(13),
+                end_row: None,
+                end_column: None,
+                offset: None,
             }
         );
 
@@ -1278,6 +1872,9 @@ mod tests {
                 path: PathBuf::from("\\\\?\\C:\\Users\\someone\\test_file.rs"),
                 row: Some(1902),
                 column: None,
+                end_row: None,
+                end_column: None,
+                offset: None,
             }
         );
 
@@ -1287,6 +1884,9 @@ mod tests {
                 path: PathBuf::from("C:\\Users\\someone\\test_file.rs"),
                 row: Some(1902),
                 column: Some(13),
+                end_row: None,
+                end_column: None,
+                offset: None,
             }
         );
 
@@ -1296,6 +1896,9 @@ mod tests {
                 path: PathBuf::from("C:\\Users\\someone\\test_file.rs"),
                 row: Some(1902),
                 column: Some(13),
+                end_row: None,
+                end_column: None,
+                offset: None,
             }
         );
 
@@ -1305,6 +1908,9 @@ mod tests {
                 path: PathBuf::from("C:\\Users\\someone\\test_file.rs"),
                 row: Some(1902),
                 column: None,
+                end_row: None,
+                end_column: None,
+                offset: None,
             }
         );
 
@@ -1314,10 +1920,145 @@ mod tests {
                 path: PathBuf::from("crates\\utils\\paths.rs"),
                 row: Some(101),
                 column: None,
+                end_row: None,
+                end_column: None,
+                offset: None,
             }
         );
     }
 
+    #[perf]
+    fn path_with_position_parse_byte_offset() {
+        assert_eq!(
+            PathWithPosition::parse_str("file.rs@1234"),
+            PathWithPosition {
+                path: PathBuf::from("file.rs"),
+                row: None,
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: Some(1234),
+            }
+        );
+        assert_eq!(
+            PathWithPosition::parse_str("crates/util/src/paths.rs@0"),
+            PathWithPosition {
+                path: PathBuf::from("crates/util/src/paths.rs"),
+                row: None,
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: Some(0),
+            }
+        );
+        assert_eq!(
+            PathWithPosition::parse_str("file.rs@1234")
+                .to_string(|path| path.to_string_lossy().into_owned()),
+            "file.rs@1234"
+        );
+
+        for input in ["file.rs@abc", "file.rs@", "file.rs@+12", "@1234"] {
+            assert_eq!(
+                PathWithPosition::parse_str(input),
+                PathWithPosition::from_path(PathBuf::from(input)),
+                "{input:?} has no valid offset"
+            );
+        }
+        assert_eq!(
+            PathWithPosition::parse_str("file@2x.png:10"),
+            PathWithPosition {
+                path: PathBuf::from("file@2x.png"),
+                row: Some(10),
+                column: None,
+                end_row: None,
+                end_column: None,
+                offset: None,
+            },
+            "row and column parsing is unaffected by an `@` elsewhere in the name"
+        );
+    }
+
+    #[perf]
+    #[cfg(not(target_os = "windows"))]
+    fn path_with_position_to_string_round_trips_posix_path() {
+        for input in [
+            "foo/bar.rs",
+            "foo/bar.rs:34",
+            "foo/bar.rs:34:56",
+            "foo/bar.rs:34:56-78:9",
+            "foo/bar.rs(1902,13)",
+            "foo/bar.rs:1902:13:15:",
+            "foo/bar.rs:1902:::15:",
+            "foo/bar.rs@1234",
+            "foo/notes@2024:",
+            "foo/notes@2024:3",
+            "foo/notes@2024@5",
+        ] {
+            let parsed = PathWithPosition::parse_str(input);
+            let formatted = parsed.to_string(|path| path.to_string_lossy().into_owned());
+            assert_eq!(
+                PathWithPosition::parse_str(&formatted),
+                parsed,
+                "{input:?} was formatted as {formatted:?}"
+            );
+        }
+    }
+
+    #[perf]
+    fn path_with_position_to_string_round_trips_literal_offset_path() {
+        for position in [
+            PathWithPosition::from_path(PathBuf::from("notes@2024")),
+            PathWithPosition {
+                row: Some(3),
+                ..PathWithPosition::from_path(PathBuf::from("notes@2024"))
+            },
+            PathWithPosition {
+                offset: Some(5),
+                ..PathWithPosition::from_path(PathBuf::from("notes@2024"))
+            },
+        ] {
+            let formatted = position.to_string(|path| path.to_string_lossy().into_owned());
+            assert_eq!(
+                PathWithPosition::parse_str(&formatted),
+                position,
+                "{position:?} was formatted as {formatted:?}"
+            );
+        }
+        assert_eq!(
+            PathWithPosition::from_path(PathBuf::from("notes@2024"))
+                .to_string(|path| path.to_string_lossy().into_owned()),
+            "notes@2024:"
+        );
+    }
+
+    #[perf]
+    #[cfg(target_os = "windows")]
+    fn path_with_position_to_string_round_trips_windows_path() {
+        for input in [
+            "crates\\utils\\paths.rs",
+            "C:\\Users\\someone\\test_file.rs",
+            "crates\\utils\\paths.rs:101",
+            "\\\\?\\C:\\Users\\someone\\test_file.rs:1:20",
+            "C:\\Users\\someone\\test_file.rs(1902,13)",
+            "\\\\?\\C:\\Users\\someone\\test_file.rs:1902:13:",
+            "\\\\?\\C:\\Users\\someone\\test_file.rs:1902:13:15:",
+            "\\\\?\\C:\\Users\\someone\\test_file.rs:1902:::15:",
+            "\\\\?\\C:\\Users\\someone\\test_file.rs(1902,13):",
+            "\\\\?\\C:\\Users\\someone\\test_file.rs(1902):",
+            "C:\\Users\\someone\\test_file.rs:1902:13:",
+            "C:\\Users\\someone\\test_file.rs(1902,13):",
+            "\\\\?\\C:\\Users\\someone\\test_file.rs:1:2-3:4",
+        ] {
+            let parsed = PathWithPosition::parse_str(input);
+            let formatted = parsed.to_string(|path| path.to_string_lossy().into_owned());
+            assert_eq!(
+                PathWithPosition::parse_str(&formatted),
+                parsed,
+                "{input:?} was formatted as {formatted:?}"
+            );
+        }
+    }
+
     #[perf]
     fn test_path_compact() {
         let path: PathBuf = [
@@ -1328,11 +2069,51 @@ mod tests {
         .collect();
         if cfg!(any(target_os = "linux", target_os = "freebsd")) || cfg!(target_os = "macos") {
             assert_eq!(path.compact().to_str(), Some("~/some_file.txt"));
+        } else if cfg!(target_os = "windows") {
+            assert_eq!(path.compact().to_str(), Some("~\\some_file.txt"));
         } else {
             assert_eq!(path.compact().to_str(), path.to_str());
         }
     }
 
+    #[perf]
+    fn test_path_relative_to() {
+        let base = home_dir().join("projects").join("zed");
+
+        let nested_path = base.join("crates").join("util");
+        assert_eq!(
+            nested_path.relative_to(&base),
+            Some(Path::new("crates").join("util"))
+        );
+        assert_eq!(
+            nested_path.compact_relative(&base),
+            Path::new("crates").join("util")
+        );
+
+        let sibling_path = home_dir().join("projects").join("zed-extensions");
+        assert_eq!(sibling_path.relative_to(&base), None);
+        assert_eq!(sibling_path.compact_relative(&base), sibling_path.compact());
+
+        assert_eq!(base.relative_to(&base), Some(PathBuf::from(".")));
+        assert_eq!(base.compact_relative(&base), PathBuf::from("."));
+    }
+
+    #[perf]
+    #[cfg(target_os = "windows")]
+    fn test_path_compact_windows() {
+        let path = home_dir().join("some_dir").join("some_file.txt");
+        assert_eq!(path.compact().to_str(), Some("~\\some_dir\\some_file.txt"));
+
+        let verbatim_path = PathBuf::from(format!("\\\\?\\{}", path.display()));
+        assert_eq!(
+            verbatim_path.compact().to_str(),
+            Some("~\\some_dir\\some_file.txt")
+        );
+
+        let path_outside_home = PathBuf::from("D:\\work\\some_file.txt");
+        assert_eq!(path_outside_home.compact(), path_outside_home);
+    }
+
     #[perf]
     fn test_extension_or_hidden_file_name() {
         // No dots in name
@@ -1378,6 +2159,150 @@ mod tests {
         );
     }
 
+    #[perf]
+    fn path_matcher_case_insensitive() {
+        let globs = ["**/NODE_MODULES/**"];
+        let path = Path::new("/work/node_modules/react/index.js");
+
+        let case_sensitive = PathMatcher::new(globs, PathStyle::Posix).unwrap();
+        assert!(!case_sensitive.is_match(path));
+        assert!(case_sensitive.is_match("/work/NODE_MODULES/react/index.js"));
+
+        let case_insensitive = PathMatcher::new_with_options(
+            globs,
+            PathStyle::Posix,
+            PathMatcherOptions {
+                case_insensitive: true,
+            },
+        )
+        .unwrap();
+        assert!(case_insensitive.is_match(path));
+        assert!(case_insensitive.is_match("/work/NODE_MODULES/react/index.js"));
+        assert!(case_insensitive.is_match("/work/node_modules"));
+
+        let literal = PathMatcher::new_with_options(
+            ["/work/NODE_MODULES"],
+            PathStyle::Posix,
+            PathMatcherOptions {
+                case_insensitive: true,
+            },
+        )
+        .unwrap();
+        assert!(literal.is_match("/work/node_modules/react"));
+        assert!(!literal.is_match("/work/src"));
+    }
+
+    #[perf]
+    fn path_matcher_file_vs_dir() {
+        let path_matcher = PathMatcher::new(["**/src/**"], PathStyle::Posix).unwrap();
+        let path = Path::new("/work/src");
+        assert!(path_matcher.is_match_dir(path));
+        assert!(!path_matcher.is_match_file(path));
+        assert!(path_matcher.is_match(path));
+
+        assert!(path_matcher.is_match_file("/work/src/main.rs"));
+        assert!(path_matcher.is_match_dir("/work/src/bin"));
+        assert!(!path_matcher.is_match_file("/work/source"));
+        assert!(!path_matcher.is_match_dir("/work/source"));
+
+        let literal = PathMatcher::new(["src"], PathStyle::Posix).unwrap();
+        assert!(literal.is_match_dir("/work/src"));
+        assert!(
+            !literal.is_match_file("/work/src"),
+            "a file isn't matched by a literal suffix"
+        );
+        assert!(literal.is_match("/work/src"));
+        assert!(literal.is_match_file("src"));
+    }
+
+    #[perf]
+    fn path_matcher_matching_sources() {
+        let path_matcher =
+            PathMatcher::new(["**/*.rs", "**/target/**", "src/**"], PathStyle::Posix).unwrap();
+        assert_eq!(
+            path_matcher.matching_sources("src/main.rs"),
+            ["**/*.rs", "src/**"]
+        );
+        assert_eq!(
+            path_matcher.matching_sources("/work/target"),
+            ["**/target/**"]
+        );
+        assert!(path_matcher.matching_sources("README.md").is_empty());
+
+        let path_matcher = PathMatcher::new(["**/*.log", "!keep/*.log"], PathStyle::Posix).unwrap();
+        assert_eq!(
+            path_matcher.matching_sources("keep/important.log"),
+            ["**/*.log", "!keep/*.log"]
+        );
+    }
+
+    #[perf]
+    fn path_matcher_negation() {
+        let path_matcher = PathMatcher::new(["**/*.log", "!keep/*.log"], PathStyle::Posix).unwrap();
+        assert_eq!(path_matcher.sources(), ["**/*.log", "!keep/*.log"]);
+
+        assert!(path_matcher.is_match("debug.log"));
+        assert!(path_matcher.is_match("logs/debug.log"));
+        assert!(!path_matcher.is_match("keep/important.log"));
+        assert!(path_matcher.is_match("nested/keep/important.log"));
+        assert!(!path_matcher.is_match("keep/notes.txt"));
+        assert!(!path_matcher.is_match("src/main.rs"));
+
+        let path_matcher = PathMatcher::new(
+            ["**/*.log", "!keep/*.log", "keep/debug.log"],
+            PathStyle::Posix,
+        )
+        .unwrap();
+        assert!(!path_matcher.is_match("keep/important.log"));
+        assert!(
+            path_matcher.is_match("keep/debug.log"),
+            "a later positive source should match the path again"
+        );
+
+        let path_matcher = PathMatcher::new(["!**/*.rs"], PathStyle::Posix).unwrap();
+        assert!(!path_matcher.is_match("src/main.rs"));
+        assert!(!path_matcher.is_match("README.md"));
+    }
+
+    #[perf]
+    fn path_matcher_empty_and_match_all() {
+        let paths = ["", "README.md", "src/main.rs", "/abs/path/file.txt"];
+
+        let empty = PathMatcher::new(Vec::<&str>::new(), PathStyle::Posix).unwrap();
+        for matcher in [&empty, &PathMatcher::default()] {
+            for path in paths {
+                assert!(!matcher.is_match(path), "empty matcher matched {path:?}");
+                assert!(!matcher.is_match_file(path));
+            }
+        }
+
+        let match_all = PathMatcher::match_all(PathStyle::Posix);
+        assert!(match_all.sources().is_empty());
+        for path in paths {
+            assert!(match_all.is_match(path), "match_all missed {path:?}");
+            assert!(match_all.is_match_dir(path));
+            assert!(match_all.is_match_file(path));
+        }
+        assert_ne!(match_all, empty);
+    }
+
+    #[perf]
+    fn path_matcher_matches_any() {
+        let matchers = [
+            PathMatcher::new(["**/*.rs"], PathStyle::Posix).unwrap(),
+            PathMatcher::new(["docs/**"], PathStyle::Posix).unwrap(),
+        ];
+        let path = Path::new("docs/README.md");
+        assert!(!matchers[0].is_match(path));
+        assert!(PathMatcher::matches_any(&matchers, path));
+        assert!(!PathMatcher::matches_none(&matchers, path));
+
+        let path = Path::new("assets/logo.svg");
+        assert!(!PathMatcher::matches_any(&matchers, path));
+        assert!(PathMatcher::matches_none(&matchers, path));
+        assert!(!PathMatcher::matches_any(&[], path));
+    }
+
     #[perf]
     #[cfg(target_os = "windows")]
     fn test_sanitized_path() {
@@ -28,12 +28,32 @@ impl WorktreeSettings {
             .any(|ancestor| self.file_scan_exclusions.is_match(ancestor.as_std_path()))
     }
 
-    pub fn is_path_always_included(&self, path: &RelPath) -> bool {
-        path.ancestors()
-            .any(|ancestor| self.file_scan_inclusions.is_match(ancestor.as_std_path()))
+    pub fn is_path_always_included(&self, path: &RelPath, is_dir: bool) -> bool {
+        is_path_always_included(&self.file_scan_inclusions, path, is_dir)
     }
 }
 
+/// Whether `path`, or one of the directories containing it, matches `file_scan_inclusions`.
+/// Used both when scanning locally and for entries received from the host, so that
+/// both sides agree on which entries are always included.
+pub(crate) fn is_path_always_included(
+    file_scan_inclusions: &PathMatcher,
+    path: &RelPath,
+    is_dir: bool,
+) -> bool {
+    let mut ancestors = path.ancestors();
+    let Some(path) = ancestors.next() else {
+        return false;
+    };
+    let path_matches = if is_dir {
+        file_scan_inclusions.is_match_dir(path.as_std_path())
+    } else {
+        file_scan_inclusions.is_match_file(path.as_std_path())
+    };
+    path_matches
+        || ancestors.any(|ancestor| file_scan_inclusions.is_match_dir(ancestor.as_std_path()))
+}
+
 impl Settings for WorktreeSettings {
     fn from_settings(content: &settings::SettingsContent, _cx: &mut App) -> Self {
         let worktree = content.project.worktree.clone();
@@ -14,7 +14,7 @@ use futures::future::join_all;
 use futures::{FutureExt, SinkExt, StreamExt};
 use git_ui::file_diff_view::FileDiffView;
 use gpui::{App, AsyncApp, Global, WindowHandle};
-use language::Point;
+use language::{Bias, Point};
 use onboarding::FIRST_OPEN;
 use onboarding::show_onboarding_view;
 use recent_projects::{SshSettings, open_remote_project};
@@ -247,6 +247,12 @@ fn connect_to_cli(
     Ok((async_request_rx, response_tx))
 }
 
+/// Where to put the caret in a file opened from a [`PathWithPosition`].
+enum CaretPosition {
+    Point(Point),
+    Offset(usize),
+}
+
 pub async fn open_paths_with_positions(
     path_positions: &[PathWithPosition],
     diff_paths: &[[String; 2]],
@@ -263,12 +269,14 @@ pub async fn open_paths_with_positions(
         .iter()
         .map(|path_with_position| {
             let path = path_with_position.path.clone();
-            if let Some(row) = path_with_position.row
-                && path.is_file()
-            {
-                let row = row.saturating_sub(1);
-                let col = path_with_position.column.unwrap_or(0).saturating_sub(1);
-                caret_positions.insert(path.clone(), Point::new(row, col));
+            if path.is_file() {
+                if let Some(row) = path_with_position.row_zero_based() {
+                    let col = path_with_position.column_zero_based().unwrap_or(0);
+                    caret_positions
+                        .insert(path.clone(), CaretPosition::Point(Point::new(row, col)));
+                } else if let Some(offset) = path_with_position.offset {
+                    caret_positions.insert(path.clone(), CaretPosition::Offset(offset));
+                }
             }
             path
         })
@@ -297,13 +305,25 @@ pub async fn open_paths_with_positions(
         let Some(Ok(item)) = item else {
             continue;
         };
-        let Some(point) = caret_positions.remove(path) else {
+        let Some(caret_position) = caret_positions.remove(path) else {
             continue;
         };
         if let Some(active_editor) = item.downcast::<Editor>() {
             workspace
                 .update(cx, |_, window, cx| {
                     active_editor.update(cx, |editor, cx| {
+                        let point = match caret_position {
+                            CaretPosition::Point(point) => point,
+                            CaretPosition::Offset(offset) => {
+                                let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+                                    return;
+                                };
+                                let snapshot = buffer.read(cx).snapshot();
+                                let offset =
+                                    snapshot.clip_offset(offset.min(snapshot.len()), Bias::Left);
+                                snapshot.offset_to_point(offset)
+                            }
+                        };
                         editor.go_to_singleton_buffer_point(point, window, cx);
                     });
                 })
@@ -592,16 +612,19 @@ pub async fn derive_paths_with_position(
     path_strings: impl IntoIterator<Item = impl AsRef<str>>,
 ) -> Vec<PathWithPosition> {
     join_all(path_strings.into_iter().map(|path_str| async move {
-        let canonicalized = fs.canonicalize(Path::new(path_str.as_ref())).await;
-        (path_str, canonicalized)
+        let path_str = path_str.as_ref();
+        if let Ok(canonicalized) = fs.canonicalize(Path::new(path_str)).await {
+            return PathWithPosition::from_path(canonicalized);
+        }
+        let parsed = PathWithPosition::parse_str(path_str);
+        // A name like `notes@2024` is only an offset into `notes` when that file exists,
+        // otherwise it's the name of a new file.
+        if parsed.offset.is_some() && !fs.is_file(&parsed.path).await {
+            return PathWithPosition::from_path(PathBuf::from(path_str));
+        }
+        parsed
     }))
     .await
-    .into_iter()
-    .map(|(original, canonicalized)| match canonicalized {
-        Ok(canonicalized) => PathWithPosition::from_path(canonicalized),
-        Err(_) => PathWithPosition::parse_str(original.as_ref()),
-    })
-    .collect()
 }
 
 #[cfg(test)]
@@ -763,6 +786,32 @@ mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    async fn test_derive_paths_with_offset(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(path!("/root"), json!({ "notes": "some notes" }))
+            .await;
+
+        let paths = derive_paths_with_position(
+            app_state.fs.as_ref(),
+            [path!("/root/notes@4"), path!("/root/todo@2024")],
+        )
+        .await;
+        assert_eq!(
+            paths,
+            [
+                PathWithPosition {
+                    offset: Some(4),
+                    ..PathWithPosition::from_path(PathBuf::from(path!("/root/notes")))
+                },
+                PathWithPosition::from_path(PathBuf::from(path!("/root/todo@2024"))),
+            ]
+        );
+    }
+
     async fn open_workspace_file(
         path: &str,
         open_new_workspace: Option<bool>,
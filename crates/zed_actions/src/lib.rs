@@ -187,6 +187,18 @@ pub mod git {
     );
 }
 
+pub mod git_panel {
+    use gpui::actions;
+
+    actions!(
+        git_panel,
+        [
+            /// Toggles focus on the git panel.
+            ToggleFocus
+        ]
+    );
+}
+
 pub mod toast {
     use gpui::actions;
 
@@ -1,16 +1,48 @@
-use collections::HashMap;
+use collections::{FxHasher, HashMap};
+use std::cell::RefCell;
 use std::collections::VecDeque;
+use std::hash::{Hash as _, Hasher as _};
 use std::sync::{
-    OnceLock, RwLock,
-    atomic::{AtomicU8, Ordering},
+    Mutex, RwLock,
+    atomic::{AtomicU8, AtomicU64, Ordering},
 };
 
 use crate::{SCOPE_DEPTH_MAX, SCOPE_STRING_SEP_STR, Scope, ScopeAlloc, env_config, private};
 
 use log;
 
-static ENV_FILTER: OnceLock<env_config::EnvFilter> = OnceLock::new();
+/// A scope segment that matches any single segment at the same depth, e.g. `db.*`.
+pub(crate) const SCOPE_WILDCARD: &str = "*";
+
+static ENV_FILTER: RwLock<Option<env_config::EnvFilter>> = RwLock::new(None);
 static SCOPE_MAP: RwLock<Option<ScopeMap>> = RwLock::new(None);
+/// The settings last passed to [`refresh_from_settings`], kept so that the
+/// scope map can be rebuilt when a level is changed at runtime.
+static SCOPE_SETTINGS: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+/// Levels set with [`set_scope_level`]. These take precedence over settings,
+/// the environment and [`DEFAULT_FILTERS`].
+static SCOPE_RUNTIME_LEVELS: Mutex<Vec<(String, log::LevelFilter)>> = Mutex::new(Vec::new());
+/// Incremented every time a new map is installed in `SCOPE_MAP`, which
+/// invalidates the statuses cached in `ENABLED_STATUS_CACHE`.
+static SCOPE_MAP_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// The number of scopes a thread caches before starting over, bounding the
+/// memory used by threads that log from many distinct modules.
+const ENABLED_STATUS_CACHE_CAPACITY: usize = 256;
+
+thread_local! {
+    /// Statuses computed by [`ScopeMap::is_enabled`], so that chatty scopes skip
+    /// the `SCOPE_MAP` lock and the tree walk. Each thread keeps its own cache,
+    /// so the only shared state it depends on is `SCOPE_MAP_GENERATION`.
+    static ENABLED_STATUS_CACHE: RefCell<EnabledStatusCache> =
+        RefCell::new(EnabledStatusCache::default());
+}
+
+#[cfg(test)]
+thread_local! {
+    /// The number of times this thread walked the scope map in [`is_scope_enabled`].
+    static SCOPE_MAP_LOOKUPS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
 
 pub const LEVEL_ENABLED_MAX_DEFAULT: log::LevelFilter = log::LevelFilter::Info;
 /// The maximum log level of verbosity that is enabled by default.
@@ -44,12 +76,36 @@ const DEFAULT_FILTERS: &[(&str, log::LevelFilter)] = &[
 ];
 
 pub fn init_env_filter(filter: env_config::EnvFilter) {
+    let mut env_filter = ENV_FILTER.write().unwrap_or_else(|err| {
+        ENV_FILTER.clear_poison();
+        err.into_inner()
+    });
+    if env_filter.is_some() {
+        panic!("Environment filter cannot be initialized twice");
+    }
     if let Some(level_max) = filter.level_global {
         LEVEL_ENABLED_MAX_STATIC.store(level_max as u8, Ordering::Release)
     }
-    if ENV_FILTER.set(filter).is_err() {
-        panic!("Environment filter cannot be initialized twice");
+    env_filter.replace(filter);
+}
+
+/// Replaces the environment filter, e.g. after `RUST_LOG` changed in a
+/// long-running session, and rebuilds the scope map so it applies immediately.
+pub fn reload_env_filter(filter: env_config::EnvFilter) {
+    let scope_settings = SCOPE_SETTINGS.lock().unwrap_or_else(|err| {
+        SCOPE_SETTINGS.clear_poison();
+        err.into_inner()
+    });
+    {
+        let mut env_filter = ENV_FILTER.write().unwrap_or_else(|err| {
+            ENV_FILTER.clear_poison();
+            err.into_inner()
+        });
+        let level_max = filter.level_global.unwrap_or(LEVEL_ENABLED_MAX_DEFAULT);
+        LEVEL_ENABLED_MAX_STATIC.store(level_max as u8, Ordering::Release);
+        env_filter.replace(filter);
     }
+    rebuild_scope_map(&scope_settings.clone().unwrap_or_default());
 }
 
 pub fn is_possibly_enabled_level(level: log::Level) -> bool {
@@ -66,38 +122,266 @@ pub fn is_scope_enabled(scope: &Scope, module_path: Option<&str>, level: log::Le
         return false;
     }
     let is_enabled_by_default = level as u8 <= LEVEL_ENABLED_MAX_STATIC.load(Ordering::Acquire);
+    match enabled_status(scope, module_path, level) {
+        EnabledStatus::NotConfigured => is_enabled_by_default,
+        EnabledStatus::Enabled => true,
+        EnabledStatus::Disabled => false,
+    }
+}
+
+fn enabled_status(scope: &Scope, module_path: Option<&str>, level: log::Level) -> EnabledStatus {
+    let key = EnabledStatusCache::key(scope, module_path);
+    let generation = SCOPE_MAP_GENERATION.load(Ordering::Acquire);
+    // `try_with` because threads may still log while their locals are being destroyed.
+    let cached_status = ENABLED_STATUS_CACHE
+        .try_with(|cache| {
+            cache
+                .borrow_mut()
+                .get(generation, key, scope, module_path, level)
+        })
+        .ok()
+        .flatten();
+    if let Some(status) = cached_status {
+        return status;
+    }
+
     let global_scope_map = SCOPE_MAP.read().unwrap_or_else(|err| {
         SCOPE_MAP.clear_poison();
         err.into_inner()
     });
-
-    let Some(map) = global_scope_map.as_ref() else {
-        // on failure, return false because it's not <= LEVEL_ENABLED_MAX_STATIC
-        return is_enabled_by_default;
+    // Read while holding the lock, so the status is cached under the
+    // generation of the map it was computed from.
+    let generation = SCOPE_MAP_GENERATION.load(Ordering::Acquire);
+    let status = match global_scope_map.as_ref() {
+        Some(map) if !map.is_empty() => {
+            #[cfg(test)]
+            SCOPE_MAP_LOOKUPS.set(SCOPE_MAP_LOOKUPS.get() + 1);
+            map.is_enabled(scope, module_path, level)
+        }
+        // With no map, or no configured scopes, only the default level applies.
+        _ => EnabledStatus::NotConfigured,
     };
+    drop(global_scope_map);
+
+    ENABLED_STATUS_CACHE
+        .try_with(|cache| {
+            cache
+                .borrow_mut()
+                .insert(generation, key, scope, module_path, level, status)
+        })
+        .ok();
+    status
+}
+
+#[derive(Default)]
+struct EnabledStatusCache {
+    generation: u64,
+    entries: HashMap<u64, CachedEnabledStatus>,
+}
+
+struct CachedEnabledStatus {
+    scope: Scope,
+    module_path: Option<String>,
+    /// Indexed by `log::Level as usize - 1`.
+    statuses: [Option<EnabledStatus>; 5],
+}
 
-    if map.is_empty() {
-        // if no scopes are enabled, return false because it's not <= LEVEL_ENABLED_MAX_STATIC
-        return is_enabled_by_default;
+impl EnabledStatusCache {
+    fn key(scope: &Scope, module_path: Option<&str>) -> u64 {
+        let mut hasher = FxHasher::default();
+        scope.hash(&mut hasher);
+        module_path.hash(&mut hasher);
+        hasher.finish()
     }
-    let enabled_status = map.is_enabled(scope, module_path, level);
-    match enabled_status {
-        EnabledStatus::NotConfigured => is_enabled_by_default,
-        EnabledStatus::Enabled => true,
-        EnabledStatus::Disabled => false,
+
+    fn get(
+        &mut self,
+        generation: u64,
+        key: u64,
+        scope: &Scope,
+        module_path: Option<&str>,
+        level: log::Level,
+    ) -> Option<EnabledStatus> {
+        if self.generation != generation {
+            self.entries.clear();
+            self.generation = generation;
+            return None;
+        }
+        let entry = self.entries.get(&key)?;
+        if entry.scope != *scope || entry.module_path.as_deref() != module_path {
+            return None;
+        }
+        entry.statuses[level as usize - 1]
+    }
+
+    fn insert(
+        &mut self,
+        generation: u64,
+        key: u64,
+        scope: &Scope,
+        module_path: Option<&str>,
+        level: log::Level,
+        status: EnabledStatus,
+    ) {
+        if self.generation != generation {
+            self.entries.clear();
+            self.generation = generation;
+        }
+        if self.entries.len() >= ENABLED_STATUS_CACHE_CAPACITY && !self.entries.contains_key(&key) {
+            self.entries.clear();
+        }
+        let entry = self
+            .entries
+            .entry(key)
+            .or_insert_with(|| CachedEnabledStatus {
+                scope: *scope,
+                module_path: module_path.map(str::to_string),
+                statuses: [None; 5],
+            });
+        // On a hash collision, the most recently looked up scope takes the slot.
+        if entry.scope != *scope || entry.module_path.as_deref() != module_path {
+            *entry = CachedEnabledStatus {
+                scope: *scope,
+                module_path: module_path.map(str::to_string),
+                statuses: [None; 5],
+            };
+        }
+        entry.statuses[level as usize - 1] = Some(status);
     }
 }
 
 pub fn refresh_from_settings(settings: &HashMap<String, String>) {
-    let env_config = ENV_FILTER.get();
-    let map_new = ScopeMap::new_from_settings_and_env(settings, env_config, DEFAULT_FILTERS);
+    let mut scope_settings = SCOPE_SETTINGS.lock().unwrap_or_else(|err| {
+        SCOPE_SETTINGS.clear_poison();
+        err.into_inner()
+    });
+    scope_settings.replace(settings.clone());
+    rebuild_scope_map(settings);
+    log::trace!("Log configuration updated");
+}
+
+/// Sets the level of a single scope (e.g. `editor.scroll`) or module path, or
+/// with `None` removes a level previously set this way.
+///
+/// Levels set here take precedence over settings, the environment and the
+/// built-in defaults, and survive later calls to [`refresh_from_settings`].
+/// Removing one restores whatever level those sources give the scope. Only the
+/// given scope is updated in the current map, settings aren't reparsed.
+pub fn set_scope_level(scope: &str, level: Option<log::Level>) {
+    let scope_settings = SCOPE_SETTINGS.lock().unwrap_or_else(|err| {
+        SCOPE_SETTINGS.clear_poison();
+        err.into_inner()
+    });
+    {
+        let mut runtime_levels = SCOPE_RUNTIME_LEVELS.lock().unwrap_or_else(|err| {
+            SCOPE_RUNTIME_LEVELS.clear_poison();
+            err.into_inner()
+        });
+        runtime_levels.retain(|(existing_scope, _)| existing_scope != scope);
+        if let Some(level) = level {
+            runtime_levels.push((scope.to_string(), level.to_level_filter()));
+        }
+    }
+    let level = match level {
+        Some(level) => Some(level.to_level_filter()),
+        None => configured_level(scope, scope_settings.as_ref()),
+    };
+
+    let mut global_map = SCOPE_MAP.write().unwrap_or_else(|err| {
+        SCOPE_MAP.clear_poison();
+        err.into_inner()
+    });
+    let Some(map) = global_map.as_mut() else {
+        drop(global_map);
+        rebuild_scope_map(&scope_settings.clone().unwrap_or_default());
+        return;
+    };
+    map.set_level(scope, level);
+    store_level_enabled_max(map);
+    SCOPE_MAP_GENERATION.fetch_add(1, Ordering::AcqRel);
+}
+
+/// Returns the level `scope_str` is given by [`DEFAULT_FILTERS`], the environment
+/// and `settings`, with later sources taking precedence over earlier ones as in
+/// [`ScopeMap::new_from_settings_and_env`].
+fn configured_level(
+    scope_str: &str,
+    settings: Option<&HashMap<String, String>>,
+) -> Option<log::LevelFilter> {
+    let is_module = scope_str.contains("::");
+    let scope = scope_alloc_from_scope_str(scope_str);
+    let matches = |candidate: &str| {
+        if is_module {
+            candidate == scope_str
+        } else {
+            !candidate.contains("::")
+                && scope.is_some()
+                && scope_alloc_from_scope_str(candidate) == scope
+        }
+    };
+
+    let mut level = DEFAULT_FILTERS
+        .iter()
+        .rev()
+        .find(|(candidate, _)| matches(candidate))
+        .map(|(_, level)| *level);
+    {
+        let env_filter = ENV_FILTER.read().unwrap_or_else(|err| {
+            ENV_FILTER.clear_poison();
+            err.into_inner()
+        });
+        if let Some(env_filter) = env_filter.as_ref() {
+            let env_level = env_filter
+                .directive_names
+                .iter()
+                .zip(env_filter.directive_levels.iter())
+                .rev()
+                .find(|(candidate, _)| matches(candidate))
+                .map(|(_, level)| *level);
+            level = env_level.or(level);
+        }
+    }
+    if let Some(settings) = settings {
+        let settings_level = settings
+            .iter()
+            .filter(|(candidate, _)| matches(candidate))
+            .find_map(|(_, level_str)| level_filter_from_str(level_str));
+        level = settings_level.or(level);
+    }
+    level
+}
+
+fn store_level_enabled_max(map: &ScopeMap) {
     let mut level_enabled_max = LEVEL_ENABLED_MAX_STATIC.load(Ordering::Acquire);
-    for entry in &map_new.entries {
+    for entry in &map.entries {
         if let Some(level) = entry.enabled {
             level_enabled_max = level_enabled_max.max(level as u8);
         }
     }
     LEVEL_ENABLED_MAX_CONFIG.store(level_enabled_max, Ordering::Release);
+}
+
+/// Must be called with `SCOPE_SETTINGS` locked, so that concurrent rebuilds
+/// can't install their maps out of order.
+fn rebuild_scope_map(settings: &HashMap<String, String>) {
+    let mut settings = settings.clone();
+    {
+        let runtime_levels = SCOPE_RUNTIME_LEVELS.lock().unwrap_or_else(|err| {
+            SCOPE_RUNTIME_LEVELS.clear_poison();
+            err.into_inner()
+        });
+        for (scope, level) in runtime_levels.iter() {
+            settings.insert(scope.clone(), level.to_string());
+        }
+    }
+    let env_filter = ENV_FILTER.read().unwrap_or_else(|err| {
+        ENV_FILTER.clear_poison();
+        err.into_inner()
+    });
+    let map_new =
+        ScopeMap::new_from_settings_and_env(&settings, env_filter.as_ref(), DEFAULT_FILTERS);
+    drop(env_filter);
+    store_level_enabled_max(&map_new);
 
     {
         let mut global_map = SCOPE_MAP.write().unwrap_or_else(|err| {
@@ -105,8 +389,20 @@ pub fn refresh_from_settings(settings: &HashMap<String, String>) {
             err.into_inner()
         });
         global_map.replace(map_new);
+        SCOPE_MAP_GENERATION.fetch_add(1, Ordering::AcqRel);
     }
-    log::trace!("Log configuration updated");
+}
+
+/// Returns the scopes and modules currently configured, with their levels, in
+/// the form accepted by [`refresh_from_settings`].
+pub fn current_config() -> Vec<(String, log::LevelFilter)> {
+    let map = SCOPE_MAP.read().unwrap_or_else(|err| {
+        SCOPE_MAP.clear_poison();
+        err.into_inner()
+    });
+    map.as_ref()
+        .map(ScopeMap::configured_scopes)
+        .unwrap_or_default()
 }
 
 fn level_filter_from_str(level_str: &str) -> Option<log::LevelFilter> {
@@ -133,7 +429,7 @@ fn level_filter_from_str(level_str: &str) -> Option<log::LevelFilter> {
     Some(level)
 }
 
-fn scope_alloc_from_scope_str(scope_str: &str) -> Option<ScopeAlloc> {
+pub(crate) fn scope_alloc_from_scope_str(scope_str: &str) -> Option<ScopeAlloc> {
     let mut scope_buf = [""; SCOPE_DEPTH_MAX];
     let mut index = 0;
     let mut scope_iter = scope_str.split(SCOPE_STRING_SEP_STR);
@@ -160,6 +456,12 @@ fn scope_alloc_from_scope_str(scope_str: &str) -> Option<ScopeAlloc> {
     Some(scope)
 }
 
+/// Wildcards sort after literals so that the first matching entry found when
+/// searching is always the most specific one.
+fn scope_sort_key(component: &str) -> (bool, &str) {
+    (component == SCOPE_WILDCARD, component)
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct ScopeMap {
     entries: Vec<ScopeMapEntry>,
@@ -235,7 +537,11 @@ impl ScopeMap {
             }
         }
 
-        items.sort_by(|a, b| a.0.cmp(&b.0));
+        items.sort_by(|(a, _), (b, _)| {
+            a.iter()
+                .map(|component| scope_sort_key(component))
+                .cmp(b.iter().map(|component| scope_sort_key(component)))
+        });
         modules.sort_by(|(a_name, _), (b_name, _)| a_name.cmp(b_name));
 
         let mut this = Self {
@@ -324,6 +630,164 @@ impl ScopeMap {
         self.entries.is_empty() && self.modules.is_empty()
     }
 
+    /// Sets the level of a single scope or module path in place, or with `None`
+    /// removes it, leaving the map configured as if it had been built with the
+    /// same change made to its settings.
+    ///
+    /// Entries are inserted in sorted order among their siblings, and entries left
+    /// with neither a level nor descendants are removed.
+    pub fn set_level(&mut self, scope_str: &str, level: Option<log::LevelFilter>) {
+        if scope_str.contains("::") {
+            let position = self
+                .modules
+                .binary_search_by(|(module, _)| module.as_str().cmp(scope_str));
+            match (position, level) {
+                (Ok(index), Some(level)) => self.modules[index].1 = level,
+                (Ok(index), None) => {
+                    self.modules.remove(index);
+                }
+                (Err(index), Some(level)) => {
+                    self.modules.insert(index, (scope_str.to_string(), level))
+                }
+                (Err(_), None) => {}
+            }
+            return;
+        }
+
+        let Some(scope) = scope_alloc_from_scope_str(scope_str) else {
+            return;
+        };
+        // Parents always precede their descendants, so inserting or removing an
+        // entry never moves the entries on this path.
+        let mut path = Vec::with_capacity(SCOPE_DEPTH_MAX);
+        for component in scope.iter().take_while(|component| !component.is_empty()) {
+            let parent = path.last().copied();
+            let siblings = self.descendants_of(parent);
+            let existing = siblings
+                .clone()
+                .find(|&index| self.entries[index].scope == *component);
+            let index = match existing {
+                Some(index) => index,
+                None if level.is_none() => return,
+                None if siblings.is_empty() => {
+                    let index = self.entries.len();
+                    self.insert_entry(parent, index, component);
+                    index
+                }
+                None => {
+                    let index = siblings
+                        .clone()
+                        .find(|&index| {
+                            scope_sort_key(&self.entries[index].scope) > scope_sort_key(component)
+                        })
+                        .unwrap_or(siblings.end);
+                    self.insert_entry(parent, index, component);
+                    index
+                }
+            };
+            path.push(index);
+        }
+
+        let Some(&leaf) = path.last() else {
+            return;
+        };
+        self.entries[leaf].enabled = level;
+        while let Some(index) = path.pop() {
+            let entry = &self.entries[index];
+            if entry.enabled.is_some() || !entry.descendants.is_empty() {
+                break;
+            }
+            self.remove_entry(path.last().copied(), index);
+        }
+    }
+
+    fn descendants_of(&self, parent: Option<usize>) -> std::ops::Range<usize> {
+        match parent {
+            Some(parent) => self.entries[parent].descendants.clone(),
+            None => 0..self.root_count,
+        }
+    }
+
+    /// Inserts an entry at `index`, which must be within or directly after the
+    /// descendants of `parent`, or past the end when it has none.
+    fn insert_entry(&mut self, parent: Option<usize>, index: usize, scope: &str) {
+        for (entry_index, entry) in self.entries.iter_mut().enumerate() {
+            if Some(entry_index) != parent
+                && !entry.descendants.is_empty()
+                && entry.descendants.start >= index
+            {
+                entry.descendants = entry.descendants.start + 1..entry.descendants.end + 1;
+            }
+        }
+        match parent {
+            Some(parent) => {
+                let descendants = self.entries[parent].descendants.clone();
+                self.entries[parent].descendants = if descendants.is_empty() {
+                    index..index + 1
+                } else {
+                    descendants.start..descendants.end + 1
+                };
+            }
+            None => self.root_count += 1,
+        }
+        self.entries.insert(
+            index,
+            ScopeMapEntry {
+                scope: scope.to_owned(),
+                enabled: None,
+                descendants: 0..0,
+            },
+        );
+    }
+
+    /// Removes the entry at `index`, which must be one of the descendants of
+    /// `parent` and have none of its own.
+    fn remove_entry(&mut self, parent: Option<usize>, index: usize) {
+        self.entries.remove(index);
+        for (entry_index, entry) in self.entries.iter_mut().enumerate() {
+            if Some(entry_index) != parent
+                && !entry.descendants.is_empty()
+                && entry.descendants.start > index
+            {
+                entry.descendants = entry.descendants.start - 1..entry.descendants.end - 1;
+            }
+        }
+        match parent {
+            Some(parent) => self.entries[parent].descendants.end -= 1,
+            None => self.root_count -= 1,
+        }
+    }
+
+    /// Reconstructs the configured scopes as dotted strings, followed by the
+    /// configured module paths. Entries that only group nested scopes and have
+    /// no level of their own are skipped.
+    pub fn configured_scopes(&self) -> Vec<(String, log::LevelFilter)> {
+        let mut configured = Vec::new();
+        let mut pending = self.entries[0..self.root_count]
+            .iter()
+            .rev()
+            .map(|entry| (entry, entry.scope.clone()))
+            .collect::<Vec<_>>();
+        while let Some((entry, scope)) = pending.pop() {
+            pending.extend(
+                self.entries[entry.descendants.clone()]
+                    .iter()
+                    .rev()
+                    .map(|child| {
+                        (
+                            child,
+                            format!("{scope}{SCOPE_STRING_SEP_STR}{}", child.scope),
+                        )
+                    }),
+            );
+            if let Some(level) = entry.enabled {
+                configured.push((scope, level));
+            }
+        }
+        configured.extend(self.modules.iter().cloned());
+        configured
+    }
+
     pub fn is_enabled<S>(
         &self,
         scope: &[S; SCOPE_DEPTH_MAX],
@@ -333,31 +797,37 @@ impl ScopeMap {
     where
         S: AsRef<str>,
     {
-        fn search<S>(map: &ScopeMap, scope: &[S; SCOPE_DEPTH_MAX]) -> Option<log::LevelFilter>
+        fn search<S>(
+            map: &ScopeMap,
+            entries: &[ScopeMapEntry],
+            scope: &[S; SCOPE_DEPTH_MAX],
+            depth: usize,
+        ) -> Option<log::LevelFilter>
         where
             S: AsRef<str>,
         {
-            let mut enabled = None;
-            let mut cur_range = &map.entries[0..map.root_count];
-            let mut depth = 0;
-            'search: while !cur_range.is_empty()
-                && depth < SCOPE_DEPTH_MAX
-                && scope[depth].as_ref() != ""
-            {
-                for entry in cur_range {
-                    if entry.scope == scope[depth].as_ref() {
-                        enabled = entry.enabled.or(enabled);
-                        cur_range = &map.entries[entry.descendants.clone()];
-                        depth += 1;
-                        continue 'search;
-                    }
-                }
-                break 'search;
+            if depth >= SCOPE_DEPTH_MAX || scope[depth].as_ref().is_empty() {
+                return None;
             }
-            enabled
+            // Wildcards sort after literals, so the wildcard branch is only searched when
+            // the literal branch configures nothing for this scope.
+            entries
+                .iter()
+                .filter(|entry| {
+                    entry.scope == scope[depth].as_ref() || entry.scope == SCOPE_WILDCARD
+                })
+                .find_map(|entry| {
+                    search(
+                        map,
+                        &map.entries[entry.descendants.clone()],
+                        scope,
+                        depth + 1,
+                    )
+                    .or(entry.enabled)
+                })
         }
 
-        let mut enabled = search(self, scope);
+        let mut enabled = search(self, &self.entries[0..self.root_count], scope, 0);
 
         if let Some(module_path) = module_path {
             let scope_is_empty = scope[0].as_ref().is_empty();
@@ -366,7 +836,12 @@ impl ScopeMap {
                 let crate_name = private::extract_crate_name_from_module_path(module_path);
                 let mut crate_name_scope = [""; SCOPE_DEPTH_MAX];
                 crate_name_scope[0] = crate_name;
-                enabled = search(self, &crate_name_scope);
+                enabled = search(
+                    self,
+                    &self.entries[0..self.root_count],
+                    &crate_name_scope,
+                    0,
+                );
             }
 
             if !self.modules.is_empty() {
@@ -586,6 +1061,91 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_is_enabled_off() {
+        let map = scope_map_from_keys(&[("noisy", "off"), ("quiet", "none"), ("noisy.child", "")]);
+        use log::Level;
+        for level in [Level::Error, Level::Warn, Level::Info] {
+            assert_eq!(
+                map.is_enabled(&scope_from_scope_str("noisy"), None, level),
+                EnabledStatus::Disabled
+            );
+            assert_eq!(
+                map.is_enabled(&scope_from_scope_str("noisy.other"), None, level),
+                EnabledStatus::Disabled
+            );
+            assert_eq!(
+                map.is_enabled(&scope_from_scope_str("quiet"), None, level),
+                EnabledStatus::Disabled
+            );
+        }
+        // A more specific scope can still re-enable logging under a disabled one.
+        assert_eq!(
+            map.is_enabled(&scope_from_scope_str("noisy.child"), None, Level::Error),
+            EnabledStatus::Enabled
+        );
+    }
+
+    #[test]
+    fn test_is_enabled_wildcard() {
+        let map = scope_map_from_keys(&[("db.*", "debug"), ("db.pool", "trace"), ("db", "warn")]);
+        use log::Level;
+        assert_eq!(
+            map.is_enabled(&scope_from_scope_str("db.conn"), None, Level::Debug),
+            EnabledStatus::Enabled
+        );
+        assert_eq!(
+            map.is_enabled(&scope_from_scope_str("db.conn"), None, Level::Trace),
+            EnabledStatus::Disabled
+        );
+        assert_eq!(
+            map.is_enabled(&scope_from_scope_str("db.pool"), None, Level::Trace),
+            EnabledStatus::Enabled,
+            "a literal segment should win over a wildcard at the same depth"
+        );
+        assert_eq!(
+            map.is_enabled(&scope_from_scope_str("db.conn.query"), None, Level::Debug),
+            EnabledStatus::Enabled
+        );
+        assert_eq!(
+            map.is_enabled(&scope_from_scope_str("db"), None, Level::Debug),
+            EnabledStatus::Disabled,
+            "a wildcard should only match children"
+        );
+        assert_eq!(
+            map.is_enabled(&scope_from_scope_str("dbx.conn"), None, Level::Debug),
+            EnabledStatus::NotConfigured
+        );
+    }
+
+    #[test]
+    fn test_is_enabled_wildcard_with_literal_override() {
+        let map = scope_map_from_keys(&[("db.*", "debug"), ("db.pool.conn", "trace")]);
+        use log::Level;
+        assert_eq!(
+            map.is_enabled(&scope_from_scope_str("db.pool.conn"), None, Level::Trace),
+            EnabledStatus::Enabled
+        );
+        assert_eq!(
+            map.is_enabled(&scope_from_scope_str("db.pool"), None, Level::Debug),
+            EnabledStatus::Enabled,
+            "a literal branch without a level should fall back to the wildcard"
+        );
+        assert_eq!(
+            map.is_enabled(&scope_from_scope_str("db.pool"), None, Level::Trace),
+            EnabledStatus::Disabled
+        );
+        assert_eq!(
+            map.is_enabled(&scope_from_scope_str("db.pool.other"), None, Level::Debug),
+            EnabledStatus::Enabled,
+            "a literal branch that dead-ends should fall back to the wildcard"
+        );
+        assert_eq!(
+            map.is_enabled(&scope_from_scope_str("db.conn"), None, Level::Debug),
+            EnabledStatus::Enabled
+        );
+    }
+
     fn scope_map_from_keys_and_env(kv: &[(&str, &str)], env: &env_config::EnvFilter) -> ScopeMap {
         let hash_map: HashMap<String, String> = kv
             .iter()
@@ -829,4 +1389,179 @@ mod tests {
             "crate::submodule should be disabled by disabling `crate` filter"
         );
     }
+
+    #[test]
+    fn test_set_scope_level() {
+        refresh_from_settings(&HashMap::default());
+        let scope = scope_new(&["zlog_runtime_test", "scroll"]);
+        assert!(!is_scope_enabled(&scope, None, log::Level::Debug));
+
+        set_scope_level("zlog_runtime_test.scroll", Some(log::Level::Debug));
+        assert!(is_scope_enabled(&scope, None, log::Level::Debug));
+        assert!(!is_scope_enabled(&scope, None, log::Level::Trace));
+
+        refresh_from_settings(&HashMap::default());
+        assert!(
+            is_scope_enabled(&scope, None, log::Level::Debug),
+            "runtime levels should survive a settings refresh"
+        );
+
+        set_scope_level("zlog_runtime_test.scroll", None);
+        assert!(!is_scope_enabled(&scope, None, log::Level::Debug));
+
+        refresh_from_settings(&HashMap::from_iter([(
+            "zlog_runtime_test.scroll".to_string(),
+            "info".to_string(),
+        )]));
+        set_scope_level("zlog_runtime_test.scroll", Some(log::Level::Trace));
+        assert!(
+            is_scope_enabled(&scope, None, log::Level::Trace),
+            "runtime levels should take precedence over settings"
+        );
+        set_scope_level("zlog_runtime_test.scroll", None);
+        assert!(
+            is_scope_enabled(&scope, None, log::Level::Info)
+                && !is_scope_enabled(&scope, None, log::Level::Debug),
+            "removing a runtime level should restore the level from settings"
+        );
+        refresh_from_settings(&HashMap::default());
+    }
+
+    #[test]
+    fn test_enabled_status_cache() {
+        fn lookups() -> usize {
+            SCOPE_MAP_LOOKUPS.get()
+        }
+
+        set_scope_level("zlog_cache_test.hot", Some(log::Level::Debug));
+        let scope = scope_new(&["zlog_cache_test", "hot"]);
+        let other_scope = scope_new(&["zlog_cache_test", "cold"]);
+
+        // Retry in case a concurrently running test rebuilt the map mid-loop.
+        let always_walked_map = (0..10).all(|_| {
+            let start = lookups();
+            for _ in 0..1000 {
+                assert!(is_scope_enabled(&scope, None, log::Level::Debug));
+            }
+            lookups() - start > 1
+        });
+        assert!(
+            !always_walked_map,
+            "repeated lookups of a scope should be served from the cache"
+        );
+
+        assert!(!is_scope_enabled(&scope, None, log::Level::Trace));
+        assert!(!is_scope_enabled(&other_scope, None, log::Level::Debug));
+
+        set_scope_level("zlog_cache_test.hot", Some(log::Level::Trace));
+        assert!(
+            is_scope_enabled(&scope, None, log::Level::Trace),
+            "rebuilding the scope map should invalidate the cache"
+        );
+
+        set_scope_level("zlog_cache_test.hot", None);
+        assert!(!is_scope_enabled(&scope, None, log::Level::Debug));
+    }
+
+    #[test]
+    fn test_configured_scopes() {
+        let settings = [
+            ("a.b", "trace"),
+            ("a.b.c", "debug"),
+            ("a.d.e.f", "info"),
+            ("db.*", "warn"),
+            ("g", "off"),
+            ("crate_name::module", "error"),
+        ];
+        let map = scope_map_from_keys(&settings);
+        let configured = map.configured_scopes();
+        assert_eq!(
+            configured,
+            [
+                ("a.b".to_string(), LevelFilter::Trace),
+                ("a.b.c".to_string(), LevelFilter::Debug),
+                ("a.d.e.f".to_string(), LevelFilter::Info),
+                ("db.*".to_string(), LevelFilter::Warn),
+                ("g".to_string(), LevelFilter::Off),
+                ("crate_name::module".to_string(), LevelFilter::Error),
+            ]
+        );
+
+        let configured_settings = configured
+            .iter()
+            .map(|(scope, level)| (scope.as_str(), level.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(scope_map_from_keys(&configured_settings), map);
+
+        assert!(scope_map_from_keys(&[]).configured_scopes().is_empty());
+    }
+
+    #[test]
+    fn test_set_level_matches_rebuilt_map() {
+        let mut settings = HashMap::default();
+        let mut map = ScopeMap::new_from_settings_and_env(&settings, None, &[]);
+        let changes = [
+            ("a.b", Some(LevelFilter::Debug)),
+            ("a.*.c", Some(LevelFilter::Trace)),
+            ("a.a", Some(LevelFilter::Warn)),
+            ("a", Some(LevelFilter::Error)),
+            ("b.c.d.e", Some(LevelFilter::Info)),
+            ("a.x.c", Some(LevelFilter::Off)),
+            ("crate_name::module", Some(LevelFilter::Off)),
+            ("a.b", Some(LevelFilter::Trace)),
+            ("a.b", None),
+            ("b.c.d.e", None),
+            ("a.*.c", None),
+            ("crate_name::module", None),
+            ("missing.scope", None),
+        ];
+        let scopes = [
+            scope_new(&["a"]),
+            scope_new(&["a", "b"]),
+            scope_new(&["a", "x", "c"]),
+            scope_new(&["a", "y", "c"]),
+            scope_new(&["b", "c", "d", "e"]),
+        ];
+        for (scope_str, level) in changes {
+            match level {
+                Some(level) => settings.insert(scope_str.to_string(), level.to_string()),
+                None => settings.remove(scope_str),
+            };
+            map.set_level(scope_str, level);
+
+            let rebuilt = ScopeMap::new_from_settings_and_env(&settings, None, &[]);
+            assert_eq!(
+                map.configured_scopes(),
+                rebuilt.configured_scopes(),
+                "after setting {scope_str} to {level:?}"
+            );
+            assert_eq!(map.entries.len(), rebuilt.entries.len());
+            for scope in &scopes {
+                for log_level in [log::Level::Error, log::Level::Info, log::Level::Trace] {
+                    assert_eq!(
+                        map.is_enabled(scope, Some("crate_name::module"), log_level),
+                        rebuilt.is_enabled(scope, Some("crate_name::module"), log_level),
+                        "{scope:?} at {log_level} after setting {scope_str} to {level:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_reload_env_filter() {
+        let editor_scope = scope_new(&["zlog_reload_test_editor"]);
+        let rpc_scope = scope_new(&["zlog_reload_test_rpc"]);
+
+        reload_env_filter(env_config::parse("zlog_reload_test_editor=debug").unwrap());
+        assert!(is_scope_enabled(&editor_scope, None, log::Level::Debug));
+        assert!(!is_scope_enabled(&rpc_scope, None, log::Level::Debug));
+
+        reload_env_filter(
+            env_config::parse("zlog_reload_test_editor=warn,zlog_reload_test_rpc=trace").unwrap(),
+        );
+        assert!(!is_scope_enabled(&editor_scope, None, log::Level::Info));
+        assert!(is_scope_enabled(&editor_scope, None, log::Level::Warn));
+        assert!(is_scope_enabled(&rpc_scope, None, log::Level::Trace));
+    }
 }
@@ -1,14 +1,16 @@
 use std::{
+    borrow::Cow,
+    collections::VecDeque,
     fs,
-    io::{self, Write},
+    io::{self, IsTerminal, Write},
     path::PathBuf,
     sync::{
-        Mutex, OnceLock,
-        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, OnceLock, RwLock,
+        atomic::{AtomicBool, AtomicU8, AtomicU64, Ordering},
     },
 };
 
-use crate::{SCOPE_STRING_SEP_CHAR, Scope};
+use crate::{SCOPE_STRING_SEP_CHAR, Scope, ScopeAlloc, filter};
 
 // ANSI color escape codes for log levels
 const ANSI_RESET: &str = "\x1b[0m";
@@ -29,16 +31,228 @@ static SINK_FILE_PATH_ROTATE: OnceLock<&'static PathBuf> = OnceLock::new();
 static ENABLED_SINKS_STDOUT: AtomicBool = AtomicBool::new(false);
 /// Whether stderr output is enabled.
 static ENABLED_SINKS_STDERR: AtomicBool = AtomicBool::new(false);
+/// Whether terminal output is colored, one of the `COLOR_OUTPUT_*` values.
+static COLOR_OUTPUT: AtomicU8 = AtomicU8::new(COLOR_OUTPUT_AUTO);
+const COLOR_OUTPUT_AUTO: u8 = 0;
+const COLOR_OUTPUT_ALWAYS: u8 = 1;
+const COLOR_OUTPUT_NEVER: u8 = 2;
+static STDOUT_IS_TERMINAL: OnceLock<bool> = OnceLock::new();
+static STDERR_IS_TERMINAL: OnceLock<bool> = OnceLock::new();
 /// Atomic counter for the size of the log file in bytes.
 static SINK_FILE_SIZE_BYTES: AtomicU64 = AtomicU64::new(0);
 /// Maximum size of the log file before it will be rotated, in bytes.
 const SINK_FILE_SIZE_BYTES_MAX: u64 = 1024 * 1024; // 1 MB
 
+/// How [`StdoutSink`] renders timestamps.
+static TIMESTAMP_FORMAT: RwLock<TimestampFormat> = RwLock::new(TimestampFormat::LocalRfc3339);
+const LOCAL_RFC3339_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%:z";
+
+/// The sink terminal output is sent to, or `None` for [`StdoutSink`].
+static SINK: RwLock<Option<Box<dyn Sink>>> = RwLock::new(None);
+
 pub struct Record<'a> {
     pub scope: Scope,
     pub level: log::Level,
     pub message: &'a std::fmt::Arguments<'a>,
     pub module_path: Option<&'a str>,
+    /// The thread the record was logged from.
+    pub thread: std::thread::Thread,
+}
+
+impl Record<'_> {
+    /// The name of the thread the record was logged from, or its id if it is unnamed.
+    pub fn thread_label(&self) -> Cow<'_, str> {
+        match self.thread.name() {
+            Some(name) => Cow::Borrowed(name),
+            None => Cow::Owned(format!("{:?}", self.thread.id())),
+        }
+    }
+}
+
+/// A destination for log records, such as the terminal, a buffer backing an
+/// in-app log viewer, or a capture in tests.
+pub trait Sink: Send + Sync {
+    fn submit(&self, record: &Record);
+    fn flush(&self);
+
+    /// The least severe level this sink records. Less severe records are dropped
+    /// before they reach [`Sink::submit`], after the scope filters have let them through.
+    fn min_level(&self) -> log::LevelFilter {
+        log::LevelFilter::Trace
+    }
+}
+
+fn submit_to_sink(sink: &dyn Sink, record: &Record) {
+    if record.level <= sink.min_level() {
+        sink.submit(record);
+    }
+}
+
+/// Wraps a sink so it only records messages at `min_level` or more severe,
+/// e.g. to keep the terminal quiet while a file sink records everything.
+pub struct MinLevelSink<S> {
+    sink: S,
+    min_level: log::LevelFilter,
+}
+
+impl<S: Sink> MinLevelSink<S> {
+    pub fn new(sink: S, min_level: log::LevelFilter) -> Self {
+        Self { sink, min_level }
+    }
+}
+
+impl<S: Sink> Sink for MinLevelSink<S> {
+    fn submit(&self, record: &Record) {
+        submit_to_sink(&self.sink, record);
+    }
+
+    fn flush(&self) {
+        self.sink.flush();
+    }
+
+    fn min_level(&self) -> log::LevelFilter {
+        self.min_level.min(self.sink.min_level())
+    }
+}
+
+/// Replaces the sink that records are submitted to, which defaults to [`StdoutSink`].
+/// The log file configured with [`init_output_file`] is written regardless of the sink.
+pub fn set_sink(sink: Box<dyn Sink>) {
+    let mut current_sink = SINK.write().unwrap_or_else(|err| {
+        SINK.clear_poison();
+        err.into_inner()
+    });
+    current_sink.replace(sink);
+}
+
+/// Writes records to stdout or stderr, depending on whether
+/// [`init_output_stdout`] or [`init_output_stderr`] was called.
+pub struct StdoutSink;
+
+impl Sink for StdoutSink {
+    fn submit(&self, record: &Record) {
+        let timestamp_format = TIMESTAMP_FORMAT.read().unwrap_or_else(|err| {
+            TIMESTAMP_FORMAT.clear_poison();
+            err.into_inner()
+        });
+        let timestamp = TimestampFmt {
+            format: &timestamp_format,
+            now: chrono::Utc::now(),
+        };
+        if ENABLED_SINKS_STDOUT.load(Ordering::Acquire) {
+            let color = color_output_enabled(&STDOUT_IS_TERMINAL, || io::stdout().is_terminal());
+            write_terminal_record(&mut io::stdout().lock(), record, &timestamp, color);
+        } else if ENABLED_SINKS_STDERR.load(Ordering::Acquire) {
+            let color = color_output_enabled(&STDERR_IS_TERMINAL, || io::stderr().is_terminal());
+            write_terminal_record(&mut io::stderr().lock(), record, &timestamp, color);
+        }
+    }
+
+    fn flush(&self) {
+        if ENABLED_SINKS_STDOUT.load(Ordering::Acquire) {
+            _ = std::io::stdout().lock().flush();
+        }
+    }
+}
+
+/// How timestamps are rendered by [`StdoutSink`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// Local time with its UTC offset, e.g. `2025-03-14T09:26:53-07:00`.
+    #[default]
+    LocalRfc3339,
+    /// UTC time, e.g. `2025-03-14T16:26:53Z`.
+    Utc,
+    /// Milliseconds since the Unix epoch.
+    UnixMillis,
+    /// A [`chrono::format::strftime`] format string, rendered in local time.
+    Custom(String),
+}
+
+/// Sets how [`StdoutSink`] renders timestamps. Custom formats that chrono
+/// can't parse are reported and ignored.
+pub fn set_timestamp_format(format: TimestampFormat) {
+    if let TimestampFormat::Custom(custom_format) = &format
+        && chrono::format::StrftimeItems::new(custom_format)
+            .any(|item| matches!(item, chrono::format::Item::Error))
+    {
+        eprintln!("Invalid log timestamp format: {custom_format:?}");
+        return;
+    }
+    let mut timestamp_format = TIMESTAMP_FORMAT.write().unwrap_or_else(|err| {
+        TIMESTAMP_FORMAT.clear_poison();
+        err.into_inner()
+    });
+    *timestamp_format = format;
+}
+
+struct TimestampFmt<'a> {
+    format: &'a TimestampFormat,
+    now: chrono::DateTime<chrono::Utc>,
+}
+
+impl std::fmt::Display for TimestampFmt<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.format {
+            TimestampFormat::LocalRfc3339 => self
+                .now
+                .with_timezone(&chrono::Local)
+                .format(LOCAL_RFC3339_FORMAT)
+                .fmt(f),
+            TimestampFormat::Utc => self.now.format("%Y-%m-%dT%H:%M:%SZ").fmt(f),
+            TimestampFormat::UnixMillis => self.now.timestamp_millis().fmt(f),
+            TimestampFormat::Custom(custom_format) => self
+                .now
+                .with_timezone(&chrono::Local)
+                .format(custom_format)
+                .fmt(f),
+        }
+    }
+}
+
+/// Forces colored terminal output on or off. `None` restores the default of
+/// coloring only when the output stream is a terminal.
+pub fn set_color_output(enabled: Option<bool>) {
+    let value = match enabled {
+        None => COLOR_OUTPUT_AUTO,
+        Some(true) => COLOR_OUTPUT_ALWAYS,
+        Some(false) => COLOR_OUTPUT_NEVER,
+    };
+    COLOR_OUTPUT.store(value, Ordering::Release);
+}
+
+fn color_output_enabled(is_terminal: &OnceLock<bool>, detect: impl FnOnce() -> bool) -> bool {
+    match COLOR_OUTPUT.load(Ordering::Acquire) {
+        COLOR_OUTPUT_ALWAYS => true,
+        COLOR_OUTPUT_NEVER => false,
+        _ => *is_terminal.get_or_init(detect),
+    }
+}
+
+fn write_terminal_record(
+    writer: &mut impl Write,
+    record: &Record,
+    timestamp: &dyn std::fmt::Display,
+    color: bool,
+) {
+    if !color {
+        write_plain_record(writer, record, timestamp);
+        return;
+    }
+    _ = writeln!(
+        writer,
+        "{} {ANSI_BOLD}{}{}{ANSI_RESET} {} ({}) {}",
+        timestamp,
+        LEVEL_ANSI_COLORS[record.level as usize],
+        LEVEL_OUTPUT_STRINGS[record.level as usize],
+        SourceFmt {
+            scope: record.scope,
+            module_path: record.module_path,
+            ansi: true,
+        },
+        record.thread_label(),
+        record.message
+    );
 }
 
 pub fn init_output_stdout() {
@@ -104,72 +318,294 @@ static LEVEL_ANSI_COLORS: [&str; 6] = [
     ANSI_MAGENTA, // Trace: Magenta
 ];
 
-// PERF: batching
-pub fn submit(record: Record) {
-    if ENABLED_SINKS_STDOUT.load(Ordering::Acquire) {
-        let mut stdout = std::io::stdout().lock();
-        _ = writeln!(
-            &mut stdout,
-            "{} {ANSI_BOLD}{}{}{ANSI_RESET} {} {}",
-            chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%:z"),
-            LEVEL_ANSI_COLORS[record.level as usize],
-            LEVEL_OUTPUT_STRINGS[record.level as usize],
-            SourceFmt {
-                scope: record.scope,
-                module_path: record.module_path,
-                ansi: true,
-            },
-            record.message
+/// Writes each record as a single-line JSON object with `ts`, `level`,
+/// `scope` and `message` fields, for ingestion into log aggregators.
+pub struct JsonSink<W> {
+    writer: Mutex<W>,
+}
+
+impl<W: Write + Send> JsonSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+        }
+    }
+}
+
+impl JsonSink<io::Stdout> {
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+}
+
+impl<W: Write + Send> Sink for JsonSink<W> {
+    fn submit(&self, record: &Record) {
+        let scope = crate::scope_components(&record.scope).collect::<Vec<_>>();
+        let line = serde_json::json!({
+            "ts": chrono::Local::now().format(LOCAL_RFC3339_FORMAT).to_string(),
+            "level": record.level.as_str(),
+            "scope": scope,
+            "message": record.message.to_string(),
+        });
+        let mut writer = self.writer.lock().unwrap_or_else(|err| {
+            self.writer.clear_poison();
+            err.into_inner()
+        });
+        _ = writeln!(&mut writer, "{line}");
+    }
+
+    fn flush(&self) {
+        let mut writer = self.writer.lock().unwrap_or_else(|err| {
+            self.writer.clear_poison();
+            err.into_inner()
+        });
+        _ = writer.flush();
+    }
+}
+
+/// Writes records to a file in the same format as the log file configured with
+/// [`init_output_file`], rotating it to `<path>.1`, `<path>.2`, … (newest first)
+/// once it grows past a size limit.
+pub struct FileSink {
+    state: Mutex<FileSinkState>,
+}
+
+struct FileSinkState {
+    file: fs::File,
+    path: PathBuf,
+    size_bytes: u64,
+    max_size_bytes: u64,
+    max_rotated_files: usize,
+}
+
+impl FileSink {
+    pub fn new(
+        path: impl Into<PathBuf>,
+        max_size_bytes: u64,
+        max_rotated_files: usize,
+    ) -> io::Result<Self> {
+        let path = path.into();
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        let size_bytes = file.metadata().map_or(0, |metadata| metadata.len());
+        Ok(Self {
+            state: Mutex::new(FileSinkState {
+                file,
+                path,
+                size_bytes,
+                max_size_bytes,
+                max_rotated_files,
+            }),
+        })
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, FileSinkState> {
+        self.state.lock().unwrap_or_else(|err| {
+            self.state.clear_poison();
+            err.into_inner()
+        })
+    }
+}
+
+impl FileSinkState {
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.max_rotated_files == 0 {
+            self.file.set_len(0)?;
+        } else {
+            let oldest_path = rotated_log_path(&self.path, self.max_rotated_files);
+            if oldest_path.exists() {
+                fs::remove_file(&oldest_path)?;
+            }
+            for index in (1..self.max_rotated_files).rev() {
+                let rotated_path = rotated_log_path(&self.path, index);
+                if rotated_path.exists() {
+                    fs::rename(&rotated_path, rotated_log_path(&self.path, index + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated_log_path(&self.path, 1))?;
+            self.file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+        }
+        self.size_bytes = 0;
+        Ok(())
+    }
+}
+
+fn rotated_log_path(path: &std::path::Path, index: usize) -> PathBuf {
+    let mut rotated_path = path.as_os_str().to_owned();
+    rotated_path.push(format!(".{index}"));
+    PathBuf::from(rotated_path)
+}
+
+impl Sink for FileSink {
+    fn submit(&self, record: &Record) {
+        let mut state = self.lock_state();
+        let mut writer = SizedWriter {
+            file: &mut state.file,
+            written: 0,
+        };
+        write_plain_record(
+            &mut writer,
+            record,
+            &chrono::Local::now().format(LOCAL_RFC3339_FORMAT),
         );
-    } else if ENABLED_SINKS_STDERR.load(Ordering::Acquire) {
-        let mut stdout = std::io::stderr().lock();
-        _ = writeln!(
-            &mut stdout,
-            "{} {ANSI_BOLD}{}{}{ANSI_RESET} {} {}",
-            chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%:z"),
-            LEVEL_ANSI_COLORS[record.level as usize],
-            LEVEL_OUTPUT_STRINGS[record.level as usize],
-            SourceFmt {
-                scope: record.scope,
-                module_path: record.module_path,
-                ansi: true,
-            },
-            record.message
+        state.size_bytes += writer.written;
+        if state.size_bytes > state.max_size_bytes
+            && let Err(err) = state.rotate()
+        {
+            eprintln!("Log file rotation failed: {err}");
+        }
+    }
+
+    fn flush(&self) {
+        if let Err(err) = self.lock_state().file.flush() {
+            eprintln!("Failed to flush log file: {}", err);
+        }
+    }
+}
+
+/// Keeps the most recent `capacity` records in memory, formatted like the log
+/// file, so an in-app log viewer can poll them. Clones share the same buffer,
+/// so one clone can be installed with [`set_sink`] while another is read.
+#[derive(Clone)]
+pub struct RingBufferSink {
+    records: Arc<Mutex<VecDeque<String>>>,
+    capacity: usize,
+}
+
+impl RingBufferSink {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// Returns the buffered records, oldest first.
+    pub fn snapshot(&self) -> Vec<String> {
+        self.lock_records().iter().cloned().collect()
+    }
+
+    fn lock_records(&self) -> std::sync::MutexGuard<'_, VecDeque<String>> {
+        self.records.lock().unwrap_or_else(|err| {
+            self.records.clear_poison();
+            err.into_inner()
+        })
+    }
+}
+
+impl Sink for RingBufferSink {
+    fn submit(&self, record: &Record) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut line = Vec::new();
+        write_plain_record(
+            &mut line,
+            record,
+            &chrono::Local::now().format(LOCAL_RFC3339_FORMAT),
         );
+        let mut line = String::from_utf8_lossy(&line).into_owned();
+        if line.ends_with('\n') {
+            line.pop();
+        }
+
+        let mut records = self.lock_records();
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back(line);
+    }
+
+    fn flush(&self) {}
+}
+
+/// Sends records to different sinks based on their scope, e.g. all `rpc` logs to
+/// their own file while everything else goes to the terminal.
+///
+/// Rules are checked in order and a record is only sent to the first rule whose
+/// scope prefix matches it, or to the default sink if none match. Prefixes are
+/// matched segment by segment like the keys of the scope map, including `*`
+/// wildcard segments.
+pub struct RoutingSink {
+    rules: Vec<(ScopeAlloc, Box<dyn Sink>)>,
+    default: Box<dyn Sink>,
+}
+
+impl RoutingSink {
+    pub fn new(default: Box<dyn Sink>) -> Self {
+        Self {
+            rules: Vec::new(),
+            default,
+        }
+    }
+
+    /// Adds a rule routing records under `scope_prefix` to `sink`. Invalid
+    /// prefixes are logged and ignored.
+    pub fn route(mut self, scope_prefix: &str, sink: Box<dyn Sink>) -> Self {
+        if let Some(scope_prefix) = filter::scope_alloc_from_scope_str(scope_prefix) {
+            self.rules.push((scope_prefix, sink));
+        }
+        self
+    }
+
+    fn sink_for(&self, scope: &Scope) -> &dyn Sink {
+        self.rules
+            .iter()
+            .find(|(scope_prefix, _)| {
+                scope_prefix
+                    .iter()
+                    .zip(scope)
+                    .take_while(|(prefix_component, _)| !prefix_component.is_empty())
+                    .all(|(prefix_component, component)| {
+                        prefix_component == component || prefix_component == filter::SCOPE_WILDCARD
+                    })
+            })
+            .map_or(self.default.as_ref(), |(_, sink)| sink.as_ref())
+    }
+}
+
+impl Sink for RoutingSink {
+    fn submit(&self, record: &Record) {
+        submit_to_sink(self.sink_for(&record.scope), record);
+    }
+
+    fn flush(&self) {
+        for (_, sink) in &self.rules {
+            sink.flush();
+        }
+        self.default.flush();
+    }
+}
+
+// PERF: batching
+pub fn submit(record: Record) {
+    {
+        let sink = SINK.read().unwrap_or_else(|err| {
+            SINK.clear_poison();
+            err.into_inner()
+        });
+        match sink.as_ref() {
+            Some(sink) => submit_to_sink(sink.as_ref(), &record),
+            None => StdoutSink.submit(&record),
+        }
     }
     let mut file = ENABLED_SINKS_FILE.lock().unwrap_or_else(|handle| {
         ENABLED_SINKS_FILE.clear_poison();
         handle.into_inner()
     });
     if let Some(file) = file.as_mut() {
-        struct SizedWriter<'a> {
-            file: &'a mut std::fs::File,
-            written: u64,
-        }
-        impl io::Write for SizedWriter<'_> {
-            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-                self.file.write(buf)?;
-                self.written += buf.len() as u64;
-                Ok(buf.len())
-            }
-
-            fn flush(&mut self) -> io::Result<()> {
-                self.file.flush()
-            }
-        }
         let file_size_bytes = {
             let mut writer = SizedWriter { file, written: 0 };
-            _ = writeln!(
+            write_plain_record(
                 &mut writer,
-                "{} {} {} {}",
-                chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%:z"),
-                LEVEL_OUTPUT_STRINGS[record.level as usize],
-                SourceFmt {
-                    scope: record.scope,
-                    module_path: record.module_path,
-                    ansi: false,
-                },
-                record.message
+                &record,
+                &chrono::Local::now().format(LOCAL_RFC3339_FORMAT),
             );
             SINK_FILE_SIZE_BYTES.fetch_add(writer.written, Ordering::AcqRel) + writer.written
         };
@@ -184,9 +620,50 @@ pub fn submit(record: Record) {
     }
 }
 
+struct SizedWriter<'a> {
+    file: &'a mut std::fs::File,
+    written: u64,
+}
+
+impl io::Write for SizedWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)?;
+        self.written += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Writes the record in the uncolored format used for log files.
+fn write_plain_record(writer: &mut impl Write, record: &Record, timestamp: &dyn std::fmt::Display) {
+    _ = writeln!(
+        writer,
+        "{} {} {} ({}) {}",
+        timestamp,
+        LEVEL_OUTPUT_STRINGS[record.level as usize],
+        SourceFmt {
+            scope: record.scope,
+            module_path: record.module_path,
+            ansi: false,
+        },
+        record.thread_label(),
+        record.message
+    );
+}
+
 pub fn flush() {
-    if ENABLED_SINKS_STDOUT.load(Ordering::Acquire) {
-        _ = std::io::stdout().lock().flush();
+    {
+        let sink = SINK.read().unwrap_or_else(|err| {
+            SINK.clear_poison();
+            err.into_inner()
+        });
+        match sink.as_ref() {
+            Some(sink) => sink.flush(),
+            None => StdoutSink.flush(),
+        }
     }
     let mut file = ENABLED_SINKS_FILE.lock().unwrap_or_else(|handle| {
         ENABLED_SINKS_FILE.clear_poison();
@@ -199,6 +676,26 @@ pub fn flush() {
     }
 }
 
+/// Calls [`flush`] when dropped, including when unwinding from a panic, so that
+/// records still buffered by the sinks are written out. See
+/// [`crate::init_with_flush_guard`].
+#[must_use = "the sinks are flushed when the guard is dropped"]
+pub struct LogFlushGuard {
+    _private: (),
+}
+
+impl LogFlushGuard {
+    pub(crate) fn new() -> Self {
+        Self { _private: () }
+    }
+}
+
+impl Drop for LogFlushGuard {
+    fn drop(&mut self) {
+        flush();
+    }
+}
+
 struct SourceFmt<'a> {
     scope: Scope,
     module_path: Option<&'a str>,
@@ -216,13 +713,11 @@ impl std::fmt::Display for SourceFmt<'_> {
         if (self.scope[1].is_empty() && self.module_path.is_some()) || self.scope[0].is_empty() {
             f.write_str(self.module_path.unwrap_or("?"))?;
         } else {
-            f.write_str(self.scope[0])?;
-            for subscope in &self.scope[1..] {
-                if subscope.is_empty() {
-                    break;
+            for (index, component) in crate::scope_components(&self.scope).enumerate() {
+                if index > 0 {
+                    f.write_char(SCOPE_STRING_SEP_CHAR)?;
                 }
-                f.write_char(SCOPE_STRING_SEP_CHAR)?;
-                f.write_str(subscope)?;
+                f.write_str(component)?;
             }
         }
         if self.ansi {
@@ -269,6 +764,75 @@ fn rotate_log_file<PathRef>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::MutexGuard;
+
+    /// Held by tests that replace the global sink, so they don't capture each other's records.
+    static GLOBAL_SINK_LOCK: Mutex<()> = Mutex::new(());
+
+    /// Lets a test replace the global sink, putting back the sink that was installed
+    /// before it when dropped.
+    struct GlobalSinkGuard {
+        previous_sink: Option<Box<dyn Sink>>,
+        _lock: MutexGuard<'static, ()>,
+    }
+
+    impl GlobalSinkGuard {
+        fn new() -> Self {
+            let lock = GLOBAL_SINK_LOCK
+                .lock()
+                .unwrap_or_else(|err| err.into_inner());
+            let previous_sink = SINK.write().unwrap_or_else(|err| err.into_inner()).take();
+            Self {
+                previous_sink,
+                _lock: lock,
+            }
+        }
+    }
+
+    impl Drop for GlobalSinkGuard {
+        fn drop(&mut self) {
+            *SINK.write().unwrap_or_else(|err| err.into_inner()) = self.previous_sink.take();
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct CapturedRecord {
+        level: log::Level,
+        scope: Scope,
+        thread_label: String,
+        message: String,
+    }
+
+    /// Collects the records it's given. Clones share the collected records, so one can be
+    /// handed off as a sink while another is read.
+    #[derive(Clone, Default)]
+    struct CaptureSink(Arc<Mutex<Vec<CapturedRecord>>>);
+
+    impl CaptureSink {
+        fn records(&self) -> Vec<CapturedRecord> {
+            self.0.lock().unwrap().clone()
+        }
+
+        fn messages(&self) -> Vec<String> {
+            self.records()
+                .into_iter()
+                .map(|record| record.message)
+                .collect()
+        }
+    }
+
+    impl Sink for CaptureSink {
+        fn submit(&self, record: &Record) {
+            self.0.lock().unwrap().push(CapturedRecord {
+                level: record.level,
+                scope: record.scope,
+                thread_label: record.thread_label().into_owned(),
+                message: record.message.to_string(),
+            });
+        }
+
+        fn flush(&self) {}
+    }
 
     #[test]
     fn test_rotate_log_file() {
@@ -308,4 +872,335 @@ mod tests {
         assert_eq!(LEVEL_OUTPUT_STRINGS[log::Level::Debug as usize], "DEBUG");
         assert_eq!(LEVEL_OUTPUT_STRINGS[log::Level::Trace as usize], "TRACE");
     }
+
+    #[test]
+    fn test_set_sink() {
+        let _guard = GlobalSinkGuard::new();
+        let sink = CaptureSink::default();
+        set_sink(Box::new(sink.clone()));
+        submit(Record {
+            scope: crate::private::scope_new(&["zlog", "sink_test"]),
+            level: log::Level::Warn,
+            message: &format_args!("captured {}", 42),
+            module_path: Some(module_path!()),
+            thread: std::thread::current(),
+        });
+
+        let captured = sink
+            .records()
+            .into_iter()
+            .filter(|record| record.scope[1] == "sink_test")
+            .map(|record| (record.level, record.scope, record.message))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            captured,
+            [(
+                log::Level::Warn,
+                crate::private::scope_new(&["zlog", "sink_test"]),
+                "captured 42".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_flush_guard() {
+        #[derive(Default)]
+        struct BufferedLines {
+            buffered: Vec<String>,
+            flushed: Vec<String>,
+        }
+
+        struct BufferingSink(Arc<Mutex<BufferedLines>>);
+
+        impl Sink for BufferingSink {
+            fn submit(&self, record: &Record) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .buffered
+                    .push(record.message.to_string());
+            }
+
+            fn flush(&self) {
+                let mut lines = self.0.lock().unwrap();
+                let buffered = std::mem::take(&mut lines.buffered);
+                lines.flushed.extend(buffered);
+            }
+        }
+
+        let _guard = GlobalSinkGuard::new();
+        let lines = Arc::new(Mutex::new(BufferedLines::default()));
+        set_sink(Box::new(BufferingSink(lines.clone())));
+
+        let guard = LogFlushGuard::new();
+        submit(Record {
+            scope: crate::private::scope_new(&["zlog", "flush_guard_test"]),
+            level: log::Level::Info,
+            message: &format_args!("last line"),
+            module_path: Some(module_path!()),
+            thread: std::thread::current(),
+        });
+        assert!(lines.lock().unwrap().flushed.is_empty());
+
+        drop(guard);
+        let lines = lines.lock().unwrap();
+        assert!(lines.buffered.is_empty());
+        assert_eq!(lines.flushed, ["last line"]);
+    }
+
+    #[test]
+    fn test_json_sink() {
+        let sink = JsonSink::new(Vec::new());
+        sink.submit(&Record {
+            scope: crate::private::scope_new(&["editor", "scroll"]),
+            level: log::Level::Debug,
+            message: &format_args!("scrolled to {}", 10),
+            module_path: Some("editor::scroll"),
+            thread: std::thread::current(),
+        });
+
+        let output = String::from_utf8(sink.writer.into_inner().unwrap()).unwrap();
+        assert_eq!(output.lines().count(), 1);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let object = json.as_object().unwrap();
+        assert_eq!(object.len(), 4);
+        assert!(object["ts"].is_string());
+        assert_eq!(object["level"], "DEBUG");
+        assert_eq!(object["scope"], serde_json::json!(["editor", "scroll"]));
+        assert_eq!(object["message"], "scrolled to 10");
+    }
+
+    #[test]
+    fn test_file_sink_rotation() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let log_file_path = temp_dir.path().join("zed.log");
+        let sink = FileSink::new(&log_file_path, 256, 2).unwrap();
+
+        let scope = crate::private::scope_new(&["zlog"]);
+        sink.submit(&Record {
+            scope,
+            level: log::Level::Info,
+            message: &format_args!("{}", "older record ".repeat(20)),
+            module_path: None,
+            thread: std::thread::current(),
+        });
+        sink.submit(&Record {
+            scope,
+            level: log::Level::Info,
+            message: &format_args!("newer record"),
+            module_path: None,
+            thread: std::thread::current(),
+        });
+        sink.flush();
+
+        let newest = fs::read_to_string(&log_file_path).unwrap();
+        let rotated = fs::read_to_string(temp_dir.path().join("zed.log.1")).unwrap();
+        assert!(
+            newest.ends_with(&format!(
+                " INFO  [zlog] ({}) newer record\n",
+                std::thread::current().name().unwrap()
+            )),
+            "{newest:?}"
+        );
+        assert!(rotated.contains("older record"), "{rotated:?}");
+        assert!(!temp_dir.path().join("zed.log.2").exists());
+    }
+
+    #[test]
+    fn test_record_thread_label() {
+        let sink = CaptureSink::default();
+        let threads = ["worker-a", "worker-b"].map(|name| {
+            let sink = sink.clone();
+            std::thread::Builder::new()
+                .name(name.to_string())
+                .spawn(move || {
+                    sink.submit(&Record {
+                        scope: crate::private::scope_new(&["zlog"]),
+                        level: log::Level::Info,
+                        message: &format_args!("sent from {name}"),
+                        module_path: Some(module_path!()),
+                        thread: std::thread::current(),
+                    });
+                })
+                .unwrap()
+        });
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let mut records = sink
+            .records()
+            .into_iter()
+            .map(|record| (record.thread_label, record.message))
+            .collect::<Vec<_>>();
+        records.sort();
+        assert_eq!(
+            records,
+            [
+                ("worker-a".to_string(), "sent from worker-a".to_string()),
+                ("worker-b".to_string(), "sent from worker-b".to_string()),
+            ]
+        );
+
+        let unnamed_thread_label = std::thread::spawn(|| {
+            Record {
+                scope: crate::private::scope_new(&["zlog"]),
+                level: log::Level::Info,
+                message: &format_args!("unnamed"),
+                module_path: None,
+                thread: std::thread::current(),
+            }
+            .thread_label()
+            .into_owned()
+        })
+        .join()
+        .unwrap();
+        assert!(unnamed_thread_label.starts_with("ThreadId("));
+    }
+
+    #[test]
+    fn test_color_output_disabled() {
+        set_color_output(Some(false));
+        let record = Record {
+            scope: crate::private::scope_new(&["zlog", "color_test"]),
+            level: log::Level::Warn,
+            message: &format_args!("plain output"),
+            module_path: Some(module_path!()),
+            thread: std::thread::current(),
+        };
+        let mut output = Vec::new();
+        let color = color_output_enabled(&OnceLock::new(), || true);
+        write_terminal_record(
+            &mut output,
+            &record,
+            &chrono::Local::now().format(LOCAL_RFC3339_FORMAT),
+            color,
+        );
+        set_color_output(None);
+
+        let output = String::from_utf8(output).unwrap();
+        let (timestamp, rest) = output.split_once(' ').unwrap();
+        assert!(
+            chrono::DateTime::parse_from_str(timestamp, "%Y-%m-%dT%H:%M:%S%:z").is_ok(),
+            "{output:?}"
+        );
+        assert_eq!(
+            rest,
+            format!(
+                "WARN  [zlog.color_test] ({}) plain output\n",
+                std::thread::current().name().unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_routing_sink() {
+        let rpc_records = CaptureSink::default();
+        let default_records = CaptureSink::default();
+        let sink = RoutingSink::new(Box::new(default_records.clone()))
+            .route("rpc", Box::new(rpc_records.clone()));
+
+        for (scope, message) in [
+            (crate::private::scope_new(&["rpc"]), "rpc"),
+            (crate::private::scope_new(&["rpc", "client"]), "rpc.client"),
+            (crate::private::scope_new(&["rpc_extra"]), "rpc_extra"),
+            (crate::private::scope_new(&["editor", "rpc"]), "editor.rpc"),
+        ] {
+            sink.submit(&Record {
+                scope,
+                level: log::Level::Info,
+                message: &format_args!("{message}"),
+                module_path: None,
+                thread: std::thread::current(),
+            });
+        }
+
+        assert_eq!(rpc_records.messages(), ["rpc", "rpc.client"]);
+        assert_eq!(default_records.messages(), ["rpc_extra", "editor.rpc"]);
+    }
+
+    #[test]
+    fn test_sink_min_level() {
+        let file_records = CaptureSink::default();
+        let terminal_records = CaptureSink::default();
+        let file_sink = MinLevelSink::new(file_records.clone(), log::LevelFilter::Trace);
+        let terminal_sink = RoutingSink::new(Box::new(MinLevelSink::new(
+            terminal_records.clone(),
+            log::LevelFilter::Warn,
+        )));
+
+        for level in [
+            log::Level::Trace,
+            log::Level::Debug,
+            log::Level::Info,
+            log::Level::Warn,
+            log::Level::Error,
+        ] {
+            let record = Record {
+                scope: crate::private::scope_new(&["zlog"]),
+                level,
+                message: &format_args!("{level}"),
+                module_path: None,
+                thread: std::thread::current(),
+            };
+            submit_to_sink(&file_sink, &record);
+            submit_to_sink(&terminal_sink, &record);
+        }
+
+        assert_eq!(
+            file_records.messages(),
+            ["TRACE", "DEBUG", "INFO", "WARN", "ERROR"]
+        );
+        assert_eq!(terminal_records.messages(), ["WARN", "ERROR"]);
+    }
+
+    #[test]
+    fn test_ring_buffer_sink() {
+        let capacity = 8;
+        let sink = RingBufferSink::new(capacity);
+        let reader = sink.clone();
+        for index in 0..capacity + 5 {
+            sink.submit(&Record {
+                scope: crate::private::scope_new(&["zlog"]),
+                level: log::Level::Info,
+                message: &format_args!("record {index}"),
+                module_path: None,
+                thread: std::thread::current(),
+            });
+        }
+
+        let snapshot = reader.snapshot();
+        let messages = snapshot
+            .iter()
+            .map(|line| line.rsplit_once(") ").unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            (5..capacity + 5)
+                .map(|index| format!("record {index}"))
+                .collect::<Vec<_>>()
+        );
+        assert!(snapshot[0].contains(" INFO  [zlog] "), "{snapshot:?}");
+    }
+
+    #[test]
+    fn test_timestamp_format() {
+        let now = chrono::DateTime::parse_from_rfc3339("2024-06-15T12:34:56.789Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let render = |format: TimestampFormat| {
+            TimestampFmt {
+                format: &format,
+                now,
+            }
+            .to_string()
+        };
+
+        assert_eq!(render(TimestampFormat::UnixMillis), "1718454896789");
+        assert_eq!(render(TimestampFormat::Utc), "2024-06-15T12:34:56Z");
+        assert_eq!(
+            render(TimestampFormat::Custom("%Y-%m (%s.%3f)".to_string())),
+            "2024-06 (1718454896.789)"
+        );
+    }
 }
@@ -5,7 +5,12 @@ mod env_config;
 pub mod filter;
 pub mod sink;
 
-pub use sink::{flush, init_output_file, init_output_stderr, init_output_stdout};
+pub use filter::{current_config, set_scope_level};
+pub use sink::{
+    FileSink, JsonSink, LogFlushGuard, MinLevelSink, RingBufferSink, RoutingSink, Sink, StdoutSink,
+    TimestampFormat, flush, init_output_file, init_output_stderr, init_output_stdout,
+    set_color_output, set_sink, set_timestamp_format,
+};
 
 pub const SCOPE_DEPTH_MAX: usize = 4;
 
@@ -16,6 +21,21 @@ pub fn init() {
     }
 }
 
+/// Like [`init`], but also returns a guard that flushes the sinks when dropped.
+/// Bind it to a variable at the top of `main` so that it lives until `main` returns
+/// or unwinds, and the last records buffered by the sinks aren't lost:
+///
+/// ```no_run
+/// let _log_flush_guard = zlog::init_with_flush_guard();
+/// zlog::init_output_stderr();
+/// ```
+///
+/// Destructors don't run on `std::process::exit`, so call [`flush`] before exiting that way.
+pub fn init_with_flush_guard() -> LogFlushGuard {
+    init();
+    LogFlushGuard::new()
+}
+
 pub fn try_init() -> anyhow::Result<()> {
     log::set_logger(&ZLOG)?;
     log::set_max_level(log::LevelFilter::max());
@@ -50,6 +70,26 @@ pub fn process_env() {
     }
 }
 
+/// Re-reads `ZED_LOG` or `RUST_LOG` and replaces the filter set by [`process_env`].
+pub fn reload_env() {
+    let Some(env_config) = get_env_config() else {
+        filter::reload_env_filter(env_config::EnvFilter {
+            level_global: None,
+            directive_names: Vec::new(),
+            directive_levels: Vec::new(),
+        });
+        return;
+    };
+    match env_config::parse(&env_config) {
+        Ok(filter) => {
+            filter::reload_env_filter(filter);
+        }
+        Err(err) => {
+            eprintln!("Failed to parse log filter: {}", err);
+        }
+    }
+}
+
 static ZLOG: Zlog = Zlog {};
 
 pub struct Zlog {}
@@ -83,6 +123,7 @@ impl log::Log for Zlog {
             message: record.args(),
             // PERF(batching): store non-static paths in a cache + leak them and pass static str here
             module_path: record.module_path().or(record.file()),
+            thread: std::thread::current(),
         });
     }
 
@@ -103,6 +144,7 @@ macro_rules! log {
                 level,
                 message: &format_args!($($arg)+),
                 module_path: Some(module_path!()),
+                thread: ::std::thread::current(),
             });
         }
     }
@@ -270,6 +312,21 @@ pub type ScopeAlloc = [String; SCOPE_DEPTH_MAX];
 const SCOPE_STRING_SEP_STR: &str = ".";
 const SCOPE_STRING_SEP_CHAR: char = '.';
 
+/// The components of `scope` up to its first empty one, which is where the scope ends.
+pub fn scope_components(scope: &Scope) -> impl Iterator<Item = &'static str> + '_ {
+    scope
+        .iter()
+        .copied()
+        .take_while(|component| !component.is_empty())
+}
+
+/// Renders `scope` the way sinks print it, e.g. `editor.scroll`.
+pub fn format_scope(scope: &Scope) -> String {
+    scope_components(scope)
+        .collect::<Vec<_>>()
+        .join(SCOPE_STRING_SEP_STR)
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Logger {
     pub scope: Scope,
@@ -293,6 +350,7 @@ impl log::Log for Logger {
             level,
             message: record.args(),
             module_path: record.module_path(),
+            thread: std::thread::current(),
         });
     }
 
@@ -388,4 +446,24 @@ mod tests {
             "my_speedy_crate_"
         );
     }
+
+    #[test]
+    fn test_format_scope() {
+        assert_eq!(format_scope(&private::scope_new(&["zlog"])), "zlog");
+        assert_eq!(
+            format_scope(&private::scope_new(&[
+                "editor",
+                "scroll",
+                "autoscroll",
+                "vertical"
+            ])),
+            "editor.scroll.autoscroll.vertical"
+        );
+        assert_eq!(format_scope(&private::scope_new(&[])), "");
+        assert_eq!(
+            format_scope(&["editor", "", "ignored", ""]),
+            "editor",
+            "components after the first empty one are not part of the scope"
+        );
+    }
 }